@@ -0,0 +1,122 @@
+use {
+    super::compiler::Options,
+    std::{
+        collections::hash_map::DefaultHasher,
+        fs::{read_to_string, write},
+        hash::{Hash, Hasher},
+        path::{Path, PathBuf},
+        str::Lines,
+    },
+};
+
+/// Remembers the content hash of the last successful build so an unchanged
+/// file can reuse its previous output instead of being compiled again.
+pub struct BuildCache<'a> {
+    options: &'a Options,
+    hash: u64,
+}
+
+impl<'a> BuildCache<'a> {
+    pub fn new(options: &'a Options, content: &str) -> Self {
+        let mut hasher: DefaultHasher = DefaultHasher::new();
+
+        content.hash(&mut hasher);
+
+        // Any option that changes the produced file invalidates the cache.
+        format!("{:?}", options.optimization).hash(&mut hasher);
+        options.target_triple.to_string().hash(&mut hasher);
+        options.target_cpu.hash(&mut hasher);
+        options.target_features.hash(&mut hasher);
+        options.data_layout.hash(&mut hasher);
+        format!("{:?}", options.reloc_mode).hash(&mut hasher);
+        format!("{:?}", options.code_model).hash(&mut hasher);
+        format!("{:?}", options.linking).hash(&mut hasher);
+        format!("{:?}", options.abort()).hash(&mut hasher);
+        options.emit_llvm.hash(&mut hasher);
+        options.emit_assembly.hash(&mut hasher);
+        options.frame_pointer.hash(&mut hasher);
+        format!("{:?}", options.float_format).hash(&mut hasher);
+        options.sanitizers.hash(&mut hasher);
+        options.clang_path.hash(&mut hasher);
+        options.emit_object.hash(&mut hasher);
+        options.build.hash(&mut hasher);
+        options.strip.hash(&mut hasher);
+        options.mangle.hash(&mut hasher);
+
+        Self {
+            options,
+            hash: hasher.finish(),
+        }
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        // The dump modes and the interpreter need the module, so they always go through codegen.
+        if self.options.force
+            || self.options.interpret
+            || self.options.dump_tokens
+            || self.options.dump_deps
+            || self.options.dump_used_libc
+            || self.options.dump_cfg
+            || self.options.dump_metrics
+            || self.options.print_ir_after_pass
+            || self.options.stats
+            || !self.options.output_path().exists()
+        {
+            return false;
+        }
+
+        let Ok(sidecar) = read_to_string(self.sidecar_path()) else {
+            return false;
+        };
+
+        let mut lines: Lines<'_> = sidecar.lines();
+
+        if lines.next() != Some(format!("{:016x}", self.hash).as_str()) {
+            return false;
+        }
+
+        // Every included file is listed after the main hash as '{hash} {path}'.
+        lines.all(|line| match line.split_once(' ') {
+            Some((hash, path)) => Some(hash.to_string()) == hash_file(Path::new(path)),
+            None => false,
+        })
+    }
+
+    pub fn save(&self, includes: &[PathBuf]) {
+        let mut sidecar: String = format!("{:016x}\n", self.hash);
+
+        includes.iter().for_each(|include| {
+            if let Some(hash) = hash_file(include) {
+                sidecar.push_str(&format!("{} {}\n", hash, include.display()));
+            }
+        });
+
+        let _ = write(self.sidecar_path(), sidecar);
+    }
+
+    /// The files included by the cached build, as listed in the sidecar.
+    pub fn includes(&self) -> Vec<PathBuf> {
+        read_to_string(self.sidecar_path())
+            .map(|sidecar| {
+                sidecar
+                    .lines()
+                    .skip(1)
+                    .filter_map(|line| line.split_once(' '))
+                    .map(|(_, path)| PathBuf::from(path))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn sidecar_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.thcache", self.options.name))
+    }
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    let mut hasher: DefaultHasher = DefaultHasher::new();
+
+    read_to_string(path).ok()?.hash(&mut hasher);
+
+    Some(format!("{:016x}", hasher.finish()))
+}
@@ -1,31 +1,51 @@
 use {
     super::{
-        super::{frontend::lexer::DataTypes, logging},
+        super::{
+            diagnostic::Diagnostic,
+            error::{ThrushError, ThrushErrorKind},
+            frontend::lexer::{DataTypes, TokenKind},
+            logging, PATH,
+        },
         llvm::{
-            build_alloca_with_float, build_alloca_with_integer, build_const_float,
-            build_const_integer, build_int_array_type_from_size, datatype_float_to_type,
-            datatype_integer_to_type, datatype_to_fn_type, set_globals_options,
+            append_module_asm, build_alloca_with_float, build_alloca_with_integer,
+            build_const_float, build_const_integer, build_default_value,
+            build_int_array_type_from_size, datatype_alignment, datatype_float_to_type,
+            datatype_integer_to_type, datatype_to_basic_type_enum,
+            datatype_to_basicmetadata_type_enum, datatype_to_fn_type, mangle_symbol, printf_spec,
+            set_symbol_visibility,
         },
-        objects::ThrushBasicValueEnum,
+        objects::{ThrushBasicValueEnum, ThrushLocal},
     },
     inkwell::{
+        attributes::{Attribute, AttributeLoc},
         basic_block::BasicBlock,
         builder::Builder,
         context::Context,
-        module::{Linkage, Module},
-        targets::{CodeModel, RelocMode, TargetMachine, TargetTriple},
-        types::{ArrayType, FloatType, FunctionType, IntType, VectorType},
+        debug_info::{
+            AsDIScope, DICompileUnit, DIFlags, DIFlagsConstants, DILocation, DISubprogram,
+            DISubroutineType, DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+        },
+        intrinsics::Intrinsic,
+        module::{FlagBehavior, Linkage, Module},
+        targets::{CodeModel, FileType, RelocMode, TargetData, TargetMachine, TargetTriple},
+        types::{
+            ArrayType, BasicMetadataTypeEnum, BasicTypeEnum, FloatType, FunctionType, IntType,
+            PointerType,
+        },
         values::{
-            BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue, GlobalValue,
-            InstructionValue, IntValue, PointerValue,
+            ArrayValue, BasicMetadataValueEnum, BasicValue, BasicValueEnum, CallSiteValue,
+            FloatValue, FunctionValue, GlobalValue, InstructionValue, IntValue, PhiValue,
+            PointerValue,
         },
-        AddressSpace, GlobalVisibility,
+        AddressSpace, AtomicOrdering, DLLStorageClass, FloatPredicate, GlobalVisibility,
+        IntPredicate,
     },
     std::{
-        collections::HashMap,
+        collections::{BTreeMap, HashSet},
+        env,
         fs::remove_file,
         path::{Path, PathBuf},
-        process::Command,
+        process::{Command, Output},
     },
 };
 
@@ -35,9 +55,19 @@ pub struct Compiler<'a, 'ctx> {
     context: &'ctx Context,
     instructions: &'ctx [Instruction<'ctx>],
     current: usize,
-    globals: HashMap<&'a str, Instruction<'ctx>>,
-    locals: Vec<HashMap<&'a str, Instruction<'ctx>>>,
+    globals: BTreeMap<&'a str, Instruction<'ctx>>,
+    locals: Vec<BTreeMap<&'a str, Instruction<'ctx>>>,
+    deferred: Vec<Vec<&'ctx Instruction<'ctx>>>,
+    loops: Vec<(Option<&'ctx str>, BasicBlock<'ctx>, usize)>,
+    mutated: HashSet<&'ctx str>,
     scope: usize,
+    errors: Vec<ThrushError>,
+    diagnostics: Diagnostic,
+    abort: Abort,
+    frame_pointer: bool,
+    float_format: FloatFormat,
+    mangle: Option<String>,
+    source_map: Option<(DebugInfoBuilder<'ctx>, DICompileUnit<'ctx>)>,
 }
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
@@ -46,39 +76,287 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         builder: &'a Builder<'ctx>,
         context: &'ctx Context,
         instructions: &'ctx [Instruction<'ctx>],
-    ) {
+        abort: Abort,
+        frame_pointer: bool,
+        float_format: FloatFormat,
+        mangle: bool,
+        source_map: bool,
+    ) -> Result<(), ThrushError> {
+        // Private functions are mangled with the name of their source file.
+        let mangle: Option<String> = mangle.then(|| {
+            Path::new(PATH.lock().unwrap().as_str())
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        });
+
+        // Only line tables, enough to map the emitted IR back to the source.
+        let source_map: Option<(DebugInfoBuilder<'ctx>, DICompileUnit<'ctx>)> =
+            source_map.then(|| {
+                let path: PathBuf = PathBuf::from(PATH.lock().unwrap().as_str());
+
+                module.add_basic_value_flag(
+                    "Debug Info Version",
+                    FlagBehavior::Warning,
+                    context.i32_type().const_int(3, false),
+                );
+
+                module.create_debug_info_builder(
+                    true,
+                    DWARFSourceLanguage::C,
+                    &path.file_name().unwrap_or_default().to_string_lossy(),
+                    &path.parent().unwrap_or(Path::new("")).to_string_lossy(),
+                    "thrushc",
+                    false,
+                    "",
+                    0,
+                    "",
+                    DWARFEmissionKind::LineTablesOnly,
+                    0,
+                    false,
+                    false,
+                    "",
+                    "",
+                )
+            });
+
+        let mut mutated: HashSet<&'ctx str> = HashSet::new();
+
+        instructions
+            .iter()
+            .for_each(|instr| Self::collect_mutated(instr, &mut mutated));
+
         Self {
             module,
             builder,
             context,
             instructions,
             current: 0,
-            globals: HashMap::new(),
-            locals: vec![HashMap::new()],
+            globals: BTreeMap::new(),
+            locals: vec![BTreeMap::new()],
+            deferred: vec![Vec::new()],
+            loops: Vec::new(),
+            mutated,
             scope: 0,
+            errors: Vec::with_capacity(10),
+            diagnostics: Diagnostic::new(&PATH.lock().unwrap()),
+            abort,
+            frame_pointer,
+            float_format,
+            mangle,
+            source_map,
         }
-        .start();
+        .start()
     }
 
-    fn start(&mut self) {
-        while !self.is_end() {
-            let instr: &Instruction<'_> = self.advance();
-            self.codegen(instr);
+    /// Every error but the last is reported here, the last one is returned so the caller
+    /// reports it after them and stops.
+    fn start(&mut self) -> Result<(), ThrushError> {
+        // A second 'main' would only fail at link time, so check it before codegen.
+        self.check_entry_points();
+
+        if self.errors.is_empty() {
+            while !self.is_end() {
+                let instr: &Instruction<'_> = self.advance();
+                self.codegen(instr);
+            }
+        }
+
+        if let Some((dibuilder, _)) = &self.source_map {
+            dibuilder.finalize();
+        }
+
+        match self.errors.pop() {
+            Some(last) => {
+                self.errors.iter().for_each(|error| {
+                    self.diagnostics.report(error);
+                });
+
+                Err(last)
+            }
+
+            None => Ok(()),
+        }
+    }
+
+    fn check_entry_points(&mut self) {
+        let count: usize = self
+            .instructions
+            .iter()
+            .filter(|instr| match instr {
+                Instruction::EntryPoint { .. } => true,
+                Instruction::Function { name, .. } => *name == "main",
+                _ => false,
+            })
+            .count();
+
+        if count > 1 {
+            self.errors.push(ThrushError::Compile(format!(
+                "Multiple entry points defined, 'main' is declared {} times in this file.",
+                count
+            )));
+        }
+    }
+
+    /// The names assigned or loaded atomically anywhere in the program. Shadowing is
+    /// ignored, so a name assigned in one scope counts as mutated in all of them.
+    fn collect_mutated(instr: &'ctx Instruction<'ctx>, mutated: &mut HashSet<&'ctx str>) {
+        let mut collect = |instr: &'ctx Instruction<'ctx>| Self::collect_mutated(instr, mutated);
+
+        match instr {
+            Instruction::MutVar { name, value, .. } => {
+                mutated.insert(*name);
+                Self::collect_mutated(value, mutated);
+            }
+
+            // Another thread may write the variable, so its value can't be propagated.
+            Instruction::RefVar {
+                name,
+                ordering: Some(_),
+                ..
+            } => {
+                mutated.insert(*name);
+            }
+
+            Instruction::Block { stmts: instrs }
+            | Instruction::Println(instrs)
+            | Instruction::Print(instrs)
+            | Instruction::EPrintln(instrs)
+            | Instruction::EPrint(instrs)
+            | Instruction::Call { args: instrs, .. }
+            | Instruction::InlineAsm {
+                operands: instrs, ..
+            }
+            | Instruction::Array {
+                elements: instrs, ..
+            } => instrs.iter().for_each(collect),
+
+            Instruction::EntryPoint { body: instr }
+            | Instruction::Function { body: instr, .. }
+            | Instruction::Return(instr)
+            | Instruction::Var {
+                value: Some(instr), ..
+            }
+            | Instruction::ArgAt(instr)
+            | Instruction::ToString(instr)
+            | Instruction::BitCast { value: instr, .. }
+            | Instruction::Cast { value: instr, .. }
+            | Instruction::Defer(instr)
+            | Instruction::Loop { body: instr, .. }
+            | Instruction::Unary { operand: instr, .. }
+            | Instruction::Index { index: instr, .. } => collect(instr),
+
+            Instruction::While {
+                condition: left,
+                body: right,
+                ..
+            }
+            | Instruction::BinaryOp { left, right, .. }
+            | Instruction::Compare { left, right, .. }
+            | Instruction::LogicalAnd { left, right }
+            | Instruction::LogicalOr { left, right } => {
+                collect(left);
+                collect(right);
+            }
+
+            Instruction::If {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                collect(condition);
+                collect(then_block);
+
+                if let Some(else_block) = else_block {
+                    collect(else_block);
+                }
+            }
+
+            _ => {}
         }
     }
 
     fn codegen(&mut self, instr: &'ctx Instruction<'ctx>) {
         match instr {
             Instruction::Block { stmts, .. } => {
-                self.scope += 1;
-                self.locals.push(HashMap::new());
+                self.begin_scope();
+
+                // A folded condition can return early, what follows it is never reached.
+                for instr in stmts.iter() {
+                    if self.is_terminated() {
+                        break;
+                    }
 
-                stmts.iter().for_each(|instr| {
                     self.codegen(instr);
-                });
+                }
+
+                self.end_scope();
+            }
+
+            Instruction::Defer(stmt) => {
+                self.deferred[self.scope].push(stmt);
+            }
+
+            Instruction::ConstArray {
+                name,
+                kind,
+                values,
+                visibility,
+            } => {
+                self.emit_array_constant(name, kind, values, *visibility);
+            }
 
-                self.scope -= 1;
-                self.locals.pop();
+            Instruction::InlineAsm {
+                template,
+                constraints,
+                operands,
+                side_effects,
+            } => {
+                self.emit_inline_asm(template, constraints, operands, *side_effects);
+            }
+
+            Instruction::If {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                self.emit_if(condition, then_block, else_block.as_deref());
+            }
+
+            Instruction::While {
+                condition,
+                body,
+                label,
+            } => {
+                self.emit_while(condition, body, *label);
+            }
+
+            Instruction::Loop { body, label } => {
+                self.emit_loop(body, *label);
+            }
+
+            Instruction::Break(label) => {
+                self.emit_break(*label);
+            }
+
+            Instruction::Fence(ordering) => {
+                self.builder.build_fence(*ordering, 0, "").unwrap();
+            }
+
+            Instruction::ModuleAsm(asm) => {
+                append_module_asm(self.module, asm);
+            }
+
+            Instruction::Call {
+                name,
+                args,
+                line,
+                inline,
+                ..
+            } => {
+                self.set_source_line(*line);
+                self.emit_call(name, args, *line, *inline);
             }
 
             Instruction::Function {
@@ -87,8 +365,20 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
                 body,
                 return_kind,
                 is_public,
+                visibility,
+                export,
+                naked,
             } => {
-                self.emit_function(name, params, body, return_kind, *is_public);
+                self.emit_function(
+                    name,
+                    params,
+                    body,
+                    return_kind,
+                    *is_public,
+                    *visibility,
+                    *export,
+                    *naked,
+                );
             }
 
             Instruction::Return(instr) => {
@@ -99,30 +389,79 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
                 self.emit_global_string_constant(string);
             }
 
-            Instruction::Println(data) | Instruction::Print(data) => {
-                if self.module.get_function("printf").is_none() {
-                    self.define_printf();
-                }
+            Instruction::Println(data) => {
+                self.emit_print(data, true, false);
+            }
+
+            Instruction::Print(data) => {
+                self.emit_print(data, false, false);
+            }
+
+            Instruction::EPrintln(data) => {
+                self.emit_print(data, true, true);
+            }
 
-                self.emit_print(data);
+            Instruction::EPrint(data) => {
+                self.emit_print(data, false, true);
             }
 
             Instruction::Var {
-                name, kind, value, ..
-            } => match value {
-                Some(value) => {
-                    self.emit_variable(name, kind, value);
-                }
-                None => self.emit_variable(name, kind, &Instruction::Null),
-            },
+                name,
+                kind,
+                value,
+                volatile,
+                is_static: true,
+                ..
+            } => self.emit_static_variable(
+                name,
+                kind,
+                value.as_deref().unwrap_or(&Instruction::Null),
+                *volatile,
+            ),
+
+            Instruction::Var {
+                name,
+                kind,
+                value,
+                line,
+                visibility,
+                thread_local,
+                volatile,
+                ..
+            } => {
+                self.set_source_line(*line);
+                self.emit_variable(
+                    name,
+                    kind,
+                    value.as_deref().unwrap_or(&Instruction::Null),
+                    *line,
+                    *visibility,
+                    *thread_local,
+                    *volatile,
+                );
+            }
+
+            Instruction::MutVar {
+                name,
+                value,
+                kind,
+                line,
+                ordering,
+            } => {
+                self.set_source_line(*line);
+                self.emit_mutate_variable(name, value, kind, *ordering, *line);
+            }
 
             Instruction::EntryPoint { body } => {
                 self.emit_main();
                 self.codegen(body);
-                self.build_const_integer_return(self.context.i32_type(), 0, false);
+
+                if !self.is_terminated() {
+                    self.build_const_integer_return(self.context.i32_type(), 0, false);
+                }
             }
 
-            _ => todo!(),
+            instr => self.unsupported(instr, None),
         }
     }
 
@@ -135,667 +474,4771 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
             .add_function("printf", printf, Some(Linkage::External));
     }
 
+    /// 'stderr' is the global 'FILE *' of the C library, declared next to 'fprintf'.
+    fn define_fprintf(&mut self) {
+        let fprintf: FunctionType = self.context.i32_type().fn_type(
+            &[
+                self.context.ptr_type(AddressSpace::default()).into(),
+                self.context.ptr_type(AddressSpace::default()).into(),
+            ],
+            true,
+        );
+        self.module
+            .add_function("fprintf", fprintf, Some(Linkage::External));
+
+        let stderr: GlobalValue<'ctx> = self.module.add_global(
+            self.context.ptr_type(AddressSpace::default()),
+            Some(AddressSpace::default()),
+            "stderr",
+        );
+
+        stderr.set_linkage(Linkage::External);
+    }
+
+    fn define_getenv(&self) {
+        let getenv: FunctionType = self.context.ptr_type(AddressSpace::default()).fn_type(
+            &[self.context.ptr_type(AddressSpace::default()).into()],
+            false,
+        );
+        self.module
+            .add_function("getenv", getenv, Some(Linkage::External));
+    }
+
+    fn define_snprintf(&self) {
+        let snprintf: FunctionType = self.context.i32_type().fn_type(
+            &[
+                self.context.ptr_type(AddressSpace::default()).into(),
+                self.context.i64_type().into(),
+                self.context.ptr_type(AddressSpace::default()).into(),
+            ],
+            true,
+        );
+        self.module
+            .add_function("snprintf", snprintf, Some(Linkage::External));
+    }
+
+    fn define_abort(&self, name: &str) {
+        let abort: FunctionType = self.context.void_type().fn_type(&[], false);
+        self.module
+            .add_function(name, abort, Some(Linkage::External));
+    }
+
     fn emit_main(&mut self) {
-        let main_kind: FunctionType = self.context.i32_type().fn_type(&[], false);
+        let main_kind: FunctionType = self.context.i32_type().fn_type(
+            &[
+                self.context.i32_type().into(),
+                self.context.ptr_type(AddressSpace::default()).into(),
+            ],
+            false,
+        );
         let main: FunctionValue = self.module.add_function("main", main_kind, None);
 
+        main.get_nth_param(0).unwrap().set_name("argc");
+        main.get_nth_param(1).unwrap().set_name("argv");
+
+        self.set_frame_pointer(main);
+        self.set_subprogram(main);
+
         let entry_point: BasicBlock = self.context.append_basic_block(main, "");
 
         self.builder.position_at_end(entry_point);
     }
 
-    fn emit_print(&mut self, instrs: &[Instruction]) {
-        let mut args: Vec<BasicMetadataValueEnum> = Vec::with_capacity(24);
+    /// With `stderr` the format is written by 'fprintf' to the standard error instead of 'printf'.
+    fn emit_print(
+        &mut self,
+        instrs: &'ctx [Instruction<'ctx>],
+        newline: bool,
+        stderr: bool,
+    ) -> IntValue<'ctx> {
+        let mut args: Vec<BasicMetadataValueEnum> = Vec::with_capacity(25);
 
-        instrs.iter().for_each(|instr| match instr {
-            Instruction::String(string) => {
-                let kind: ArrayType<'_> = build_int_array_type_from_size(
-                    self.context,
-                    DataTypes::I8,
-                    string.len() as u32,
-                );
+        let printer: &str = if stderr {
+            if self.module.get_function("fprintf").is_none() {
+                self.define_fprintf();
+            }
 
-                let global: GlobalValue<'ctx> =
-                    self.module
-                        .add_global(kind, Some(AddressSpace::default()), "");
+            let stream: BasicValueEnum<'ctx> = self
+                .builder
+                .build_load(
+                    self.context.ptr_type(AddressSpace::default()),
+                    self.module.get_global("stderr").unwrap().as_pointer_value(),
+                    "",
+                )
+                .unwrap();
 
-                set_globals_options(self.context, global, Some(instr));
+            args.push(stream.into());
 
-                args.push(
-                    self.builder
-                        .build_pointer_cast(
-                            global.as_pointer_value(),
-                            self.context.ptr_type(AddressSpace::default()),
-                            "",
-                        )
-                        .unwrap()
-                        .into(),
-                );
+            "fprintf"
+        } else {
+            if self.module.get_function("printf").is_none() {
+                self.define_printf();
             }
 
-            Instruction::Integer(kind, num) => {
-                args.push(build_const_integer(self.context, kind, *num).into());
-            }
-
-            Instruction::RefVar { name, kind, .. } => match kind {
-                DataTypes::F32
-                | DataTypes::F64
-                | DataTypes::I8
-                | DataTypes::I16
-                | DataTypes::I32
-                | DataTypes::I64
-                | DataTypes::U8
-                | DataTypes::U16
-                | DataTypes::U32
-                | DataTypes::U64 => {
-                    if let Instruction::Value(pointer) = self.get_local(name) {
-                        args.push(pointer.value.into());
-                    }
-                }
-                DataTypes::String | DataTypes::Bool => {
-                    if let Instruction::Value(pointer) = self.get_global(name) {
-                        match pointer.kind {
-                            DataTypes::String => match pointer.value {
-                                BasicValueEnum::PointerValue(vector) => {
-                                    args.push(vector.into());
-                                }
+            "printf"
+        };
 
-                                _ => todo!(),
-                            },
-                            DataTypes::Bool => {
-                                args.push(pointer.value.into());
-                            }
+        // String literals are copied into the format, every other argument gets the
+        // conversion of its type. Consecutive values are separated by a space.
+        let mut fmt: String = String::new();
+        let mut after_value: bool = false;
 
-                            _ => todo!(),
-                        }
-                    }
-                }
+        instrs.iter().for_each(|instr| match instr {
+            Instruction::String(string) => {
+                fmt.push_str(&string.trim_end_matches('\0').replace('%', "%%"));
+                after_value = false;
+            }
 
-                e => {
-                    println!("{e}")
+            instr => {
+                if after_value {
+                    fmt.push(' ');
                 }
-            },
 
-            _ => todo!(),
+                fmt.push_str(self.print_spec(instr));
+                after_value = true;
+            }
         });
 
-        self.builder
-            .build_call(self.module.get_function("printf").unwrap(), &args, "")
-            .unwrap();
-    }
-
-    fn emit_variable(&mut self, name: &'a str, kind: &DataTypes, value: &Instruction) {
-        let instr: Instruction<'ctx> = match kind {
-            DataTypes::I8
-            | DataTypes::I16
-            | DataTypes::I32
-            | DataTypes::I64
-            | DataTypes::U8
-            | DataTypes::U16
-            | DataTypes::U32
-            | DataTypes::U64 => {
-                let ptr_kind: IntType<'_> = datatype_integer_to_type(self.context, kind);
+        if newline {
+            fmt.push('\n');
+        }
 
-                let ptr: PointerValue<'_> = match kind {
-                    DataTypes::I8 => build_alloca_with_integer(
-                        self.builder,
-                        datatype_integer_to_type(self.context, kind),
-                    ),
+        fmt.push('\0');
 
-                    DataTypes::I16 => build_alloca_with_integer(
-                        self.builder,
-                        datatype_integer_to_type(self.context, kind),
-                    ),
+        args.push(self.emit_global_string_constant(&fmt).into());
 
-                    DataTypes::I32 => build_alloca_with_integer(
-                        self.builder,
-                        datatype_integer_to_type(self.context, kind),
-                    ),
+        for instr in instrs.iter() {
+            match instr {
+                Instruction::String(_) => {}
 
-                    DataTypes::I64 => build_alloca_with_integer(
-                        self.builder,
-                        datatype_integer_to_type(self.context, kind),
-                    ),
+                Instruction::Integer(kind, num) => {
+                    let value: BasicValueEnum<'ctx> =
+                        build_const_integer(self.context, kind, *num).into();
 
-                    DataTypes::U8 => build_alloca_with_integer(
-                        self.builder,
-                        datatype_integer_to_type(self.context, kind),
-                    ),
+                    args.push(self.promote_vararg(value, kind));
+                }
 
-                    DataTypes::U16 => build_alloca_with_integer(
-                        self.builder,
-                        datatype_integer_to_type(self.context, kind),
-                    ),
+                Instruction::Float(_, num) => {
+                    args.push(self.context.f64_type().const_float(*num).into());
+                }
 
-                    DataTypes::U32 => build_alloca_with_integer(
-                        self.builder,
-                        datatype_integer_to_type(self.context, kind),
-                    ),
+                Instruction::Char(_) => {
+                    let value: BasicValueEnum<'ctx> = self.emit_numeric(instr);
 
-                    DataTypes::U64 => build_alloca_with_integer(
-                        self.builder,
-                        datatype_integer_to_type(self.context, kind),
-                    ),
+                    args.push(self.promote_vararg(value, &DataTypes::Char));
+                }
 
-                    _ => todo!(),
-                };
+                Instruction::Boolean(_)
+                | Instruction::RefVar {
+                    kind: DataTypes::Bool,
+                    ..
+                } => {
+                    let value: BasicValueEnum<'ctx> = self.emit_condition(instr).into();
 
-                match value {
-                    Instruction::Null => {
-                        let store: InstructionValue<'_> = self
-                            .builder
-                            .build_store(ptr, build_const_integer(self.context, kind, 0.0))
-                            .unwrap();
+                    args.push(self.promote_vararg(value, &DataTypes::Bool));
+                }
 
-                        store.set_alignment(4).unwrap();
-                    }
+                Instruction::RefVar {
+                    name,
+                    kind,
+                    line,
+                    ordering,
+                } => match kind {
+                    DataTypes::F16
+                    | DataTypes::BF16
+                    | DataTypes::F32
+                    | DataTypes::F64
+                    | DataTypes::I8
+                    | DataTypes::I16
+                    | DataTypes::I32
+                    | DataTypes::I64
+                    | DataTypes::U8
+                    | DataTypes::U16
+                    | DataTypes::U32
+                    | DataTypes::U64
+                    | DataTypes::Char => match self.load_local(name, *ordering, *line) {
+                        Ok(value) => args.push(self.promote_vararg(value, kind)),
+                        Err(error) => self.errors.push(error),
+                    },
+                    DataTypes::String => match self.get_global(name, *line) {
+                        Ok(Instruction::Value(pointer)) => match pointer.kind {
+                            DataTypes::String => match pointer.value {
+                                BasicValueEnum::PointerValue(vector) => {
+                                    args.push(vector.into());
+                                }
 
-                    Instruction::Integer(kind, num) => match kind {
-                        DataTypes::I8
-                        | DataTypes::I16
-                        | DataTypes::I32
-                        | DataTypes::I64
-                        | DataTypes::U8
-                        | DataTypes::U16
-                        | DataTypes::U32
-                        | DataTypes::U64 => {
-                            let store: InstructionValue<'_> = self
-                                .builder
-                                .build_store(ptr, build_const_integer(self.context, kind, *num))
-                                .unwrap();
+                                _ => self.unsupported(instr, Some(kind)),
+                            },
 
-                            store.set_alignment(4).unwrap();
-                        }
+                            _ => self.unsupported(instr, Some(kind)),
+                        },
 
-                        _ => todo!(),
+                        Ok(_) => self.unsupported(instr, Some(kind)),
+                        Err(error) => self.errors.push(error),
                     },
 
-                    _ => unreachable!(),
-                }
-
-                let load: BasicValueEnum<'ctx> =
-                    self.builder.build_load(ptr_kind, ptr, "").unwrap();
+                    kind => {
+                        self.errors.push(ThrushError::Compile(format!(
+                            "Cannot print the variable `{}` of type '{}' at line {}.",
+                            name, kind, line
+                        )));
 
-                load.as_instruction_value()
-                    .unwrap()
-                    .set_alignment(4)
-                    .unwrap();
+                        return self.context.i32_type().const_zero();
+                    }
+                },
 
-                Instruction::Value(ThrushBasicValueEnum {
-                    kind: kind.defer(),
-                    value: load,
-                })
-            }
+                Instruction::ArgCount => {
+                    args.push(self.emit_argc().into());
+                }
 
-            DataTypes::F32 | DataTypes::F64 => {
-                let ptr_kind: FloatType<'_> = datatype_float_to_type(self.context, kind);
+                Instruction::ArgAt(index) => {
+                    args.push(self.emit_argv(index).into());
+                }
 
-                let ptr: PointerValue<'_> = match kind {
-                    DataTypes::F32 => build_alloca_with_float(
-                        self.builder,
-                        datatype_float_to_type(self.context, kind),
-                    ),
+                Instruction::ToString(value) => {
+                    args.push(self.emit_to_string(value).into());
+                }
 
-                    DataTypes::F64 => build_alloca_with_float(
-                        self.builder,
-                        datatype_float_to_type(self.context, kind),
-                    ),
+                Instruction::BinaryOp { kind, .. }
+                | Instruction::Unary { kind, .. }
+                | Instruction::Call { kind, .. }
+                | Instruction::Cast { kind, .. }
+                | Instruction::Index { kind, .. } => {
+                    let value: BasicValueEnum<'ctx> = self.emit_numeric(instr);
 
-                    _ => unreachable!(),
-                };
+                    args.push(self.promote_vararg(value, kind));
+                }
 
-                match value {
-                    Instruction::Null => {
-                        let store: InstructionValue<'_> = self
-                            .builder
-                            .build_store(ptr, build_const_float(self.context, kind, 0.0))
-                            .unwrap();
+                Instruction::BitCast { value, kind } => {
+                    let value: BasicValueEnum<'ctx> = self.emit_bitcast(value, kind);
 
-                        store.set_alignment(4).unwrap();
-                    }
+                    args.push(self.promote_vararg(value, kind));
+                }
 
-                    Instruction::Integer(kind, num) => match kind {
-                        DataTypes::F32 | DataTypes::F64 => {
-                            let store: InstructionValue<'_> = self
-                                .builder
-                                .build_store(ptr, build_const_float(self.context, kind, *num))
-                                .unwrap();
+                Instruction::GetEnv(name) => {
+                    let value: PointerValue<'ctx> = self.emit_getenv(name);
 
-                            store.set_alignment(4).unwrap();
-                        }
+                    // Unset variables are printed as an empty string.
+                    let is_null: IntValue<'ctx> = self.builder.build_is_null(value, "").unwrap();
+                    let empty: PointerValue<'ctx> = self.emit_global_string_constant("\0");
 
-                        _ => todo!(),
-                    },
+                    args.push(
+                        self.builder
+                            .build_select(is_null, empty, value, "")
+                            .unwrap()
+                            .into(),
+                    );
+                }
 
-                    _ => unreachable!(),
+                instr => {
+                    self.unsupported(instr, None);
+                    return self.context.i32_type().const_zero();
                 }
+            }
+        }
 
-                let load: BasicValueEnum<'ctx> =
-                    self.builder.build_load(ptr_kind, ptr, "").unwrap();
+        self.builder
+            .build_call(self.module.get_function(printer).unwrap(), &args, "")
+            .unwrap()
+            .try_as_basic_value()
+            .unwrap_left()
+            .into_int_value()
+    }
 
-                load.as_instruction_value()
-                    .unwrap()
-                    .set_alignment(4)
-                    .unwrap();
+    /// The default argument promotions of C for a value passed to a variadic function:
+    /// floats narrower than a 'double' are extended to one, and integers narrower than an
+    /// 'int' are extended to an 'int' by their sign. Like C, a boolean becomes the 'int' '0' or '1'.
+    fn promote_vararg(
+        &self,
+        value: BasicValueEnum<'ctx>,
+        kind: &DataTypes,
+    ) -> BasicMetadataValueEnum<'ctx> {
+        match kind {
+            DataTypes::F16 | DataTypes::BF16 | DataTypes::F32 => self
+                .builder
+                .build_float_ext(value.into_float_value(), self.context.f64_type(), "")
+                .unwrap()
+                .into(),
+            DataTypes::I8 | DataTypes::I16 => self
+                .builder
+                .build_int_s_extend(value.into_int_value(), self.context.i32_type(), "")
+                .unwrap()
+                .into(),
+            DataTypes::U8 | DataTypes::U16 | DataTypes::Char | DataTypes::Bool => self
+                .builder
+                .build_int_z_extend(value.into_int_value(), self.context.i32_type(), "")
+                .unwrap()
+                .into(),
+            _ => value.into(),
+        }
+    }
 
-                Instruction::Value(ThrushBasicValueEnum {
-                    kind: kind.defer(),
-                    value: load,
-                })
+    fn print_spec(&self, instr: &Instruction) -> &'static str {
+        match instr {
+            // Reported when the arguments are emitted.
+            Instruction::RefVar {
+                kind: DataTypes::Array(..),
+                ..
+            } => "",
+
+            Instruction::Float(kind, _)
+            | Instruction::RefVar { kind, .. }
+            | Instruction::BitCast { kind, .. }
+            | Instruction::BinaryOp { kind, .. }
+            | Instruction::Unary { kind, .. }
+            | Instruction::Call { kind, .. }
+            | Instruction::Cast { kind, .. }
+            | Instruction::Index { kind, .. }
+                if kind.is_float() =>
+            {
+                self.float_format.spec()
             }
 
-            DataTypes::String => match value {
-                Instruction::Null => Instruction::Value(ThrushBasicValueEnum {
-                    kind: DataTypes::String,
-                    value: self.emit_global_string("\0", name).into(),
-                }),
+            Instruction::Integer(kind, _)
+            | Instruction::Float(kind, _)
+            | Instruction::RefVar { kind, .. }
+            | Instruction::BitCast { kind, .. }
+            | Instruction::BinaryOp { kind, .. }
+            | Instruction::Unary { kind, .. }
+            | Instruction::Call { kind, .. }
+            | Instruction::Cast { kind, .. }
+            | Instruction::Index { kind, .. } => printf_spec(kind),
+            Instruction::ArgCount => printf_spec(&DataTypes::I32),
+            Instruction::String(_)
+            | Instruction::ArgAt(_)
+            | Instruction::GetEnv(_)
+            | Instruction::ToString(_) => printf_spec(&DataTypes::String),
+            Instruction::Boolean(_) => printf_spec(&DataTypes::Bool),
+            Instruction::Char(_) => printf_spec(&DataTypes::Char),
 
-                Instruction::String(string) => Instruction::Value(ThrushBasicValueEnum {
-                    kind: DataTypes::String,
-                    value: self.emit_global_string(string, name).into(),
-                }),
+            // Reported when the arguments are emitted.
+            _ => "",
+        }
+    }
 
-                _ => unreachable!(),
-            },
+    fn emit_argc(&self) -> IntValue<'ctx> {
+        self.module
+            .get_function("main")
+            .unwrap()
+            .get_nth_param(0)
+            .unwrap()
+            .into_int_value()
+    }
 
-            DataTypes::Bool => match value {
-                Instruction::Boolean(bool) => Instruction::Value(ThrushBasicValueEnum {
-                    kind: DataTypes::Bool,
-                    value: self.emit_global_boolean(*bool).into(),
-                }),
+    fn emit_argv(&mut self, index: &'ctx Instruction<'ctx>) -> PointerValue<'ctx> {
+        let main: FunctionValue<'ctx> = self.module.get_function("main").unwrap();
+        let argv: PointerValue<'ctx> = main.get_nth_param(1).unwrap().into_pointer_value();
 
-                _ => unimplemented!(),
-            },
+        let value: IntValue<'ctx> = self.emit_numeric(index).into_int_value();
 
-            _ => todo!(),
-        };
+        let index: IntValue<'ctx> = self
+            .builder
+            .build_int_cast_sign_flag(
+                value,
+                self.context.i64_type(),
+                index.get_data_type().is_signed(),
+                "",
+            )
+            .unwrap();
 
-        if let Instruction::Value(instr) = instr {
-            match instr.kind {
-                DataTypes::F32
-                | DataTypes::F64
-                | DataTypes::I8
-                | DataTypes::I16
-                | DataTypes::I32
-                | DataTypes::I64
-                | DataTypes::U8
-                | DataTypes::U16
-                | DataTypes::U32
-                | DataTypes::U64 => {
-                    self.locals[self.scope - 1].insert(name, Instruction::Value(instr))
-                }
-                _ => self.globals.insert(name, Instruction::Value(instr)),
-            };
-        }
-    }
+        let argc: IntValue<'ctx> = self
+            .builder
+            .build_int_s_extend(self.emit_argc(), self.context.i64_type(), "")
+            .unwrap();
 
-    fn emit_return(&mut self, instr: &Instruction) {
-        match &instr {
-            Instruction::Null => {}
-            Instruction::Integer(kind, num) => {
-                self.builder
-                    .build_return(Some(&build_const_integer(self.context, kind, *num)))
-                    .unwrap();
-            }
+        self.emit_bounds_check(index, argc);
 
-            Instruction::String(string) => {
-                self.builder
-                    .build_return(Some(&self.emit_global_string_constant(string)))
-                    .unwrap();
-            }
+        let ptr_kind: PointerType<'ctx> = self.context.ptr_type(AddressSpace::default());
 
-            _ => todo!(),
-        }
+        let arg: PointerValue<'ctx> = unsafe {
+            self.builder
+                .build_in_bounds_gep(ptr_kind, argv, &[index], "")
+                .unwrap()
+        };
+
+        self.builder
+            .build_load(ptr_kind, arg, "")
+            .unwrap()
+            .into_pointer_value()
     }
 
-    fn emit_function(
+    /// Reads an element of a local array. The parser checked a constant index, any other
+    /// one aborts the program when it is out of bounds, like 'argv'.
+    fn emit_index(
         &mut self,
-        name: &str,
-        params: &[Instruction<'ctx>],
-        body: &'ctx Instruction<'ctx>,
-        return_kind: &Option<DataTypes>,
-        is_public: bool,
-    ) {
-        let kind: FunctionType = datatype_to_fn_type(self.context, return_kind, params, None);
+        array: &str,
+        index: &'ctx Instruction<'ctx>,
+        kind: &DataTypes,
+        line: usize,
+    ) -> BasicValueEnum<'ctx> {
+        let (ptr, array_kind, volatile): (PointerValue<'ctx>, ArrayType<'ctx>, bool) =
+            match self.get_local(array, line) {
+                Ok(Instruction::Local(local)) => (
+                    local.ptr,
+                    datatype_to_basic_type_enum(self.context, &local.kind).into_array_type(),
+                    local.volatile,
+                ),
 
-        let function: FunctionValue<'_> = self.module.add_function(name, kind, None);
+                Ok(_) => unreachable!(),
+                Err(error) => {
+                    self.errors.push(error);
+                    return build_default_value(self.context, kind);
+                }
+            };
 
-        if is_public {
-            function.set_linkage(Linkage::External);
-        } else {
-            function.set_linkage(Linkage::Private);
-        }
+        let index: IntValue<'ctx> = match index {
+            Instruction::Integer(_, num) => self.context.i64_type().const_int(*num, false),
 
-        let mut index: usize = 0;
+            _ => {
+                let value: IntValue<'ctx> = self.emit_numeric(index).into_int_value();
 
-        function.get_params().iter().for_each(|param| {
-            if let Some(Instruction::Param { name, .. }) = params.get(index) {
-                param.set_name(name);
+                let index: IntValue<'ctx> = self
+                    .builder
+                    .build_int_cast_sign_flag(
+                        value,
+                        self.context.i64_type(),
+                        index.get_data_type().is_signed(),
+                        "",
+                    )
+                    .unwrap();
+
+                let size: IntValue<'ctx> = self
+                    .context
+                    .i64_type()
+                    .const_int(array_kind.len() as u64, false);
+
+                self.emit_bounds_check(index, size);
+
+                index
             }
+        };
 
-            index += 1;
-        });
+        let element: PointerValue<'ctx> = unsafe {
+            self.builder
+                .build_in_bounds_gep(
+                    array_kind,
+                    ptr,
+                    &[self.context.i64_type().const_zero(), index],
+                    "",
+                )
+                .unwrap()
+        };
 
-        let entry: BasicBlock = self.context.append_basic_block(function, "");
+        let load: BasicValueEnum<'ctx> = self
+            .builder
+            .build_load(datatype_to_basic_type_enum(self.context, kind), element, "")
+            .unwrap();
 
-        self.builder.position_at_end(entry);
+        let load_instr: InstructionValue<'_> = load.as_instruction_value().unwrap();
 
-        self.codegen(body);
+        load_instr.set_alignment(datatype_alignment(kind)).unwrap();
+        load_instr.set_volatile(volatile).unwrap();
 
-        if return_kind.is_none() {
-            self.builder.build_return(None).unwrap();
-        }
+        load
     }
 
-    fn emit_global_boolean(&mut self, value: bool) -> PointerValue<'ctx> {
-        let kind: IntType<'_> = self.context.bool_type();
+    /// The minimum of a signed type divided by '-1' overflows, which LLVM leaves undefined
+    /// and most targets trap on. The program aborts instead, like out of bounds indexes.
+    fn emit_division_check(&self, lhs: IntValue<'ctx>, rhs: IntValue<'ctx>) {
+        let kind: IntType<'ctx> = lhs.get_type();
 
-        let global: GlobalValue<'_> =
-            self.module
-                .add_global(kind, Some(AddressSpace::default()), "");
+        let min: IntValue<'ctx> = kind.const_int(1 << (kind.get_bit_width() - 1), false);
 
-        global.set_linkage(Linkage::Private);
-        global.set_visibility(GlobalVisibility::Protected);
+        // A constant divisor other than '-1' needs no check, the usual case.
+        let is_minus_one: IntValue<'ctx> = self
+            .builder
+            .build_int_compare(IntPredicate::EQ, rhs, kind.const_all_ones(), "")
+            .unwrap();
 
-        if !value {
-            global.set_initializer(&kind.const_int(0, false));
-        } else {
-            global.set_initializer(&kind.const_int(1, false));
+        if is_minus_one.get_zero_extended_constant() == Some(0) {
+            return;
         }
 
-        self.builder
-            .build_pointer_cast(
-                global.as_pointer_value(),
-                self.context.ptr_type(AddressSpace::default()),
-                "",
-            )
-            .unwrap()
-    }
+        let is_min: IntValue<'ctx> = self
+            .builder
+            .build_int_compare(IntPredicate::EQ, lhs, min, "")
+            .unwrap();
 
-    fn emit_global_string_constant(&mut self, string: &str) -> PointerValue<'ctx> {
-        let kind: ArrayType<'_> = self.context.i8_type().array_type(string.len() as u32);
-        let global: GlobalValue<'_> =
-            self.module
-                .add_global(kind, Some(AddressSpace::default()), "");
-        global.set_linkage(Linkage::Private);
-        global.set_initializer(&self.context.const_string(string.as_ref(), false));
-        global.set_visibility(GlobalVisibility::Protected);
-        global.set_constant(true);
-        global.set_unnamed_addr(true);
+        let overflows: IntValue<'ctx> = self.builder.build_and(is_min, is_minus_one, "").unwrap();
 
-        self.builder
-            .build_pointer_cast(
-                global.as_pointer_value(),
-                self.context.ptr_type(AddressSpace::default()),
-                "",
-            )
-            .unwrap()
+        self.emit_abort_unless(self.builder.build_not(overflows, "").unwrap());
     }
 
-    fn emit_global_string(&mut self, string: &str, name: &str) -> PointerValue<'ctx> {
-        let mut buffer: Vec<IntValue> = Vec::with_capacity(string.len());
-        string
-            .as_bytes()
-            .iter()
-            .for_each(|b| buffer.push(self.context.i8_type().const_int(*b as u64, false)));
-
-        let kind: VectorType = self.context.i8_type().vec_type(string.len() as u32);
-        let global: GlobalValue<'_> =
-            self.module
-                .add_global(kind, Some(AddressSpace::default()), name);
-        global.set_linkage(Linkage::Private);
-        global.set_initializer(&VectorType::const_vector(&buffer));
-        global.set_constant(false);
+    /// Aborts the program unless `index` is below `len`. Negative indexes wrap around
+    /// and fail the unsigned comparison too.
+    fn emit_bounds_check(&self, index: IntValue<'ctx>, len: IntValue<'ctx>) {
+        let in_bounds: IntValue<'ctx> = self
+            .builder
+            .build_int_compare(IntPredicate::ULT, index, len, "")
+            .unwrap();
 
-        self.builder
-            .build_pointer_cast(
-                global.as_pointer_value(),
-                self.context.ptr_type(AddressSpace::default()),
-                name,
-            )
-            .unwrap()
+        self.emit_abort_unless(in_bounds);
     }
 
-    fn build_const_integer_return(&mut self, kind: IntType, value: u64, signed: bool) {
+    /// Continues in a new block when `condition` holds, aborts the program otherwise.
+    fn emit_abort_unless(&self, condition: IntValue<'ctx>) {
+        let function: FunctionValue<'ctx> = self.current_function();
+
+        let abort_block: BasicBlock = self.context.append_basic_block(function, "");
+        let continue_block: BasicBlock = self.context.append_basic_block(function, "");
+
         self.builder
-            .build_return(Some(&kind.const_int(value, signed)))
+            .build_conditional_branch(condition, continue_block, abort_block)
             .unwrap();
+
+        self.builder.position_at_end(abort_block);
+        self.emit_abort();
+
+        self.builder.position_at_end(continue_block);
     }
 
-    fn get_local(&self, name: &str) -> &Instruction {
-        for index in (0..self.scope - 1).rev() {
-            if self.locals[index].contains_key(name) {
-                return self.locals[index].get(name).unwrap();
+    fn emit_abort(&self) {
+        let abort: FunctionValue<'ctx> = match &self.abort {
+            Abort::Trap => Intrinsic::find("llvm.trap")
+                .unwrap()
+                .get_declaration(self.module, &[])
+                .unwrap(),
+
+            Abort::Function(name) => {
+                if self.module.get_function(name).is_none() {
+                    self.define_abort(name);
+                }
+
+                self.module.get_function(name).unwrap()
             }
-        }
+        };
 
-        panic!()
+        self.builder.build_call(abort, &[], "").unwrap();
+        self.builder.build_unreachable().unwrap();
     }
 
-    fn get_global(&self, name: &str) -> &Instruction {
-        self.globals.get(name).unwrap()
-    }
+    fn emit_getenv(&mut self, name: &str) -> PointerValue<'ctx> {
+        if self.module.get_function("getenv").is_none() {
+            self.define_getenv();
+        }
 
-    fn advance(&mut self) -> &'ctx Instruction<'ctx> {
-        let c: &Instruction = &self.instructions[self.current];
-        self.current += 1;
+        let name: PointerValue<'ctx> = self.emit_global_string_constant(name);
 
-        c
+        self.builder
+            .build_call(
+                self.module.get_function("getenv").unwrap(),
+                &[name.into()],
+                "",
+            )
+            .unwrap()
+            .try_as_basic_value()
+            .unwrap_left()
+            .into_pointer_value()
     }
 
-    fn is_end(&self) -> bool {
-        self.current >= self.instructions.len()
+    fn emit_numeric(&mut self, value: &'ctx Instruction<'ctx>) -> BasicValueEnum<'ctx> {
+        match value {
+            Instruction::Integer(kind, num) => build_const_integer(self.context, kind, *num).into(),
+            Instruction::Float(kind, num) => build_const_float(self.context, kind, *num).into(),
+            Instruction::Char(byte) => self.context.i8_type().const_int(*byte as u64, false).into(),
+            Instruction::RefVar {
+                name,
+                kind,
+                line,
+                ordering,
+            } => match self.load_local(name, *ordering, *line) {
+                Ok(value) => value,
+                Err(error) => {
+                    self.errors.push(error);
+                    build_default_value(self.context, kind)
+                }
+            },
+            Instruction::BinaryOp {
+                left,
+                op,
+                right,
+                kind,
+            } => self.emit_binary_op(left, op, right, kind),
+            Instruction::Unary { op, operand, kind } => self.emit_unary(op, operand, kind),
+            Instruction::Call {
+                name,
+                args,
+                kind,
+                line,
+                inline,
+            } => self
+                .emit_call(name, args, *line, *inline)
+                .unwrap_or_else(|| build_default_value(self.context, kind)),
+            Instruction::Cast { value, kind } => self.emit_cast(value, kind),
+            Instruction::Index {
+                array,
+                index,
+                kind,
+                line,
+            } => self.emit_index(array, index, kind, *line),
+
+            _ => unreachable!(),
+        }
     }
-}
 
-#[derive(Debug, Clone)]
-pub enum Instruction<'ctx> {
-    Println(Vec<Instruction<'ctx>>),
-    Print(Vec<Instruction<'ctx>>),
-    String(String),
-    Integer(DataTypes, f64),
-    Block {
-        stmts: Vec<Instruction<'ctx>>,
-    },
-    EntryPoint {
-        body: Box<Instruction<'ctx>>,
-    },
-    Value(ThrushBasicValueEnum<'ctx>),
-    Param {
-        name: &'ctx str,
-        kind: DataTypes,
-    },
-    Function {
-        name: &'ctx str,
-        params: Vec<Instruction<'ctx>>,
-        body: Box<Instruction<'ctx>>,
-        return_kind: Option<DataTypes>,
-        is_public: bool,
-    },
-    Return(Box<Instruction<'ctx>>),
-    Var {
-        name: &'ctx str,
-        kind: DataTypes,
-        value: Option<Box<Instruction<'ctx>>>,
-        line: usize,
-    },
-    RefVar {
-        name: &'ctx str,
-        line: usize,
-        kind: DataTypes,
-    },
-    MutVar {
-        name: &'ctx str,
-        value: Box<Instruction<'ctx>>,
-        kind: DataTypes,
-    },
-    Boolean(bool),
-    Null,
-}
+    /// Lowers an arithmetic expression, every operand takes the type of the whole
+    /// expression so literals are built directly with it.
+    fn emit_binary_op(
+        &mut self,
+        left: &'ctx Instruction<'ctx>,
+        op: &TokenKind,
+        right: &'ctx Instruction<'ctx>,
+        kind: &DataTypes,
+    ) -> BasicValueEnum<'ctx> {
+        let lhs: BasicValueEnum<'ctx> = self.emit_operand(left, kind);
+        let rhs: BasicValueEnum<'ctx> = self.emit_operand(right, kind);
 
-#[derive(Default, Debug)]
-pub enum Opt {
-    #[default]
-    None,
-    Low,
-    Mid,
-    Mcqueen,
-}
+        if kind.is_float() {
+            let (lhs, rhs): (FloatValue<'ctx>, FloatValue<'ctx>) =
+                (lhs.into_float_value(), rhs.into_float_value());
 
-#[derive(Default, Debug)]
-pub enum Linking {
-    #[default]
-    Static,
-    Dynamic,
-}
+            return match op {
+                TokenKind::Plus => self.builder.build_float_add(lhs, rhs, ""),
+                TokenKind::Minus => self.builder.build_float_sub(lhs, rhs, ""),
+                TokenKind::Star => self.builder.build_float_mul(lhs, rhs, ""),
+                TokenKind::Slash => self.builder.build_float_div(lhs, rhs, ""),
+                TokenKind::Arith => self.builder.build_float_rem(lhs, rhs, ""),
 
-#[derive(Debug)]
-pub struct Options {
-    pub name: String,
-    pub target_triple: TargetTriple,
-    pub optimization: Opt,
-    pub interpret: bool,
-    pub emit_llvm: bool,
-    pub emit_object: bool,
-    pub build: bool,
-    pub linking: Linking,
-    pub path: PathBuf,
-    pub is_main: bool,
-    pub reloc_mode: RelocMode,
-    pub code_model: CodeModel,
-}
+                _ => unreachable!(),
+            }
+            .unwrap()
+            .into();
+        }
 
-impl Default for Options {
-    fn default() -> Self {
-        Self {
-            name: String::from("main"),
-            target_triple: TargetMachine::get_default_triple(),
-            optimization: Opt::default(),
-            interpret: false,
-            emit_llvm: false,
-            emit_object: false,
-            build: false,
-            linking: Linking::default(),
-            path: PathBuf::new(),
-            is_main: true,
-            reloc_mode: RelocMode::Default,
-            code_model: CodeModel::Default,
+        let (lhs, rhs): (IntValue<'ctx>, IntValue<'ctx>) =
+            (lhs.into_int_value(), rhs.into_int_value());
+
+        if kind.is_signed() && matches!(op, TokenKind::Slash | TokenKind::Arith) {
+            self.emit_division_check(lhs, rhs);
         }
-    }
-}
 
-pub struct FileBuilder<'a, 'ctx> {
-    module: &'a Module<'ctx>,
-    options: &'a Options,
-}
+        match op {
+            TokenKind::Plus => self.builder.build_int_add(lhs, rhs, ""),
+            TokenKind::Minus => self.builder.build_int_sub(lhs, rhs, ""),
+            TokenKind::Star => self.builder.build_int_mul(lhs, rhs, ""),
+            TokenKind::Slash if kind.is_signed() => self.builder.build_int_signed_div(lhs, rhs, ""),
+            TokenKind::Slash => self.builder.build_int_unsigned_div(lhs, rhs, ""),
+            TokenKind::Arith if kind.is_signed() => self.builder.build_int_signed_rem(lhs, rhs, ""),
+            TokenKind::Arith => self.builder.build_int_unsigned_rem(lhs, rhs, ""),
 
-impl<'a, 'ctx> FileBuilder<'a, 'ctx> {
-    pub fn new(options: &'a Options, module: &'a Module<'ctx>) -> Self {
-        Self { options, module }
+            _ => unreachable!(),
+        }
+        .unwrap()
+        .into()
     }
 
-    pub fn build(self) {
-        let opt_level: &str = match self.options.optimization {
-            Opt::None => "O0",
-            Opt::Low => "O1",
-            Opt::Mid => "O2",
-            Opt::Mcqueen => "O3",
-        };
+    /// Both sides are built with `kind`, the type of the operands, the result is an 'i1'.
+    /// Like in C, '!=' is true when a float side is NaN.
+    fn emit_compare(
+        &mut self,
+        left: &'ctx Instruction<'ctx>,
+        op: &TokenKind,
+        right: &'ctx Instruction<'ctx>,
+        kind: &DataTypes,
+    ) -> IntValue<'ctx> {
+        let lhs: BasicValueEnum<'ctx> = self.emit_operand(left, kind);
+        let rhs: BasicValueEnum<'ctx> = self.emit_operand(right, kind);
 
-        let linking: &str = match self.options.linking {
-            Linking::Static => "--static",
-            Linking::Dynamic => "-dynamic",
-        };
+        if kind.is_float() {
+            let predicate: FloatPredicate = match op {
+                TokenKind::EqEq => FloatPredicate::OEQ,
+                TokenKind::BangEqual => FloatPredicate::UNE,
+                TokenKind::Less => FloatPredicate::OLT,
+                TokenKind::LessEqual => FloatPredicate::OLE,
+                TokenKind::Greater => FloatPredicate::OGT,
+                TokenKind::GreaterEqual => FloatPredicate::OGE,
 
-        if self.options.emit_llvm {
-            self.module
-                .print_to_file(format!("{}.ll", self.options.name))
+                _ => unreachable!(),
+            };
+
+            return self
+                .builder
+                .build_float_compare(
+                    predicate,
+                    lhs.into_float_value(),
+                    rhs.into_float_value(),
+                    "",
+                )
                 .unwrap();
-            return;
         }
 
-        self.module
-            .write_bitcode_to_path(Path::new(&format!("{}.bc", self.options.name)));
+        let signed: bool = kind.is_signed();
 
-        match Command::new("clang-18").spawn() {
-            Ok(mut child) => {
-                child.kill().unwrap();
+        let predicate: IntPredicate = match op {
+            TokenKind::EqEq => IntPredicate::EQ,
+            TokenKind::BangEqual => IntPredicate::NE,
+            TokenKind::Less if signed => IntPredicate::SLT,
+            TokenKind::Less => IntPredicate::ULT,
+            TokenKind::LessEqual if signed => IntPredicate::SLE,
+            TokenKind::LessEqual => IntPredicate::ULE,
+            TokenKind::Greater if signed => IntPredicate::SGT,
+            TokenKind::Greater => IntPredicate::UGT,
+            TokenKind::GreaterEqual if signed => IntPredicate::SGE,
+            TokenKind::GreaterEqual => IntPredicate::UGE,
+
+            _ => unreachable!(),
+        };
+
+        self.builder
+            .build_int_compare(predicate, lhs.into_int_value(), rhs.into_int_value(), "")
+            .unwrap()
+    }
+
+    /// '-' negates the operand built with `kind`, '!' inverts a condition.
+    fn emit_unary(
+        &mut self,
+        op: &TokenKind,
+        operand: &'ctx Instruction<'ctx>,
+        kind: &DataTypes,
+    ) -> BasicValueEnum<'ctx> {
+        match op {
+            TokenKind::Bang => {
+                let value: IntValue<'ctx> = self.emit_condition(operand);
+
+                self.builder.build_not(value, "").unwrap().into()
+            }
+
+            TokenKind::Minus if kind.is_float() => {
+                let value: FloatValue<'ctx> = self.emit_operand(operand, kind).into_float_value();
+
+                self.builder.build_float_neg(value, "").unwrap().into()
+            }
+
+            TokenKind::Minus => {
+                let value: IntValue<'ctx> = self.emit_operand(operand, kind).into_int_value();
+
+                self.builder.build_int_neg(value, "").unwrap().into()
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn emit_operand(
+        &mut self,
+        instr: &'ctx Instruction<'ctx>,
+        kind: &DataTypes,
+    ) -> BasicValueEnum<'ctx> {
+        match instr {
+            Instruction::Integer(_, num) if kind.is_float() => {
+                datatype_float_to_type(self.context, kind)
+                    .const_float(*num as f64)
+                    .into()
+            }
+            Instruction::Float(_, num) => datatype_float_to_type(self.context, kind)
+                .const_float(*num)
+                .into(),
+            Instruction::Integer(_, num) => datatype_integer_to_type(self.context, kind)
+                .const_int(*num, false)
+                .into(),
+            Instruction::BinaryOp {
+                left, op, right, ..
+            } => self.emit_binary_op(left, op, right, kind),
+            Instruction::Unary { op, operand, .. } => self.emit_unary(op, operand, kind),
+
+            _ => self.emit_numeric(instr),
+        }
+    }
+
+    fn emit_bitcast(
+        &mut self,
+        value: &'ctx Instruction<'ctx>,
+        kind: &DataTypes,
+    ) -> BasicValueEnum<'ctx> {
+        let target: BasicTypeEnum<'ctx> = if kind.is_float() {
+            datatype_float_to_type(self.context, kind).into()
+        } else {
+            datatype_integer_to_type(self.context, kind).into()
+        };
+
+        let value: BasicValueEnum<'ctx> = self.emit_numeric(value);
+
+        self.builder.build_bit_cast(value, target, "").unwrap()
+    }
+
+    /// Between integers of the same width only the type changes, the value is kept as is.
+    fn emit_cast(
+        &mut self,
+        value: &'ctx Instruction<'ctx>,
+        kind: &DataTypes,
+    ) -> BasicValueEnum<'ctx> {
+        let from: DataTypes = value.get_data_type();
+
+        if from == DataTypes::Ptr && *kind == DataTypes::Ptr {
+            return self.emit_numeric(value);
+        }
+
+        if from == DataTypes::Ptr {
+            let pointer_width: u32 = self.pointer_width();
+
+            if kind.bit_width() < pointer_width {
+                logging::log(
+                    logging::LogType::WARN,
+                    &format!(
+                        "Converting a pointer to '{}' drops the upper bits of the address, it takes {} bits on this target.",
+                        kind, pointer_width
+                    ),
+                );
+            }
+
+            let value: PointerValue<'ctx> = self.emit_numeric(value).into_pointer_value();
+
+            return self
+                .builder
+                .build_ptr_to_int(value, datatype_integer_to_type(self.context, kind), "")
+                .unwrap()
+                .into();
+        }
+
+        let value: IntValue<'ctx> = self.emit_numeric(value).into_int_value();
+
+        if *kind == DataTypes::Ptr {
+            return self
+                .builder
+                .build_int_to_ptr(value, self.context.ptr_type(AddressSpace::default()), "")
+                .unwrap()
+                .into();
+        }
+
+        if from.bit_width() == kind.bit_width() {
+            return value.into();
+        }
+
+        self.builder
+            .build_int_cast_sign_flag(
+                value,
+                datatype_integer_to_type(self.context, kind),
+                from.is_signed(),
+                "",
+            )
+            .unwrap()
+            .into()
+    }
+
+    /// The size of an address in bits, from the data layout of the module.
+    fn pointer_width(&self) -> u32 {
+        let data_layout: String = self
+            .module
+            .get_data_layout()
+            .as_str()
+            .to_string_lossy()
+            .into_owned();
+
+        TargetData::create(&data_layout).get_pointer_byte_size(None) * 8
+    }
+
+    fn emit_to_string(&mut self, value: &'ctx Instruction<'ctx>) -> PointerValue<'ctx> {
+        if self.module.get_function("snprintf").is_none() {
+            self.define_snprintf();
+        }
+
+        let (kind, value): (&DataTypes, BasicValueEnum<'ctx>) = match value {
+            Instruction::Integer(kind, _)
+            | Instruction::Float(kind, _)
+            | Instruction::RefVar { kind, .. }
+            | Instruction::BinaryOp { kind, .. }
+            | Instruction::Unary { kind, .. }
+            | Instruction::Cast { kind, .. } => (kind, self.emit_numeric(value)),
 
-                if self.options.build {
-                    match self.opt(opt_level) {
-                        Ok(()) => {
-                            Command::new("clang-18")
-                                .arg("-opaque-pointers")
-                                .arg(linking)
-                                .arg("-ffast-math")
-                                .arg(format!("{}.bc", self.options.name))
-                                .arg("-o")
-                                .arg(self.options.name.as_str())
-                                .output()
+            _ => unreachable!(),
+        };
+
+        let value: BasicMetadataValueEnum<'ctx> = self.promote_vararg(value, kind);
+
+        // Enough for the longest '%f' rendering of a 'double': sign, 309 integer
+        // digits, the point, 6 decimals and the terminator.
+        let buffer_kind: ArrayType<'ctx> = self.context.i8_type().array_type(320);
+
+        let buffer: PointerValue<'ctx> = self.build_entry_alloca(buffer_kind.into());
+        let fmt: PointerValue<'ctx> =
+            self.emit_global_string_constant(&format!("{}\0", printf_spec(kind)));
+
+        self.builder
+            .build_call(
+                self.module.get_function("snprintf").unwrap(),
+                &[
+                    buffer.into(),
+                    self.context.i64_type().const_int(320, false).into(),
+                    fmt.into(),
+                    value,
+                ],
+                "",
+            )
+            .unwrap();
+
+        buffer
+    }
+
+    /// Allocates in the entry block, so a slot used in a loop is reserved once per call
+    /// of the function instead of growing the stack on every iteration.
+    fn build_entry_alloca(&self, kind: BasicTypeEnum<'ctx>) -> PointerValue<'ctx> {
+        let entry: BasicBlock<'ctx> = self.current_function().get_first_basic_block().unwrap();
+        let builder: Builder<'ctx> = self.context.create_builder();
+
+        match entry.get_first_instruction() {
+            Some(instr) => builder.position_before(&instr),
+            None => builder.position_at_end(entry),
+        }
+
+        builder.build_alloca(kind, "").unwrap()
+    }
+
+    fn emit_variable(
+        &mut self,
+        name: &'a str,
+        kind: &DataTypes,
+        value: &'ctx Instruction<'ctx>,
+        line: usize,
+        visibility: Option<GlobalVisibility>,
+        thread_local: bool,
+        volatile: bool,
+    ) {
+        // Like 'let a = f();' with a function that returns nothing.
+        if *kind == DataTypes::Void {
+            self.errors.push(ThrushError::Parse(
+                ThrushErrorKind::TypeMismatch,
+                String::from("Type Mismatch"),
+                format!(
+                    "The variable `{}` can't have type 'void', there is no value to store in it.",
+                    name
+                ),
+                line,
+            ));
+
+            return;
+        }
+
+        // A number or char that is never assigned keeps its initializer, so its reads
+        // use the constant itself and the variable needs no stack slot.
+        if !volatile && !self.mutated.contains(name) {
+            let constant: Option<BasicValueEnum<'ctx>> = match value {
+                Instruction::Integer(literal, num) if kind.is_integer() && literal.is_integer() => {
+                    Some(self.emit_integer_literal(literal, kind, *num).into())
+                }
+                Instruction::Float(literal, num) if kind.is_float() && literal.is_float() => {
+                    Some(build_const_float(self.context, literal, *num).into())
+                }
+                Instruction::Char(_) if *kind == DataTypes::Char => Some(self.emit_numeric(value)),
+
+                _ => None,
+            };
+
+            if let Some(constant) = constant {
+                self.locals[self.scope].insert(
+                    name,
+                    Instruction::Value(ThrushBasicValueEnum {
+                        kind: kind.defer(),
+                        value: constant,
+                    }),
+                );
+
+                return;
+            }
+        }
+
+        let instr: Instruction<'ctx> = match kind {
+            DataTypes::I8
+            | DataTypes::I16
+            | DataTypes::I32
+            | DataTypes::I64
+            | DataTypes::U8
+            | DataTypes::U16
+            | DataTypes::U32
+            | DataTypes::U64 => {
+                let ptr_kind: IntType<'_> = datatype_integer_to_type(self.context, kind);
+
+                let alignment: u32 = datatype_alignment(kind);
+
+                let ptr: PointerValue<'_> =
+                    build_alloca_with_integer(self.builder, ptr_kind, alignment);
+
+                match value {
+                    Instruction::Null => {
+                        let store: InstructionValue<'_> = self
+                            .builder
+                            .build_store(ptr, build_default_value(self.context, kind))
+                            .unwrap();
+
+                        store.set_alignment(alignment).unwrap();
+                        store.set_volatile(volatile).unwrap();
+                    }
+
+                    Instruction::Integer(literal, num) => match literal {
+                        DataTypes::I8
+                        | DataTypes::I16
+                        | DataTypes::I32
+                        | DataTypes::I64
+                        | DataTypes::U8
+                        | DataTypes::U16
+                        | DataTypes::U32
+                        | DataTypes::U64 => {
+                            let store: InstructionValue<'_> = self
+                                .builder
+                                .build_store(ptr, self.emit_integer_literal(literal, kind, *num))
                                 .unwrap();
+
+                            store.set_alignment(alignment).unwrap();
+                            store.set_volatile(volatile).unwrap();
                         }
-                        Err(error) => {
-                            logging::log(logging::LogType::ERROR, &error);
+
+                        kind => {
+                            self.unsupported(value, Some(kind));
                             return;
                         }
+                    },
+
+                    Instruction::Block { stmts } => {
+                        let result: BasicValueEnum<'ctx> = self.emit_block_value(stmts);
+
+                        let store: InstructionValue<'_> =
+                            self.builder.build_store(ptr, result).unwrap();
+
+                        store.set_alignment(alignment).unwrap();
+                        store.set_volatile(volatile).unwrap();
+                    }
+
+                    Instruction::BinaryOp { .. }
+                    | Instruction::Unary { .. }
+                    | Instruction::Call { .. }
+                    | Instruction::Cast { .. }
+                    | Instruction::Index { .. } => {
+                        let result: BasicValueEnum<'ctx> = self.emit_numeric(value);
+
+                        let store: InstructionValue<'_> =
+                            self.builder.build_store(ptr, result).unwrap();
+
+                        store.set_alignment(alignment).unwrap();
+                        store.set_volatile(volatile).unwrap();
+                    }
+
+                    // 'print' yields the number of bytes written, like 'printf'.
+                    Instruction::Print(data)
+                    | Instruction::Println(data)
+                    | Instruction::EPrint(data)
+                    | Instruction::EPrintln(data) => {
+                        let written: IntValue<'ctx> = self.emit_print(
+                            data,
+                            matches!(value, Instruction::Println(_) | Instruction::EPrintln(_)),
+                            matches!(value, Instruction::EPrint(_) | Instruction::EPrintln(_)),
+                        );
+
+                        let store: InstructionValue<'_> =
+                            self.builder.build_store(ptr, written).unwrap();
+
+                        store.set_alignment(alignment).unwrap();
+                        store.set_volatile(volatile).unwrap();
+                    }
+
+                    _ => {
+                        self.unsupported(value, Some(kind));
+                        return;
+                    }
+                }
+
+                Instruction::Local(ThrushLocal {
+                    kind: kind.defer(),
+                    ptr,
+                    volatile,
+                })
+            }
+
+            DataTypes::F16 | DataTypes::BF16 | DataTypes::F32 | DataTypes::F64 => {
+                let ptr_kind: FloatType<'_> = datatype_float_to_type(self.context, kind);
+
+                let alignment: u32 = datatype_alignment(kind);
+
+                let ptr: PointerValue<'_> =
+                    build_alloca_with_float(self.builder, ptr_kind, alignment);
+
+                match value {
+                    Instruction::Null => {
+                        let store: InstructionValue<'_> = self
+                            .builder
+                            .build_store(ptr, build_default_value(self.context, kind))
+                            .unwrap();
+
+                        store.set_alignment(alignment).unwrap();
+                        store.set_volatile(volatile).unwrap();
                     }
-                } else {
-                    match self.opt(opt_level) {
-                        Ok(()) => {
-                            Command::new("clang-18")
-                                .arg("-opaque-pointers")
-                                .arg(linking)
-                                .arg("-ffast-math")
-                                .arg("-c")
-                                .arg(format!("{}.bc", self.options.name))
-                                .arg("-o")
-                                .arg(format!("{}.o", self.options.name))
-                                .output()
+
+                    Instruction::Float(kind, num) => match kind {
+                        DataTypes::F16 | DataTypes::BF16 | DataTypes::F32 | DataTypes::F64 => {
+                            let store: InstructionValue<'_> = self
+                                .builder
+                                .build_store(ptr, build_const_float(self.context, kind, *num))
                                 .unwrap();
+
+                            store.set_alignment(alignment).unwrap();
+                            store.set_volatile(volatile).unwrap();
                         }
-                        Err(error) => {
-                            logging::log(logging::LogType::ERROR, &error);
+
+                        kind => {
+                            self.unsupported(value, Some(kind));
                             return;
                         }
+                    },
+
+                    Instruction::Block { stmts } => {
+                        let result: BasicValueEnum<'ctx> = self.emit_block_value(stmts);
+
+                        let store: InstructionValue<'_> =
+                            self.builder.build_store(ptr, result).unwrap();
+
+                        store.set_alignment(alignment).unwrap();
+                        store.set_volatile(volatile).unwrap();
+                    }
+
+                    Instruction::BinaryOp { .. }
+                    | Instruction::Unary { .. }
+                    | Instruction::Call { .. }
+                    | Instruction::Cast { .. }
+                    | Instruction::Index { .. } => {
+                        let result: BasicValueEnum<'ctx> = self.emit_numeric(value);
+
+                        let store: InstructionValue<'_> =
+                            self.builder.build_store(ptr, result).unwrap();
+
+                        store.set_alignment(alignment).unwrap();
+                        store.set_volatile(volatile).unwrap();
+                    }
+
+                    _ => {
+                        self.unsupported(value, Some(kind));
+                        return;
                     }
                 }
 
-                remove_file(format!("{}.bc", self.options.name)).unwrap();
+                Instruction::Local(ThrushLocal {
+                    kind: kind.defer(),
+                    ptr,
+                    volatile,
+                })
             }
-            Err(_) => {
-                logging::log(
-                    logging::LogType::ERROR,
-                    "Compilation failed. Clang version 17 is not installed.",
-                );
+
+            DataTypes::String => match value {
+                Instruction::Null => Instruction::Value(ThrushBasicValueEnum {
+                    kind: DataTypes::String,
+                    value: self
+                        .emit_global_string("", name, visibility, thread_local)
+                        .into(),
+                }),
+
+                Instruction::String(string) => Instruction::Value(ThrushBasicValueEnum {
+                    kind: DataTypes::String,
+                    value: self
+                        .emit_global_string(string, name, visibility, thread_local)
+                        .into(),
+                }),
+
+                _ => {
+                    self.unsupported(value, Some(kind));
+                    return;
+                }
+            },
+
+            DataTypes::Bool => {
+                let (initializer, stored): (IntValue<'ctx>, Option<IntValue<'ctx>>) = match value {
+                    Instruction::Null => (
+                        build_default_value(self.context, kind).into_int_value(),
+                        None,
+                    ),
+                    Instruction::Boolean(bool) => (
+                        self.context.bool_type().const_int(*bool as u64, false),
+                        None,
+                    ),
+
+                    // A negated constant is folded, any other starts out 'false' and is stored.
+                    Instruction::Unary {
+                        op: TokenKind::Bang,
+                        ..
+                    } => {
+                        let negated: IntValue<'ctx> = self.emit_condition(value);
+
+                        if negated.is_const() {
+                            (negated, None)
+                        } else {
+                            (self.context.bool_type().const_zero(), Some(negated))
+                        }
+                    }
+
+                    _ => {
+                        self.unsupported(value, Some(kind));
+                        return;
+                    }
+                };
+
+                let global: PointerValue<'ctx> =
+                    self.emit_global_boolean(initializer, visibility, thread_local);
+
+                if let Some(stored) = stored {
+                    self.builder.build_store(global, stored).unwrap();
+                }
+
+                Instruction::Value(ThrushBasicValueEnum {
+                    kind: DataTypes::Bool,
+                    value: global.into(),
+                })
             }
-        }
-    }
 
-    fn opt(&self, opt_level: &str) -> Result<(), String> {
-        match Command::new("opt").spawn() {
-            Ok(mut child) => {
-                child.kill().unwrap();
+            DataTypes::Char => {
+                let initializer: IntValue<'ctx> = match value {
+                    Instruction::Null => build_default_value(self.context, kind).into_int_value(),
+                    Instruction::Char(_) | Instruction::Call { .. } | Instruction::Index { .. } => {
+                        self.emit_numeric(value).into_int_value()
+                    }
 
-                Command::new("opt")
-                    .arg(format!("-p={}", opt_level))
-                    .arg("-p=globalopt")
-                    .arg("-p=globaldce")
-                    .arg("-p=dce")
-                    .arg("-p=instcombine")
-                    .arg("-p=strip-dead-prototypes")
-                    .arg("-p=strip")
-                    .arg("-p=mem2reg")
-                    .arg("-p=memcpyopt")
-                    .arg(format!("{}.bc", self.options.name))
-                    .output()
+                    _ => {
+                        self.unsupported(value, Some(kind));
+                        return;
+                    }
+                };
+
+                let ptr: PointerValue<'ctx> =
+                    build_alloca_with_integer(self.builder, self.context.i8_type(), 1);
+
+                let store: InstructionValue<'_> =
+                    self.builder.build_store(ptr, initializer).unwrap();
+
+                store.set_alignment(1).unwrap();
+                store.set_volatile(volatile).unwrap();
+
+                Instruction::Local(ThrushLocal {
+                    kind: DataTypes::Char,
+                    ptr,
+                    volatile,
+                })
+            }
+
+            // A pointer starts out null or as an address converted from an integer.
+            DataTypes::Ptr => {
+                let initializer: BasicValueEnum<'ctx> = match value {
+                    Instruction::Null => build_default_value(self.context, kind),
+                    Instruction::Cast { .. } => self.emit_numeric(value),
+
+                    _ => {
+                        self.unsupported(value, Some(kind));
+                        return;
+                    }
+                };
+
+                let ptr: PointerValue<'ctx> = self
+                    .builder
+                    .build_alloca(self.context.ptr_type(AddressSpace::default()), "")
                     .unwrap();
 
-                Ok(())
+                let store: InstructionValue<'_> =
+                    self.builder.build_store(ptr, initializer).unwrap();
+
+                store.set_volatile(volatile).unwrap();
+
+                Instruction::Local(ThrushLocal {
+                    kind: DataTypes::Ptr,
+                    ptr,
+                    volatile,
+                })
             }
 
-            Err(_) => Err(String::from(
-                "Compilation failed. LLVM Optimizer is not installed.",
-            )),
-        }
+            // The elements are stored one by one, any of them may be computed.
+            DataTypes::Array(element, _) => {
+                let array_kind: BasicTypeEnum<'ctx> =
+                    datatype_to_basic_type_enum(self.context, kind);
+
+                let alignment: u32 = datatype_alignment(kind);
+
+                let ptr: PointerValue<'ctx> = self.builder.build_alloca(array_kind, "").unwrap();
+
+                ptr.as_instruction()
+                    .unwrap()
+                    .set_alignment(alignment)
+                    .unwrap();
+
+                match value {
+                    Instruction::Null => {
+                        let store: InstructionValue<'_> = self
+                            .builder
+                            .build_store(ptr, build_default_value(self.context, kind))
+                            .unwrap();
+
+                        store.set_alignment(alignment).unwrap();
+                        store.set_volatile(volatile).unwrap();
+                    }
+
+                    Instruction::Array { elements, .. } => {
+                        for (index, instr) in elements.iter().enumerate() {
+                            let value: BasicValueEnum<'ctx> = self.emit_operand(instr, element);
+
+                            let slot: PointerValue<'ctx> = unsafe {
+                                self.builder
+                                    .build_in_bounds_gep(
+                                        array_kind,
+                                        ptr,
+                                        &[
+                                            self.context.i64_type().const_zero(),
+                                            self.context.i64_type().const_int(index as u64, false),
+                                        ],
+                                        "",
+                                    )
+                                    .unwrap()
+                            };
+
+                            let store: InstructionValue<'_> =
+                                self.builder.build_store(slot, value).unwrap();
+
+                            store.set_alignment(alignment).unwrap();
+                            store.set_volatile(volatile).unwrap();
+                        }
+                    }
+
+                    _ => {
+                        self.unsupported(value, Some(kind));
+                        return;
+                    }
+                }
+
+                Instruction::Local(ThrushLocal {
+                    kind: kind.defer(),
+                    ptr,
+                    volatile,
+                })
+            }
+
+            _ => {
+                self.unsupported(value, Some(kind));
+                return;
+            }
+        };
+
+        // Strings and booleans are globals, the other variables live on the stack.
+        match instr {
+            Instruction::Local(local) => {
+                self.locals[self.scope].insert(name, Instruction::Local(local));
+            }
+
+            instr => {
+                self.globals.insert(name, instr);
+            }
+        }
+    }
+
+    /// Static locals are private globals named after their function. Their initializer
+    /// is a constant, so the global holds it before the first call and needs no guard.
+    fn emit_static_variable(
+        &mut self,
+        name: &'a str,
+        kind: &DataTypes,
+        value: &'ctx Instruction<'ctx>,
+        volatile: bool,
+    ) {
+        let initializer: BasicValueEnum<'ctx> = match value {
+            Instruction::Integer(kind, num) => build_const_integer(self.context, kind, *num).into(),
+            Instruction::Float(kind, num) => build_const_float(self.context, kind, *num).into(),
+            _ => build_default_value(self.context, kind),
+        };
+
+        let symbol: String = format!(
+            "{}.{}",
+            self.current_function().get_name().to_str().unwrap(),
+            name
+        );
+
+        let alignment: u32 = datatype_alignment(kind);
+
+        let global: GlobalValue<'_> = self.module.add_global(
+            initializer.get_type(),
+            Some(AddressSpace::default()),
+            &symbol,
+        );
+
+        global.set_linkage(Linkage::Private);
+        global.set_initializer(&initializer);
+        global.set_alignment(alignment);
+
+        self.locals[self.scope].insert(
+            name,
+            Instruction::Local(ThrushLocal {
+                kind: kind.defer(),
+                ptr: global.as_pointer_value(),
+                volatile,
+            }),
+        );
+    }
+
+    /// Literals are built with the type of the variable, the lexer only gives their sign.
+    fn emit_integer_literal(
+        &self,
+        literal: &DataTypes,
+        kind: &DataTypes,
+        num: u64,
+    ) -> IntValue<'ctx> {
+        let num: IntValue<'ctx> =
+            datatype_integer_to_type(self.context, kind).const_int(num, false);
+
+        if literal.is_signed() {
+            num.const_neg()
+        } else {
+            num
+        }
+    }
+
+    fn emit_mutate_variable(
+        &mut self,
+        name: &str,
+        value: &'ctx Instruction<'ctx>,
+        kind: &'ctx DataTypes,
+        ordering: Option<AtomicOrdering>,
+        line: usize,
+    ) {
+        let value: BasicValueEnum<'ctx> = match value {
+            Instruction::Boolean(bool) => self
+                .context
+                .bool_type()
+                .const_int(*bool as u64, false)
+                .into(),
+            Instruction::Integer(literal, num) => {
+                self.emit_integer_literal(literal, kind, *num).into()
+            }
+            Instruction::Float(_, num) => build_const_float(self.context, kind, *num).into(),
+
+            _ => self.emit_numeric(value),
+        };
+
+        // Boolean parameters live on the stack like any other local.
+        if *kind == DataTypes::Bool && self.get_local(name, line).is_err() {
+            match self.get_global(name, line) {
+                Ok(Instruction::Value(pointer)) => {
+                    self.builder
+                        .build_store(pointer.value.into_pointer_value(), value)
+                        .unwrap();
+                }
+
+                Ok(_) => unreachable!(),
+                Err(error) => self.errors.push(error),
+            }
+
+            return;
+        }
+
+        match self.get_local(name, line) {
+            Ok(Instruction::Local(local)) => {
+                let store: InstructionValue<'_> =
+                    self.builder.build_store(local.ptr, value).unwrap();
+
+                if local.kind != DataTypes::Ptr {
+                    store
+                        .set_alignment(datatype_alignment(&local.kind))
+                        .unwrap();
+                }
+
+                store.set_volatile(local.volatile).unwrap();
+
+                if let Some(ordering) = ordering {
+                    store.set_atomic_ordering(ordering).unwrap();
+                }
+            }
+
+            Ok(_) => unreachable!(),
+            Err(error) => self.errors.push(error),
+        }
+    }
+
+    fn emit_block_value(&mut self, stmts: &'ctx [Instruction<'ctx>]) -> BasicValueEnum<'ctx> {
+        let (last, body): (&'ctx Instruction<'ctx>, &'ctx [Instruction<'ctx>]) =
+            stmts.split_last().unwrap();
+
+        self.begin_scope();
+
+        body.iter().for_each(|instr| {
+            self.codegen(instr);
+        });
+
+        // Read the value before popping the scope, it may refer to the block's locals.
+        let value: BasicValueEnum<'ctx> = self.emit_numeric(last);
+
+        self.end_scope();
+
+        value
+    }
+
+    fn begin_scope(&mut self) {
+        self.scope += 1;
+        self.locals.push(BTreeMap::new());
+        self.deferred.push(Vec::new());
+    }
+
+    /// Runs the scope's deferred statements in reverse order, unless a 'return'
+    /// already ran them and terminated the block.
+    fn end_scope(&mut self) {
+        let deferred: Vec<&'ctx Instruction<'ctx>> = self.deferred.pop().unwrap();
+
+        if !self.is_terminated() {
+            deferred.iter().rev().for_each(|stmt| {
+                self.codegen(stmt);
+            });
+        }
+
+        self.scope -= 1;
+        self.locals.pop();
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.builder
+            .get_insert_block()
+            .is_some_and(|block| block.get_terminator().is_some())
+    }
+
+    /// A 'return' leaves every open scope of the function, innermost first.
+    fn emit_all_deferred(&mut self) {
+        self.emit_deferred_above(0);
+    }
+
+    fn emit_deferred_above(&mut self, scope: usize) {
+        for scope in (scope + 1..=self.scope).rev() {
+            let deferred: Vec<&'ctx Instruction<'ctx>> = self.deferred[scope].clone();
+
+            deferred.iter().rev().for_each(|stmt| {
+                self.codegen(stmt);
+            });
+        }
+    }
+
+    fn current_function(&self) -> FunctionValue<'ctx> {
+        self.builder
+            .get_insert_block()
+            .unwrap()
+            .get_parent()
+            .unwrap()
+    }
+
+    /// Only the branches that fall through get a block to continue in, so an 'if'
+    /// whose branches all return leaves no empty block behind.
+    fn emit_if(
+        &mut self,
+        condition: &'ctx Instruction<'ctx>,
+        then_block: &'ctx Instruction<'ctx>,
+        else_block: Option<&'ctx Instruction<'ctx>>,
+    ) {
+        // Only the taken side is emitted, in the current block.
+        match Self::constant_condition(condition) {
+            Some(true) => return self.codegen(then_block),
+            Some(false) => return else_block.into_iter().for_each(|els| self.codegen(els)),
+            None => {}
+        }
+
+        let function: FunctionValue<'ctx> = self.current_function();
+        let condition: IntValue<'ctx> = self.emit_condition(condition);
+
+        let then: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+        let otherwise: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+
+        self.builder
+            .build_conditional_branch(condition, then, otherwise)
+            .unwrap();
+
+        self.builder.position_at_end(then);
+        self.codegen(then_block);
+
+        let then_end: Option<BasicBlock<'ctx>> = self.fallthrough_block();
+
+        self.builder.position_at_end(otherwise);
+
+        if let Some(else_block) = else_block {
+            self.codegen(else_block);
+        }
+
+        let otherwise_end: Option<BasicBlock<'ctx>> = self.fallthrough_block();
+
+        if then_end.is_none() && otherwise_end.is_none() {
+            return;
+        }
+
+        let merge: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+
+        [then_end, otherwise_end]
+            .iter()
+            .flatten()
+            .for_each(|block| {
+                self.builder.position_at_end(*block);
+                self.builder.build_unconditional_branch(merge).unwrap();
+            });
+
+        self.builder.position_at_end(merge);
+    }
+
+    /// The header re-evaluates the condition on every iteration, the body branches
+    /// back to it unless it returned.
+    fn emit_while(
+        &mut self,
+        condition: &'ctx Instruction<'ctx>,
+        body: &'ctx Instruction<'ctx>,
+        label: Option<&'ctx str>,
+    ) {
+        // A condition that is always true makes a plain loop, one that is always false no code.
+        match Self::constant_condition(condition) {
+            Some(true) => return self.emit_loop(body, label),
+            Some(false) => return,
+            None => {}
+        }
+
+        let function: FunctionValue<'ctx> = self.current_function();
+
+        let header: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+        let start: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+        let exit: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+
+        self.builder.build_unconditional_branch(header).unwrap();
+        self.builder.position_at_end(header);
+
+        let condition: IntValue<'ctx> = self.emit_condition(condition);
+
+        self.builder
+            .build_conditional_branch(condition, start, exit)
+            .unwrap();
+
+        self.builder.position_at_end(start);
+
+        self.loops.push((label, exit, self.scope));
+        self.codegen(body);
+        self.loops.pop();
+
+        if !self.is_terminated() {
+            self.builder.build_unconditional_branch(header).unwrap();
+        }
+
+        self.builder.position_at_end(exit);
+    }
+
+    fn emit_loop(&mut self, body: &'ctx Instruction<'ctx>, label: Option<&'ctx str>) {
+        let function: FunctionValue<'ctx> = self.current_function();
+
+        let start: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+        let exit: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+
+        self.builder.build_unconditional_branch(start).unwrap();
+        self.builder.position_at_end(start);
+
+        self.loops.push((label, exit, self.scope));
+        self.codegen(body);
+        self.loops.pop();
+
+        if !self.is_terminated() {
+            self.builder.build_unconditional_branch(start).unwrap();
+        }
+
+        self.builder.position_at_end(exit);
+
+        // Only a 'return' leaves a loop without 'break', so nothing comes after it.
+        if !body.has_break() {
+            self.builder.build_unreachable().unwrap();
+        }
+    }
+
+    /// Leaves the innermost loop, or the one named by the label, running the deferred
+    /// statements of the scopes it opened.
+    fn emit_break(&mut self, label: Option<&'ctx str>) {
+        let target: Option<(BasicBlock<'ctx>, usize)> = self
+            .loops
+            .iter()
+            .rev()
+            .find(|(name, ..)| label.is_none() || *name == label)
+            .map(|(_, exit, scope)| (*exit, *scope));
+
+        match target {
+            Some((exit, scope)) => {
+                self.emit_deferred_above(scope);
+                self.builder.build_unconditional_branch(exit).unwrap();
+            }
+
+            None => self.errors.push(ThrushError::Compile(format!(
+                "The label '{}' doesn't name a loop around this 'break'.",
+                label.unwrap_or_default()
+            ))),
+        }
+    }
+
+    fn fallthrough_block(&self) -> Option<BasicBlock<'ctx>> {
+        if self.is_terminated() {
+            return None;
+        }
+
+        self.builder.get_insert_block()
+    }
+
+    /// The value of a condition known at compile time: a boolean literal, a comparison
+    /// of number literals, or '!', 'and' and 'or' of those.
+    fn constant_condition(condition: &Instruction) -> Option<bool> {
+        match condition {
+            Instruction::Boolean(value) => Some(*value),
+
+            Instruction::Unary {
+                op: TokenKind::Bang,
+                operand,
+                ..
+            } => Self::constant_condition(operand).map(|value| !value),
+
+            Instruction::LogicalAnd { left, right } => {
+                Some(Self::constant_condition(left)? && Self::constant_condition(right)?)
+            }
+            Instruction::LogicalOr { left, right } => {
+                Some(Self::constant_condition(left)? || Self::constant_condition(right)?)
+            }
+
+            Instruction::Compare {
+                left, op, right, ..
+            } => match (&**left, &**right) {
+                (Instruction::Integer(lkind, lnum), Instruction::Integer(rkind, rnum)) => {
+                    // Signed literals only come from a negation, the lexer gives the magnitude.
+                    let integer = |kind: &DataTypes, num: u64| match kind.is_signed() {
+                        true => -(num as i128),
+                        false => num as i128,
+                    };
+
+                    Self::constant_compare(op, integer(lkind, *lnum), integer(rkind, *rnum))
+                }
+
+                (Instruction::Float(_, lhs), Instruction::Float(_, rhs)) => {
+                    Self::constant_compare(op, lhs, rhs)
+                }
+
+                _ => None,
+            },
+
+            _ => None,
+        }
+    }
+
+    fn constant_compare<T: PartialOrd>(op: &TokenKind, lhs: T, rhs: T) -> Option<bool> {
+        match op {
+            TokenKind::EqEq => Some(lhs == rhs),
+            TokenKind::BangEqual => Some(lhs != rhs),
+            TokenKind::Less => Some(lhs < rhs),
+            TokenKind::LessEqual => Some(lhs <= rhs),
+            TokenKind::Greater => Some(lhs > rhs),
+            TokenKind::GreaterEqual => Some(lhs >= rhs),
+
+            _ => None,
+        }
+    }
+
+    /// Numbers are true when they aren't zero, like in C.
+    fn emit_condition(&mut self, condition: &'ctx Instruction<'ctx>) -> IntValue<'ctx> {
+        match condition {
+            Instruction::Boolean(value) => self.context.bool_type().const_int(*value as u64, false),
+
+            // A boolean parameter.
+            Instruction::RefVar {
+                name,
+                kind: DataTypes::Bool,
+                line,
+                ..
+            } if self.get_local(name, *line).is_ok() => {
+                self.load_local(name, None, *line).unwrap().into_int_value()
+            }
+
+            Instruction::RefVar {
+                name,
+                kind: DataTypes::Bool,
+                line,
+                ..
+            } => match self.get_global(name, *line) {
+                Ok(Instruction::Value(pointer)) => self
+                    .builder
+                    .build_load(
+                        self.context.bool_type(),
+                        pointer.value.into_pointer_value(),
+                        "",
+                    )
+                    .unwrap()
+                    .into_int_value(),
+
+                Ok(_) => unreachable!(),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.context.bool_type().const_zero()
+                }
+            },
+
+            Instruction::Compare {
+                left,
+                op,
+                right,
+                kind,
+            } => self.emit_compare(left, op, right, kind),
+
+            Instruction::Unary {
+                op: TokenKind::Bang,
+                operand,
+                kind,
+            } => self
+                .emit_unary(&TokenKind::Bang, operand, kind)
+                .into_int_value(),
+
+            Instruction::LogicalAnd { left, right } => self.emit_logical(left, right, true),
+            Instruction::LogicalOr { left, right } => self.emit_logical(left, right, false),
+
+            _ => match self.emit_numeric(condition) {
+                BasicValueEnum::FloatValue(value) => self
+                    .builder
+                    .build_float_compare(
+                        FloatPredicate::ONE,
+                        value,
+                        value.get_type().const_zero(),
+                        "",
+                    )
+                    .unwrap(),
+
+                BasicValueEnum::IntValue(value) => self
+                    .builder
+                    .build_int_compare(IntPredicate::NE, value, value.get_type().const_zero(), "")
+                    .unwrap(),
+
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    /// The right side only runs when the left one doesn't decide the result, which
+    /// is 'false' for 'and' and 'true' for 'or'. Both paths meet in a phi.
+    fn emit_logical(
+        &mut self,
+        left: &'ctx Instruction<'ctx>,
+        right: &'ctx Instruction<'ctx>,
+        and: bool,
+    ) -> IntValue<'ctx> {
+        let function: FunctionValue<'ctx> = self.current_function();
+
+        let lhs: IntValue<'ctx> = self.emit_condition(left);
+        let lhs_block: BasicBlock<'ctx> = self.builder.get_insert_block().unwrap();
+
+        let rhs_block: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+        let merge: BasicBlock<'ctx> = self.context.append_basic_block(function, "");
+
+        if and {
+            self.builder
+                .build_conditional_branch(lhs, rhs_block, merge)
+                .unwrap();
+        } else {
+            self.builder
+                .build_conditional_branch(lhs, merge, rhs_block)
+                .unwrap();
+        }
+
+        self.builder.position_at_end(rhs_block);
+
+        let rhs: IntValue<'ctx> = self.emit_condition(right);
+
+        // A nested 'and' or 'or' on the right side ends in its own merge block.
+        let rhs_block: BasicBlock<'ctx> = self.builder.get_insert_block().unwrap();
+
+        self.builder.build_unconditional_branch(merge).unwrap();
+        self.builder.position_at_end(merge);
+
+        let result: PhiValue<'ctx> = self
+            .builder
+            .build_phi(self.context.bool_type(), "")
+            .unwrap();
+
+        result.add_incoming(&[
+            (
+                &self.context.bool_type().const_int(!and as u64, false),
+                lhs_block,
+            ),
+            (&rhs, rhs_block),
+        ]);
+
+        result.as_basic_value().into_int_value()
+    }
+
+    fn emit_return(&mut self, instr: &'ctx Instruction<'ctx>) {
+        match &instr {
+            Instruction::Null => {
+                self.emit_all_deferred();
+
+                // 'main' returns an 'i32' even without a declared return type.
+                match self.current_function().get_type().get_return_type() {
+                    Some(kind) => self.builder.build_return(Some(&kind.const_zero())).unwrap(),
+                    None => self.builder.build_return(None).unwrap(),
+                };
+            }
+            Instruction::Integer(kind, num) => {
+                let mut value: IntValue<'ctx> = build_const_integer(self.context, kind, *num);
+
+                // The literal has the smallest type that holds it, while 'main' returns an 'i32'.
+                if let Some(BasicTypeEnum::IntType(int)) =
+                    self.current_function().get_type().get_return_type()
+                {
+                    value = self
+                        .builder
+                        .build_int_cast_sign_flag(value, int, kind.is_signed(), "")
+                        .unwrap();
+                }
+
+                self.emit_all_deferred();
+                self.builder.build_return(Some(&value)).unwrap();
+            }
+
+            Instruction::Float(kind, num) => {
+                self.emit_all_deferred();
+                self.builder
+                    .build_return(Some(&build_const_float(self.context, kind, *num)))
+                    .unwrap();
+            }
+
+            Instruction::Boolean(_)
+            | Instruction::Compare { .. }
+            | Instruction::LogicalAnd { .. }
+            | Instruction::LogicalOr { .. }
+            | Instruction::RefVar {
+                kind: DataTypes::Bool,
+                ..
+            } => {
+                let value: IntValue<'ctx> = self.emit_condition(instr);
+
+                self.emit_all_deferred();
+                self.builder.build_return(Some(&value)).unwrap();
+            }
+
+            Instruction::BinaryOp { .. }
+            | Instruction::Unary { .. }
+            | Instruction::Call { .. }
+            | Instruction::Cast { .. }
+            | Instruction::RefVar { .. }
+            | Instruction::Index { .. }
+            | Instruction::Char(_) => {
+                let value: BasicValueEnum<'ctx> = self.emit_numeric(instr);
+
+                self.emit_all_deferred();
+                self.builder.build_return(Some(&value)).unwrap();
+            }
+
+            Instruction::String(string) => {
+                self.emit_all_deferred();
+                self.builder
+                    .build_return(Some(&self.emit_global_string_constant(string)))
+                    .unwrap();
+            }
+
+            instr => self.unsupported(instr, None),
+        }
+    }
+
+    fn emit_function(
+        &mut self,
+        name: &str,
+        params: &[Instruction<'ctx>],
+        body: &'ctx Instruction<'ctx>,
+        return_kind: &Option<DataTypes>,
+        is_public: bool,
+        visibility: Option<GlobalVisibility>,
+        export: bool,
+        naked: bool,
+    ) {
+        let kind: FunctionType = datatype_to_fn_type(self.context, return_kind, params, None);
+
+        // Only the functions visible outside of the module keep their plain name.
+        let symbol: String = match &self.mangle {
+            Some(unit) if !is_public && !export && visibility.is_none() => {
+                mangle_symbol(unit, name)
+            }
+            _ => name.to_string(),
+        };
+
+        let function: FunctionValue<'_> = self.module.add_function(&symbol, kind, None);
+
+        if is_public || visibility.is_some() {
+            function.set_linkage(Linkage::External);
+        } else {
+            function.set_linkage(Linkage::Private);
+        }
+
+        if let Some(visibility) = visibility {
+            function.as_global_value().set_visibility(visibility);
+        }
+
+        // Exported functions keep their plain name and are visible to C callers,
+        // whatever the `public` modifier or visibility attributes say.
+        if export {
+            function.set_linkage(Linkage::External);
+            function
+                .as_global_value()
+                .set_visibility(GlobalVisibility::Default);
+
+            // Only a DLL has an export table, ELF and Mach-O export by visibility.
+            if self
+                .module
+                .get_triple()
+                .as_str()
+                .to_string_lossy()
+                .contains("windows")
+            {
+                function
+                    .as_global_value()
+                    .set_dll_storage_class(DLLStorageClass::Export);
+            }
+        }
+
+        // A naked function has no prologue, so there is no frame pointer to keep.
+        if naked {
+            ["naked", "noinline"].iter().for_each(|attribute| {
+                function.add_attribute(
+                    AttributeLoc::Function,
+                    self.context
+                        .create_enum_attribute(Attribute::get_named_enum_kind_id(attribute), 0),
+                );
+            });
+        } else {
+            self.set_frame_pointer(function);
+        }
+
+        self.set_subprogram(function);
+
+        let mut index: usize = 0;
+
+        function.get_params().iter().for_each(|param| {
+            if let Some(Instruction::Param { name, restrict, .. }) = params.get(index) {
+                param.set_name(name);
+
+                if *restrict {
+                    function.add_attribute(
+                        AttributeLoc::Param(index as u32),
+                        self.context
+                            .create_enum_attribute(Attribute::get_named_enum_kind_id("noalias"), 0),
+                    );
+                }
+            }
+
+            index += 1;
+        });
+
+        let entry: BasicBlock = self.context.append_basic_block(function, "");
+
+        self.builder.position_at_end(entry);
+
+        self.begin_scope();
+
+        // The parameters are copied to the stack like variables, so they can be reassigned.
+        // A naked function has no stack frame and its assembly reads them from the registers.
+        if !naked {
+            params.iter().enumerate().for_each(|(index, param)| {
+                if let Instruction::Param { name, kind, .. } = param {
+                    let value: BasicValueEnum<'ctx> = function.get_nth_param(index as u32).unwrap();
+
+                    let ptr: PointerValue<'ctx> =
+                        self.builder.build_alloca(value.get_type(), "").unwrap();
+
+                    self.builder.build_store(ptr, value).unwrap();
+
+                    self.locals[self.scope].insert(
+                        name,
+                        Instruction::Local(ThrushLocal {
+                            kind: kind.defer(),
+                            ptr,
+                            volatile: false,
+                        }),
+                    );
+                }
+            });
+        }
+
+        self.codegen(body);
+
+        // The assembly of a naked function returns by itself.
+        if naked {
+            self.builder.build_unreachable().unwrap();
+        } else if return_kind.is_none() && !self.is_terminated() {
+            self.builder.build_return(None).unwrap();
+        }
+
+        self.end_scope();
+    }
+
+    /// The operands are passed as the arguments of the assembly, in the order of their constraints.
+    /// The arguments are converted to the types of the parameters, like a C call to a
+    /// prototyped function. Nothing is returned for a 'void' function or a failed call.
+    fn emit_call(
+        &mut self,
+        name: &str,
+        args: &'ctx [Instruction<'ctx>],
+        line: usize,
+        inline: bool,
+    ) -> Option<BasicValueEnum<'ctx>> {
+        let function: FunctionValue<'ctx> = match self.get_function(name) {
+            Some(function) => function,
+            None => {
+                self.errors.push(ThrushError::Scope(
+                    ThrushErrorKind::FunctionNotDefined,
+                    String::from("Undefined Function"),
+                    format!("The function `{}` is not defined before this call.", name),
+                    line,
+                ));
+
+                return None;
+            }
+        };
+
+        let params: Vec<BasicTypeEnum<'ctx>> = function.get_type().get_param_types();
+
+        if params.len() != args.len() {
+            self.errors.push(ThrushError::Compile(format!(
+                "The function `{}` takes {} arguments but {} were given at line {}.",
+                name,
+                params.len(),
+                args.len(),
+                line
+            )));
+
+            return None;
+        }
+
+        let mut values: Vec<BasicMetadataValueEnum<'ctx>> = Vec::with_capacity(args.len());
+
+        for (index, (arg, param)) in args.iter().zip(params).enumerate() {
+            let value: BasicValueEnum<'ctx> = match arg {
+                Instruction::String(string) => self.emit_global_string_constant(string).into(),
+                Instruction::Boolean(_)
+                | Instruction::RefVar {
+                    kind: DataTypes::Bool,
+                    ..
+                } => self.emit_condition(arg).into(),
+                _ => self.emit_numeric(arg),
+            };
+
+            let value: BasicValueEnum<'ctx> = match (value, param) {
+                (BasicValueEnum::IntValue(value), BasicTypeEnum::IntType(param)) => self
+                    .builder
+                    .build_int_cast_sign_flag(value, param, arg.get_data_type().is_signed(), "")
+                    .unwrap()
+                    .into(),
+
+                (BasicValueEnum::FloatValue(value), BasicTypeEnum::FloatType(param)) => self
+                    .builder
+                    .build_float_cast(value, param, "")
+                    .unwrap()
+                    .into(),
+
+                (value, param) if value.get_type() == param => value,
+
+                _ => {
+                    self.errors.push(ThrushError::Compile(format!(
+                        "The argument {} of `{}` doesn't match the type of its parameter at line {}.",
+                        index + 1,
+                        name,
+                        line
+                    )));
+
+                    return None;
+                }
+            };
+
+            values.push(value.into());
+        }
+
+        let call: CallSiteValue<'ctx> = self.builder.build_call(function, &values, "").unwrap();
+
+        if inline {
+            call.add_attribute(
+                AttributeLoc::Function,
+                self.context
+                    .create_enum_attribute(Attribute::get_named_enum_kind_id("alwaysinline"), 0),
+            );
+        }
+
+        call.try_as_basic_value().left()
+    }
+
+    fn emit_inline_asm(
+        &mut self,
+        template: &str,
+        constraints: &str,
+        operands: &'ctx [Instruction<'ctx>],
+        side_effects: bool,
+    ) {
+        let param_types: Vec<BasicMetadataTypeEnum<'ctx>> = operands
+            .iter()
+            .map(|operand| {
+                datatype_to_basicmetadata_type_enum(self.context, &operand.get_data_type())
+            })
+            .collect();
+
+        let args: Vec<BasicMetadataValueEnum<'ctx>> = operands
+            .iter()
+            .map(|operand| self.emit_numeric(operand).into())
+            .collect();
+
+        let kind: FunctionType<'ctx> = self.context.void_type().fn_type(&param_types, false);
+
+        let asm: PointerValue<'ctx> = self.context.create_inline_asm(
+            kind,
+            template.to_string(),
+            constraints.to_string(),
+            side_effects,
+            false,
+            None,
+            false,
+        );
+
+        self.builder
+            .build_indirect_call(kind, asm, &args, "")
+            .unwrap();
+    }
+
+    /// Without '--frame-pointer' the target decides whether to keep the frame pointer.
+    fn set_frame_pointer(&self, function: FunctionValue<'ctx>) {
+        if self.frame_pointer {
+            function.add_attribute(
+                AttributeLoc::Function,
+                self.context.create_string_attribute("frame-pointer", "all"),
+            );
+        }
+    }
+
+    /// With '--emit-llvm' every function gets a subprogram in the line tables, the
+    /// scope the '!dbg' locations of its instructions point to.
+    fn set_subprogram(&self, function: FunctionValue<'ctx>) {
+        if let Some((dibuilder, unit)) = &self.source_map {
+            let kind: DISubroutineType<'ctx> =
+                dibuilder.create_subroutine_type(unit.get_file(), None, &[], DIFlags::ZERO);
+
+            let subprogram: DISubprogram<'ctx> = dibuilder.create_function(
+                unit.as_debug_info_scope(),
+                function.get_name().to_str().unwrap(),
+                None,
+                unit.get_file(),
+                0,
+                kind,
+                true,
+                true,
+                0,
+                DIFlags::ZERO,
+                false,
+            );
+
+            function.set_subprogram(subprogram);
+
+            // Calls in a function with a subprogram need a location, even before the first line.
+            let location: DILocation<'ctx> = dibuilder.create_debug_location(
+                self.context,
+                0,
+                0,
+                subprogram.as_debug_info_scope(),
+                None,
+            );
+
+            self.builder.set_current_debug_location(location);
+        }
+    }
+
+    /// The next instructions are attributed to `line`, until another line is set.
+    fn set_source_line(&self, line: usize) {
+        let Some((dibuilder, _)) = &self.source_map else {
+            return;
+        };
+
+        if let Some(subprogram) = self
+            .builder
+            .get_insert_block()
+            .and_then(|block| block.get_parent())
+            .and_then(|function| function.get_subprogram())
+        {
+            let location: DILocation<'ctx> = dibuilder.create_debug_location(
+                self.context,
+                line as u32,
+                0,
+                subprogram.as_debug_info_scope(),
+                None,
+            );
+
+            self.builder.set_current_debug_location(location);
+        }
+    }
+
+    fn emit_global_boolean(
+        &mut self,
+        value: IntValue<'ctx>,
+        visibility: Option<GlobalVisibility>,
+        thread_local: bool,
+    ) -> PointerValue<'ctx> {
+        let global: GlobalValue<'_> =
+            self.module
+                .add_global(value.get_type(), Some(AddressSpace::default()), "");
+
+        set_symbol_visibility(global, visibility);
+        global.set_thread_local(thread_local);
+        global.set_initializer(&value);
+
+        global.as_pointer_value()
+    }
+
+    /// Constant integer arrays, like lookup tables: a named immutable global.
+    fn emit_array_constant(
+        &mut self,
+        name: &str,
+        kind: &DataTypes,
+        values: &[u64],
+        visibility: Option<GlobalVisibility>,
+    ) -> PointerValue<'ctx> {
+        let element: IntType<'ctx> = datatype_integer_to_type(self.context, kind);
+
+        let values: Vec<IntValue<'ctx>> = values
+            .iter()
+            .map(|value| element.const_int(*value, false))
+            .collect();
+
+        let array: ArrayType<'_> =
+            build_int_array_type_from_size(self.context, kind.defer(), values.len() as u32);
+
+        let global: GlobalValue<'_> =
+            self.module
+                .add_global(array, Some(AddressSpace::default()), name);
+
+        set_symbol_visibility(global, visibility);
+        global.set_initializer(&element.const_array(&values));
+        global.set_constant(true);
+
+        global.as_pointer_value()
+    }
+
+    /// String literals: an anonymous, private and immutable byte array.
+    fn emit_global_string_constant(&mut self, string: &str) -> PointerValue<'ctx> {
+        let kind: ArrayType<'_> = self.context.i8_type().array_type(string.len() as u32);
+        let global: GlobalValue<'_> =
+            self.module
+                .add_global(kind, Some(AddressSpace::default()), "");
+        global.set_linkage(Linkage::Private);
+        global.set_initializer(&self.context.const_string(string.as_ref(), false));
+        global.set_constant(true);
+        global.set_unnamed_addr(true);
+
+        global.as_pointer_value()
+    }
+
+    /// String variables: the same byte array as a literal, but named. Thrush
+    /// never writes a string, so only the ones exported with a visibility stay
+    /// writable, the C code linked against them may own the buffer.
+    fn emit_global_string(
+        &mut self,
+        string: &str,
+        name: &str,
+        visibility: Option<GlobalVisibility>,
+        thread_local: bool,
+    ) -> PointerValue<'ctx> {
+        // Keep at least the terminator so the variable points to a valid C string.
+        let initializer: ArrayValue<'ctx> = if string.is_empty() {
+            build_default_value(self.context, &DataTypes::String).into_array_value()
+        } else {
+            self.context.const_string(string.as_ref(), false)
+        };
+
+        let global: GlobalValue<'_> =
+            self.module
+                .add_global(initializer.get_type(), Some(AddressSpace::default()), name);
+        set_symbol_visibility(global, visibility);
+        global.set_thread_local(thread_local);
+        global.set_initializer(&initializer);
+        global.set_constant(visibility.is_none());
+        global.set_unnamed_addr(visibility.is_none());
+
+        global.as_pointer_value()
+    }
+
+    fn build_const_integer_return(&mut self, kind: IntType, value: u64, signed: bool) {
+        self.builder
+            .build_return(Some(&kind.const_int(value, signed)))
+            .unwrap();
+    }
+
+    /// `self.scope` is the index of the innermost scope, so it's searched first
+    /// and an inner variable shadows an outer one.
+    fn get_local(&self, name: &str, line: usize) -> Result<&Instruction<'ctx>, ThrushError> {
+        for index in (0..=self.scope).rev() {
+            if let Some(instr) = self.locals[index].get(name) {
+                return Ok(instr);
+            }
+        }
+
+        Err(ThrushError::Scope(
+            ThrushErrorKind::VariableNotDefined,
+            String::from("Undefined Variable"),
+            format!("The variable `{}` not found in this scope.", name),
+            line,
+        ))
+    }
+
+    /// With an ordering, the load is atomic.
+    fn load_local(
+        &self,
+        name: &str,
+        ordering: Option<AtomicOrdering>,
+        line: usize,
+    ) -> Result<BasicValueEnum<'ctx>, ThrushError> {
+        let local: &ThrushLocal<'ctx> = match self.get_local(name, line)? {
+            Instruction::Local(local) => local,
+            // A constant that was propagated instead of stored.
+            Instruction::Value(constant) => return Ok(constant.value),
+            _ => unreachable!(),
+        };
+
+        let load: BasicValueEnum<'ctx> = self
+            .builder
+            .build_load(
+                datatype_to_basic_type_enum(self.context, &local.kind),
+                local.ptr,
+                "",
+            )
+            .unwrap();
+
+        let load_instr: InstructionValue<'_> = load.as_instruction_value().unwrap();
+
+        if local.kind != DataTypes::Ptr {
+            load_instr
+                .set_alignment(datatype_alignment(&local.kind))
+                .unwrap();
+        }
+
+        load_instr.set_volatile(local.volatile).unwrap();
+
+        if let Some(ordering) = ordering {
+            load_instr.set_atomic_ordering(ordering).unwrap();
+        }
+
+        Ok(load)
+    }
+
+    /// A private function may have been mangled, a public one keeps its name.
+    fn get_function(&self, name: &str) -> Option<FunctionValue<'ctx>> {
+        self.mangle
+            .as_ref()
+            .and_then(|unit| self.module.get_function(&mangle_symbol(unit, name)))
+            .or_else(|| self.module.get_function(name))
+    }
+
+    fn get_global(&self, name: &str, line: usize) -> Result<&Instruction<'ctx>, ThrushError> {
+        self.globals.get(name).ok_or_else(|| {
+            ThrushError::Scope(
+                ThrushErrorKind::VariableNotDefined,
+                String::from("Undefined Variable"),
+                format!("The variable `{}` not found in this scope.", name),
+                line,
+            )
+        })
+    }
+
+    /// A construct the backend can't lower yet is reported, instead of aborting the compiler.
+    fn unsupported(&mut self, instr: &Instruction, kind: Option<&DataTypes>) {
+        self.errors.push(ThrushError::Compile(match kind {
+            Some(kind) => format!(
+                "The instruction '{}' of type '{}' is not supported here yet.",
+                instr.variant_name(),
+                kind
+            ),
+            None => format!(
+                "The instruction '{}' is not supported here yet.",
+                instr.variant_name()
+            ),
+        }));
+    }
+
+    fn advance(&mut self) -> &'ctx Instruction<'ctx> {
+        let c: &Instruction = &self.instructions[self.current];
+        self.current += 1;
+
+        c
+    }
+
+    fn is_end(&self) -> bool {
+        self.current >= self.instructions.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction<'ctx> {
+    Println(Vec<Instruction<'ctx>>),
+    Print(Vec<Instruction<'ctx>>),
+    EPrintln(Vec<Instruction<'ctx>>),
+    EPrint(Vec<Instruction<'ctx>>),
+    String(String),
+    Integer(DataTypes, u64),
+    Float(DataTypes, f64),
+    Char(u8),
+    Block {
+        stmts: Vec<Instruction<'ctx>>,
+    },
+    EntryPoint {
+        body: Box<Instruction<'ctx>>,
+    },
+    Value(ThrushBasicValueEnum<'ctx>),
+    Local(ThrushLocal<'ctx>),
+    Param {
+        name: &'ctx str,
+        kind: DataTypes,
+        restrict: bool,
+    },
+    Function {
+        name: &'ctx str,
+        params: Vec<Instruction<'ctx>>,
+        body: Box<Instruction<'ctx>>,
+        return_kind: Option<DataTypes>,
+        is_public: bool,
+        visibility: Option<GlobalVisibility>,
+        export: bool,
+        naked: bool,
+    },
+    Return(Box<Instruction<'ctx>>),
+    Var {
+        name: &'ctx str,
+        kind: DataTypes,
+        value: Option<Box<Instruction<'ctx>>>,
+        line: usize,
+        visibility: Option<GlobalVisibility>,
+        thread_local: bool,
+        volatile: bool,
+        is_static: bool,
+    },
+    RefVar {
+        name: &'ctx str,
+        line: usize,
+        kind: DataTypes,
+        /// The ordering of an 'atomic_load', a plain load without it.
+        ordering: Option<AtomicOrdering>,
+    },
+    MutVar {
+        name: &'ctx str,
+        value: Box<Instruction<'ctx>>,
+        kind: DataTypes,
+        line: usize,
+        /// The ordering of an 'atomic_store', a plain store without it.
+        ordering: Option<AtomicOrdering>,
+    },
+    Boolean(bool),
+    Call {
+        name: &'ctx str,
+        args: Vec<Instruction<'ctx>>,
+        kind: DataTypes,
+        line: usize,
+        inline: bool,
+    },
+    ArgCount,
+    ArgAt(Box<Instruction<'ctx>>),
+    GetEnv(String),
+    ToString(Box<Instruction<'ctx>>),
+    BitCast {
+        value: Box<Instruction<'ctx>>,
+        kind: DataTypes,
+    },
+    Cast {
+        value: Box<Instruction<'ctx>>,
+        kind: DataTypes,
+    },
+    Defer(Box<Instruction<'ctx>>),
+    If {
+        condition: Box<Instruction<'ctx>>,
+        then_block: Box<Instruction<'ctx>>,
+        else_block: Option<Box<Instruction<'ctx>>>,
+    },
+    While {
+        condition: Box<Instruction<'ctx>>,
+        body: Box<Instruction<'ctx>>,
+        label: Option<&'ctx str>,
+    },
+    Loop {
+        body: Box<Instruction<'ctx>>,
+        label: Option<&'ctx str>,
+    },
+    Break(Option<&'ctx str>),
+    Fence(AtomicOrdering),
+    ModuleAsm(String),
+    InlineAsm {
+        template: String,
+        constraints: String,
+        operands: Vec<Instruction<'ctx>>,
+        side_effects: bool,
+    },
+    BinaryOp {
+        left: Box<Instruction<'ctx>>,
+        op: TokenKind,
+        right: Box<Instruction<'ctx>>,
+        kind: DataTypes,
+    },
+    Unary {
+        op: TokenKind,
+        operand: Box<Instruction<'ctx>>,
+        kind: DataTypes,
+    },
+    Compare {
+        left: Box<Instruction<'ctx>>,
+        op: TokenKind,
+        right: Box<Instruction<'ctx>>,
+        kind: DataTypes,
+    },
+    LogicalAnd {
+        left: Box<Instruction<'ctx>>,
+        right: Box<Instruction<'ctx>>,
+    },
+    LogicalOr {
+        left: Box<Instruction<'ctx>>,
+        right: Box<Instruction<'ctx>>,
+    },
+    ConstArray {
+        name: &'ctx str,
+        kind: DataTypes,
+        values: Vec<u64>,
+        visibility: Option<GlobalVisibility>,
+    },
+    Array {
+        elements: Vec<Instruction<'ctx>>,
+        kind: DataTypes,
+    },
+    Index {
+        array: &'ctx str,
+        index: Box<Instruction<'ctx>>,
+        kind: DataTypes,
+        line: usize,
+    },
+    Null,
+}
+
+#[derive(Default, Debug)]
+pub enum Opt {
+    #[default]
+    None,
+    Low,
+    Mid,
+    Mcqueen,
+}
+
+impl Opt {
+    /// The passes given to 'opt', in the order they run.
+    pub fn passes(&self) -> [&'static str; 9] {
+        let level: &str = match self {
+            Opt::None => "default<O0>",
+            Opt::Low => "default<O1>",
+            Opt::Mid => "default<O2>",
+            Opt::Mcqueen => "default<O3>",
+        };
+
+        [
+            level,
+            "globalopt",
+            "globaldce",
+            "dce",
+            "instcombine",
+            "strip-dead-prototypes",
+            "strip",
+            "mem2reg",
+            "memcpyopt",
+        ]
+    }
+}
+
+/// How 'print' writes floats: the shortest form, '1' and '0.25', or always six
+/// decimals, '1.000000' and '0.250000'.
+#[derive(Default, Debug, Clone, Copy)]
+pub enum FloatFormat {
+    #[default]
+    Shortest,
+    Fixed,
+}
+
+impl FloatFormat {
+    pub fn spec(&self) -> &'static str {
+        match self {
+            FloatFormat::Shortest => "%g",
+            FloatFormat::Fixed => "%f",
+        }
+    }
+}
+
+/// What the runtime checks call when they fail.
+#[derive(Debug, Clone)]
+pub enum Abort {
+    Function(String),
+    Trap,
+}
+
+#[derive(Default, Debug)]
+pub enum Linking {
+    #[default]
+    Static,
+    Dynamic,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub name: String,
+    pub target_triple: TargetTriple,
+    pub target_cpu: String,
+    pub target_features: String,
+    pub data_layout: Option<String>,
+    pub optimization: Opt,
+    pub interpret: bool,
+    pub emit_llvm: bool,
+    pub emit_assembly: bool,
+    pub emit_object: bool,
+    pub build: bool,
+    pub linking: Linking,
+    pub path: PathBuf,
+    pub is_main: bool,
+    pub reloc_mode: RelocMode,
+    pub code_model: CodeModel,
+    pub dump_timings_json: bool,
+    pub force: bool,
+    pub print_ir_after_pass: bool,
+    pub dump_tokens: bool,
+    pub dump_deps: bool,
+    pub dump_used_libc: bool,
+    pub dump_cfg: bool,
+    pub dump_metrics: bool,
+    pub stats: bool,
+    pub emit_dep_info: bool,
+    pub abort: Option<Abort>,
+    pub print_passes: bool,
+    pub frame_pointer: bool,
+    pub float_format: FloatFormat,
+    pub mangle: bool,
+    pub strip: bool,
+    pub sanitizers: Vec<String>,
+    pub clang_path: String,
+    pub run: bool,
+    pub fail_fast: bool,
+    pub max_errors: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            name: String::from("main"),
+            target_triple: TargetMachine::get_default_triple(),
+            target_cpu: String::new(),
+            target_features: String::new(),
+            data_layout: None,
+            optimization: Opt::default(),
+            interpret: false,
+            emit_llvm: false,
+            emit_assembly: false,
+            emit_object: false,
+            build: false,
+            linking: Linking::default(),
+            path: PathBuf::new(),
+            is_main: true,
+            reloc_mode: RelocMode::Default,
+            code_model: CodeModel::Default,
+            dump_timings_json: false,
+            force: false,
+            print_ir_after_pass: false,
+            dump_tokens: false,
+            dump_deps: false,
+            dump_used_libc: false,
+            dump_cfg: false,
+            dump_metrics: false,
+            stats: false,
+            emit_dep_info: false,
+            abort: None,
+            print_passes: false,
+            frame_pointer: false,
+            float_format: FloatFormat::default(),
+            mangle: false,
+            strip: false,
+            sanitizers: Vec::new(),
+            clang_path: find_clang(),
+            run: false,
+            fail_fast: false,
+            max_errors: 50,
+        }
+    }
+}
+
+/// The first clang in the PATH that reads the bitcode of LLVM 18, a versioned name
+/// first since a plain 'clang' may be another release. Without one, 'clang-18' is
+/// kept so the error names it.
+pub fn find_clang() -> String {
+    let paths: Vec<PathBuf> = env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).collect())
+        .unwrap_or_default();
+
+    ["clang-18", "clang"]
+        .iter()
+        .find(|name| paths.iter().any(|path| path.join(name).is_file()))
+        .unwrap_or(&"clang-18")
+        .to_string()
+}
+
+impl Options {
+    /// Without an explicit choice, freestanding targets trap since they have no libc 'abort'.
+    pub fn abort(&self) -> Abort {
+        self.abort.clone().unwrap_or_else(|| {
+            if self.target_triple.to_string().contains("-none") {
+                Abort::Trap
+            } else {
+                Abort::Function(String::from("abort"))
+            }
+        })
+    }
+
+    /// How many errors a frontend phase collects before it stops and reports them.
+    pub fn max_errors(&self) -> usize {
+        if self.fail_fast {
+            1
+        } else {
+            self.max_errors
+        }
+    }
+
+    /// Rejects the flags that contradict each other, instead of letting one of them win silently.
+    pub fn validate(&self) -> Result<(), ThrushError> {
+        let conflicts: [(bool, &str); 11] = [
+            (
+                self.emit_llvm && self.emit_object,
+                "'--emit-llvm' writes LLVM IR, it can't also emit an object with '--lib'.",
+            ),
+            (
+                self.emit_llvm && self.build,
+                "'--emit-llvm' writes LLVM IR, it can't also build an executable.",
+            ),
+            (
+                self.emit_assembly && (self.emit_llvm || self.emit_object),
+                "'--emit-asm' writes assembly, it can't also write LLVM IR or an object.",
+            ),
+            (
+                self.emit_assembly && self.build,
+                "'--emit-asm' writes assembly, it can't also build an executable.",
+            ),
+            (
+                self.emit_assembly && !self.sanitizers.is_empty(),
+                "The sanitizers are applied by clang, it can't write assembly with '--sanitize'.",
+            ),
+            (
+                self.emit_object && self.build,
+                "'--lib' emits an object, it can't also build an executable.",
+            ),
+            (
+                self.interpret
+                    && (self.build || self.emit_llvm || self.emit_assembly || self.emit_object),
+                "The interpreter runs the code in memory, it can't also write an output.",
+            ),
+            (
+                self.strip && !self.build,
+                "'--strip' only applies to a linked executable, use it with '--build'.",
+            ),
+            (
+                self.interpret && self.target_triple != TargetMachine::get_default_triple(),
+                "The interpreter runs the code on this machine, it can't target another one.",
+            ),
+            (
+                self.interpret && !self.sanitizers.is_empty(),
+                "The interpreter has no sanitizer runtime, build an executable to use '--sanitize'.",
+            ),
+            (
+                self.sanitizers.len() > 1,
+                "The 'address', 'thread' and 'memory' sanitizers can't be combined, use only one.",
+            ),
+        ];
+
+        match conflicts.iter().find(|(conflict, _)| *conflict) {
+            Some((_, error)) => Err(ThrushError::Compile(String::from(*error))),
+            None => Ok(()),
+        }
+    }
+
+    pub fn output_path(&self) -> PathBuf {
+        if self.emit_llvm {
+            return PathBuf::from(format!("{}.ll", self.name));
+        }
+
+        if self.emit_assembly {
+            return PathBuf::from(format!("{}.s", self.name));
+        }
+
+        if self.build {
+            return PathBuf::from(&self.name);
+        }
+
+        PathBuf::from(format!("{}.o", self.name))
+    }
+}
+
+pub struct FileBuilder<'a, 'ctx> {
+    module: &'a Module<'ctx>,
+    options: &'a Options,
+    machine: &'a TargetMachine,
+}
+
+impl<'a, 'ctx> FileBuilder<'a, 'ctx> {
+    pub fn new(options: &'a Options, module: &'a Module<'ctx>, machine: &'a TargetMachine) -> Self {
+        Self {
+            options,
+            module,
+            machine,
+        }
+    }
+
+    pub fn build(self) -> bool {
+        if let Err(ThrushError::Compile(error)) = self.options.validate() {
+            logging::log(logging::LogType::ERROR, &error);
+            return false;
+        }
+
+        if self.options.emit_llvm {
+            self.module
+                .print_to_file(format!("{}.ll", self.options.name))
+                .unwrap();
+            return true;
+        }
+
+        self.module
+            .write_bitcode_to_path(Path::new(&format!("{}.bc", self.options.name)));
+
+        let built: Result<(), ThrushError> = self.build_bitcode();
+
+        // The bitcode is only an intermediate file, it goes whether the build worked or not.
+        if let Err(error) = remove_file(format!("{}.bc", self.options.name)) {
+            logging::log_internal(&error.to_string(), file!(), line!());
+        }
+
+        if let Err(ThrushError::Compile(error)) = built {
+            // A failed step can leave a partial object or executable behind.
+            let output: PathBuf = self.options.output_path();
+
+            if output.exists() {
+                if let Err(error) = remove_file(&output) {
+                    logging::log_internal(&error.to_string(), file!(), line!());
+                }
+            }
+
+            logging::log(logging::LogType::ERROR, &error);
+
+            return false;
+        }
+
+        true
+    }
+
+    /// Optimizes `{name}.bc` and turns it into the output of the options.
+    fn build_bitcode(&self) -> Result<(), ThrushError> {
+        // clang is only needed to link and to run the sanitizer instrumentation, LLVM
+        // emits the object or the assembly itself for the target of the options.
+        if !self.options.build && self.options.sanitizers.is_empty() {
+            self.opt()?;
+
+            if self.options.emit_assembly {
+                return self.emit_file(FileType::Assembly);
+            }
+
+            return self.emit_file(FileType::Object);
+        }
+
+        let linking: &str = match self.options.linking {
+            Linking::Static => "--static",
+            Linking::Dynamic => "-dynamic",
+        };
+
+        let reloc: &[&str] = match self.options.reloc_mode {
+            RelocMode::Default => &[],
+            RelocMode::Static => &["-fno-pic"],
+            RelocMode::PIC => &["-fPIC"],
+            RelocMode::DynamicNoPic => &["-mdynamic-no-pic"],
+        };
+
+        // Only the linked executable has a symbol table worth stripping.
+        let strip: &[&str] = if self.options.strip { &["-s"] } else { &[] };
+
+        // Runs the instrumentation on the functions marked for it, and links the runtime.
+        let sanitize: Option<String> = (!self.options.sanitizers.is_empty())
+            .then(|| format!("-fsanitize={}", self.options.sanitizers.join(",")));
+
+        let target: String = format!(
+            "--target={}",
+            self.options.target_triple.as_str().to_string_lossy()
+        );
+
+        match Command::new(&self.options.clang_path).spawn() {
+            Ok(mut child) => {
+                child.kill().unwrap();
+            }
+            Err(error) => {
+                return Err(ThrushError::Compile(format!(
+                    "Compilation failed. Cannot run clang '{}': {}. Pass another one with '--clang'.",
+                    self.options.clang_path, error
+                )));
+            }
+        }
+
+        self.opt()?;
+
+        if self.options.build {
+            Self::run(
+                Command::new(&self.options.clang_path)
+                    .arg("-opaque-pointers")
+                    .arg(&target)
+                    .arg(linking)
+                    .args(reloc)
+                    .args(strip)
+                    .args(&sanitize)
+                    .arg("-ffast-math")
+                    .arg(format!("{}.bc", self.options.name))
+                    .arg("-o")
+                    .arg(self.options.name.as_str()),
+                "clang",
+            )
+        } else {
+            Self::run(
+                Command::new(&self.options.clang_path)
+                    .arg("-opaque-pointers")
+                    .arg(&target)
+                    .arg(linking)
+                    .args(reloc)
+                    .args(&sanitize)
+                    .arg("-ffast-math")
+                    .arg("-c")
+                    .arg(format!("{}.bc", self.options.name))
+                    .arg("-o")
+                    .arg(format!("{}.o", self.options.name)),
+                "clang",
+            )
+        }
+    }
+
+    /// Runs a step of the toolchain to completion, a nonzero exit status fails the build
+    /// with what the tool wrote to stderr.
+    fn run(command: &mut Command, tool: &str) -> Result<(), ThrushError> {
+        let output: Output = command.output().map_err(|error| {
+            ThrushError::Compile(format!(
+                "Compilation failed. Cannot run {}: {}.",
+                tool, error
+            ))
+        })?;
+
+        if !output.status.success() {
+            let stderr: String = String::from_utf8_lossy(&output.stderr)
+                .trim_end()
+                .to_string();
+
+            if stderr.is_empty() {
+                return Err(ThrushError::Compile(format!(
+                    "Compilation failed. {} failed with {}.",
+                    tool, output.status
+                )));
+            }
+
+            return Err(ThrushError::Compile(format!(
+                "Compilation failed. {} failed with {}:\n\n{}",
+                tool, output.status, stderr
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Writes `{name}.o` or `{name}.s` from the optimized bitcode with the target machine,
+    /// so the output follows the triple, relocation model and code model of the options.
+    fn emit_file(&self, file_type: FileType) -> Result<(), ThrushError> {
+        let optimized: Module<'ctx> = Module::parse_bitcode_from_path(
+            format!("{}.bc", self.options.name),
+            self.module.get_context(),
+        )
+        .map_err(|error| {
+            ThrushError::Compile(format!(
+                "Compilation failed. Cannot read the optimized bitcode: {}",
+                error.to_string().trim_end()
+            ))
+        })?;
+
+        let (extension, output): (&str, &str) = match file_type {
+            FileType::Assembly => ("s", "assembly"),
+            FileType::Object => ("o", "object file"),
+        };
+
+        self.machine
+            .write_to_file(
+                &optimized,
+                file_type,
+                Path::new(&format!("{}.{}", self.options.name, extension)),
+            )
+            .map_err(|error| {
+                ThrushError::Compile(format!(
+                    "Compilation failed. Cannot emit the {} for '{}': {}",
+                    output,
+                    self.options.target_triple.as_str().to_string_lossy(),
+                    error.to_string().trim_end()
+                ))
+            })
+    }
+
+    fn opt(&self) -> Result<(), ThrushError> {
+        match Command::new("opt").spawn() {
+            Ok(mut child) => {
+                child.kill().unwrap();
+
+                Self::run(
+                    Command::new("opt")
+                        .arg(format!(
+                            "-passes={}",
+                            self.options.optimization.passes().join(",")
+                        ))
+                        .arg(format!("{}.bc", self.options.name))
+                        .arg("-o")
+                        .arg(format!("{}.bc", self.options.name)),
+                    "opt",
+                )?;
+
+                if self.options.print_ir_after_pass {
+                    self.print_optimized_ir();
+                }
+
+                Ok(())
+            }
+
+            Err(_) => Err(ThrushError::Compile(String::from(
+                "Compilation failed. LLVM Optimizer is not installed.",
+            ))),
+        }
+    }
+
+    fn print_optimized_ir(&self) {
+        match Module::parse_bitcode_from_path(
+            format!("{}.bc", self.options.name),
+            self.module.get_context(),
+        ) {
+            Ok(optimized) => {
+                logging::log(
+                    logging::LogType::INFO,
+                    &format!(
+                        "IR after the optimization passes:\n\n{}",
+                        optimized.print_to_string().to_string()
+                    ),
+                );
+            }
+
+            Err(error) => {
+                logging::log_internal(&error.to_string(), file!(), line!());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::{
+            super::super::frontend::{
+                lexer::{Lexer, Token},
+                parser::Parser,
+            },
+            *,
+        },
+        inkwell::targets::{InitializationConfig, Target},
+        std::{
+            fs,
+            os::unix::fs::PermissionsExt,
+            sync::{Mutex, MutexGuard, PoisonError},
+        },
+    };
+
+    static FRONTEND: Mutex<()> = Mutex::new(());
+
+    fn temp_name(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("thrush-test-{}", name))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// A source file the diagnostics read the code from, through 'PATH'. The frontend
+    /// runs one test at a time while it lives, and the file goes with it.
+    pub(crate) struct SourceFile {
+        path: String,
+        _frontend: MutexGuard<'static, ()>,
+    }
+
+    impl Drop for SourceFile {
+        fn drop(&mut self) {
+            let _ = remove_file(&self.path);
+        }
+    }
+
+    pub(crate) fn source_file(name: &str, source: &str) -> SourceFile {
+        let frontend: MutexGuard<'static, ()> =
+            FRONTEND.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let path: String = format!("{}.th", temp_name(name));
+
+        fs::write(&path, source).unwrap();
+        *PATH.lock().unwrap() = path.clone();
+
+        SourceFile {
+            path,
+            _frontend: frontend,
+        }
+    }
+
+    /// Lowers `source` with the default options and returns the IR of the module.
+    pub(crate) fn compile(name: &str, source: &str) -> Result<String, ThrushError> {
+        let _source: SourceFile = source_file(name, source);
+
+        let options: Options = Options::default();
+
+        let mut lexer: Lexer = Lexer::new(source.as_bytes());
+        let tokens: &[Token] = lexer.lex(options.max_errors()).unwrap();
+
+        let mut parser: Parser = Parser::new();
+
+        parser.tokens = Some(tokens);
+        parser.options = Some(&options);
+
+        lower(parser.start().unwrap())
+    }
+
+    /// The IR of `instructions`, without the frontend.
+    fn lower(instructions: &[Instruction<'_>]) -> Result<String, ThrushError> {
+        let options: Options = Options::default();
+
+        let context: Context = Context::create();
+        let builder: Builder<'_> = context.create_builder();
+        let module: Module<'_> = context.create_module(&options.name);
+
+        Compiler::compile(
+            &module,
+            &builder,
+            &context,
+            instructions,
+            options.abort(),
+            false,
+            FloatFormat::default(),
+            false,
+            false,
+        )?;
+
+        Ok(module.print_to_string().to_string())
+    }
+
+    #[test]
+    fn arithmetic_of_constants_is_folded() {
+        let ir: String = compile(
+            "arithmetic-constants",
+            "fn main() { let x i32 = 2 * 3 + 4; println(x); }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("store i32 10, ptr %1, align 4"), "{}", ir);
+    }
+
+    #[test]
+    fn arithmetic_of_a_variable_is_lowered_in_order() {
+        let ir: String = compile(
+            "arithmetic",
+            "fn main() { let a = 2; a = 3; let x = a * 3 + 4; println(x); }",
+        )
+        .unwrap();
+
+        let mul: usize = ir.find("= mul i8 %3, 3").expect(&ir);
+        let add: usize = ir.find("= add i8 %4, 4").expect(&ir);
+
+        assert!(mul < add, "{}", ir);
+        assert!(ir.contains("store i8 %5, ptr %2"), "{}", ir);
+    }
+
+    #[test]
+    fn float_arithmetic_uses_the_float_instructions() {
+        let ir: String = compile(
+            "arithmetic-float",
+            "fn main() { let a = 7.5; a = 8.0; let y = a / 2.5 - 1.0; println(y); }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("fdiv double"), "{}", ir);
+        assert!(ir.contains("fsub double"), "{}", ir);
+    }
+
+    fn host_machine() -> TargetMachine {
+        Target::initialize_native(&InitializationConfig::default()).unwrap();
+
+        let triple: TargetTriple = TargetMachine::get_default_triple();
+
+        Target::from_triple(&triple)
+            .unwrap()
+            .create_target_machine(
+                &triple,
+                "",
+                "",
+                inkwell::OptimizationLevel::None,
+                RelocMode::Default,
+                CodeModel::Default,
+            )
+            .unwrap()
+    }
+
+    /// `int main() { return 0; }`
+    fn main_module(context: &Context) -> Module<'_> {
+        let module: Module<'_> = context.create_module("main");
+        let builder: Builder<'_> = context.create_builder();
+
+        let main: FunctionValue<'_> =
+            module.add_function("main", context.i32_type().fn_type(&[], false), None);
+
+        builder.position_at_end(context.append_basic_block(main, ""));
+        builder
+            .build_return(Some(&context.i32_type().const_zero()))
+            .unwrap();
+
+        module
+    }
+
+    fn has_opt() -> bool {
+        Command::new("opt").arg("--version").output().is_ok()
+    }
+
+    #[test]
+    fn integer_literals_round_trip_through_codegen() {
+        let ir: String = compile(
+            "integer-limits",
+            "fn main() {
+                let a = 9223372036854775807; a = 9223372036854775807;
+                let b = 18446744073709551615; b = 18446744073709551615;
+                let c i64 = -9223372036854775808; c = -9223372036854775808;
+                println(a, b, c);
+            }",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains("store i64 9223372036854775807, ptr %1"),
+            "{}",
+            ir
+        );
+        // The bits of 'u64::MAX', LLVM prints the constants as signed.
+        assert!(ir.contains("store i64 -1, ptr %2"), "{}", ir);
+        assert!(
+            ir.contains("store i64 -9223372036854775808, ptr %3"),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn an_i64_local_is_aligned_to_8_bytes() {
+        let ir: String = compile(
+            "i64-alignment",
+            "fn main() { let a i64 = -5; a = -6; println(a); }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("%1 = alloca i64, align 8"), "{}", ir);
+        assert!(ir.contains("store i64 -6, ptr %1, align 8"), "{}", ir);
+        assert!(ir.contains("load i64, ptr %1, align 8"), "{}", ir);
+    }
+
+    #[test]
+    fn if_else_branches_to_both_blocks_and_merges() {
+        let ir: String = compile(
+            "if-else",
+            "fn main() { let x = true; x = false; if x { println(\"a\"); } else { println(\"b\"); } }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("br i1 %1, label %2, label %4"), "{}", ir);
+        assert!(ir.contains("call i32 (ptr, ...) @printf(ptr @1)"), "{}", ir);
+        assert!(ir.contains("call i32 (ptr, ...) @printf(ptr @2)"), "{}", ir);
+        assert_eq!(ir.matches("br label %6").count(), 2, "{}", ir);
+        assert!(ir.contains("; preds = %4, %2"), "{}", ir);
+    }
+
+    #[test]
+    fn if_else_without_a_merge_when_both_branches_return() {
+        let ir: String = compile(
+            "if-else-return",
+            "fn f() u8 { let x = true; x = false; if x { return 1; } else { return 2; } } fn main() { f(); }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("ret i8 1"), "{}", ir);
+        assert!(ir.contains("ret i8 2"), "{}", ir);
+        assert!(!ir.contains("br label"), "{}", ir);
+    }
+
+    #[test]
+    fn while_branches_back_to_its_header() {
+        let ir: String = compile(
+            "while",
+            "fn main() { let i = 0; while i < 10 { println(i); i = i + 1; } }",
+        )
+        .unwrap();
+
+        let (entry, header): (&str, &str) = ir.split_once("\n2:").expect(&ir);
+        let (header, body): (&str, &str) = header.split_once("\n5:").expect(&ir);
+        let (body, _): (&str, &str) = body.split_once("\n11:").expect(&ir);
+
+        assert!(entry.trim_end().ends_with("br label %2"), "{}", ir);
+        assert!(header.contains("; preds = %5, %0"), "{}", ir);
+
+        // The condition is loaded again on every iteration.
+        assert!(header.contains("%3 = load i8, ptr %1"), "{}", ir);
+        assert!(header.contains("br i1 %4, label %5, label %11"), "{}", ir);
+
+        assert!(body.trim_end().ends_with("br label %2"), "{}", ir);
+    }
+
+    #[test]
+    fn a_nested_block_shadows_the_outer_variable() {
+        let ir: String = compile(
+            "shadowing",
+            "fn main() { let x = 1; x = 2; { let x = 300; x = 400; println(x); } println(x); }",
+        )
+        .unwrap();
+
+        // The inner 'x' has its own storage, the outer one is read again after the block.
+        assert!(ir.contains("%2 = alloca i16"), "{}", ir);
+        assert!(ir.contains("store i16 400, ptr %2"), "{}", ir);
+        assert!(ir.contains("%3 = load i16, ptr %2"), "{}", ir);
+        assert!(ir.contains("%6 = load i8, ptr %1"), "{}", ir);
+        assert!(ir.contains("@printf(ptr @1, i32 %7)"), "{}", ir);
+    }
+
+    #[test]
+    fn a_nested_block_shadows_an_outer_constant() {
+        let ir: String = compile(
+            "shadowing-constants",
+            "fn main() { let x = 1; { let x = 300; println(x); } println(x); }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("@printf(ptr @0, i32 300)"), "{}", ir);
+        assert!(ir.contains("@printf(ptr @1, i32 1)"), "{}", ir);
+    }
+
+    #[test]
+    fn println_ends_its_format_string_with_a_newline() {
+        let ir: String = compile(
+            "print-println",
+            "fn main() { print(\"a\"); println(\"a\"); }",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains("@0 = private unnamed_addr constant [2 x i8] c\"a\\00\""),
+            "{}",
+            ir
+        );
+        assert!(
+            ir.contains("@1 = private unnamed_addr constant [3 x i8] c\"a\\0A\\00\""),
+            "{}",
+            ir
+        );
+
+        // A single call each, the newline isn't printed on its own.
+        assert_eq!(
+            ir.matches("call i32 (ptr, ...) @printf").count(),
+            2,
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn a_counter_incremented_in_a_loop_is_read_from_its_storage() {
+        let ir: String = compile(
+            "loop-counter",
+            "fn main() { let i = 0; while i < 10 { i = i + 1; } println(i); }",
+        )
+        .unwrap();
+
+        let (body, exit): (&str, &str) = ir.split_once("\n8:").expect(&ir);
+
+        assert!(body.contains("%7 = add i8 %6, 1"), "{}", ir);
+        assert!(body.contains("store i8 %7, ptr %1"), "{}", ir);
+
+        // After the loop, the value printed is the one in memory and not the initial '0'.
+        assert!(exit.contains("%9 = load i8, ptr %1"), "{}", ir);
+        assert!(exit.contains("%10 = zext i8 %9 to i32"), "{}", ir);
+        assert!(exit.contains("@printf(ptr @0, i32 %10)"), "{}", ir);
+    }
+
+    /// The predicates of `==`, `!=`, `<`, `<=`, `>` and `>=`, in that order, between
+    /// the variables 'a' and 'b' of `declare`.
+    fn comparisons(name: &str, declare: &str) -> Vec<String> {
+        let ir: String = compile(
+            name,
+            &format!(
+                "fn main() {{ {} if a == b {{}} if a != b {{}} if a < b {{}} if a <= b {{}} if a > b {{}} if a >= b {{}} }}",
+                declare
+            ),
+        )
+        .unwrap();
+
+        ir.lines()
+            .filter_map(|line| line.split_once(" = ").map(|(_, instr)| instr))
+            .filter(|instr| instr.starts_with("icmp") || instr.starts_with("fcmp"))
+            .map(|instr| instr.split(' ').take(3).collect::<Vec<&str>>().join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn comparisons_of_signed_integers() {
+        assert_eq!(
+            comparisons(
+                "compare-signed",
+                "let a i64 = -1; a = -2; let b i64 = -3; b = -4;"
+            ),
+            [
+                "icmp eq i64",
+                "icmp ne i64",
+                "icmp slt i64",
+                "icmp sle i64",
+                "icmp sgt i64",
+                "icmp sge i64"
+            ]
+        );
+    }
+
+    #[test]
+    fn comparisons_of_unsigned_integers() {
+        assert_eq!(
+            comparisons("compare-unsigned", "let a = 1; a = 2; let b = 3; b = 4;"),
+            [
+                "icmp eq i8",
+                "icmp ne i8",
+                "icmp ult i8",
+                "icmp ule i8",
+                "icmp ugt i8",
+                "icmp uge i8"
+            ]
+        );
+    }
+
+    #[test]
+    fn comparisons_of_floats() {
+        assert_eq!(
+            comparisons(
+                "compare-floats",
+                "let a = 1.5; a = 2.5; let b = 3.5; b = 4.5;"
+            ),
+            [
+                "fcmp oeq double",
+                "fcmp une double",
+                "fcmp olt double",
+                "fcmp ole double",
+                "fcmp ogt double",
+                "fcmp oge double"
+            ]
+        );
+    }
+
+    #[test]
+    fn a_comparison_is_the_condition_of_an_if() {
+        let ir: String = compile(
+            "compare-if",
+            "fn main() { let x = 3; x = 4; if x < 5 { println(\"yes\"); } }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("%3 = icmp ult i8 %2, 5"), "{}", ir);
+        assert!(ir.contains("br i1 %3, label %4, label %6"), "{}", ir);
+    }
+
+    #[test]
+    fn a_constant_condition_emits_only_the_taken_side() {
+        let ir: String = compile(
+            "constant-if",
+            "fn main() {\n    if true {\n        println(\"a\");\n    }\n    if 3 > 5 {\n        println(\"b\");\n    } else {\n        println(\"c\");\n    }\n    while false {\n        println(\"d\");\n    }\n}",
+        )
+        .unwrap();
+
+        assert!(!ir.contains("br "), "{}", ir);
+        assert!(ir.contains("c\"a\\0A\\00\""), "{}", ir);
+        assert!(ir.contains("c\"c\\0A\\00\""), "{}", ir);
+        assert!(!ir.contains("c\"b\\0A\\00\""), "{}", ir);
+        assert!(!ir.contains("c\"d\\0A\\00\""), "{}", ir);
+        assert_eq!(
+            ir.matches("call i32 (ptr, ...) @printf").count(),
+            2,
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn a_labeled_break_leaves_the_outer_loop() {
+        let ir: String = compile(
+            "labeled-break",
+            "fn main() {\n    'outer: loop {\n        loop {\n            break 'outer;\n        }\n    }\n    println(\"done\");\n}",
+        )
+        .unwrap();
+
+        let blocks: Vec<&str> = ir.split("\n\n").collect();
+
+        // The body of the inner loop jumps past both loops, to the code after the outer one.
+        let inner: &str = blocks
+            .iter()
+            .find(|block| block.starts_with("4:"))
+            .expect(&ir);
+        let after: &str = blocks
+            .iter()
+            .find(|block| block.contains("@printf"))
+            .expect(&ir);
+
+        assert!(inner.contains("; preds = %1\n  br label %2"), "{}", ir);
+        assert!(after.starts_with("2:"), "{}", ir);
+    }
+
+    #[test]
+    fn and_skips_its_right_side_when_the_left_is_false() {
+        let ir: String = compile(
+            "logical-and",
+            "fn main() { let x = 0; x = 2; if x != 0 && 10 / x > 1 { println(\"yes\"); } }",
+        )
+        .unwrap();
+
+        let (left, right): (&str, &str) = ir.split_once("\n4:").expect(&ir);
+
+        assert!(!left.contains("udiv"), "{}", ir);
+        assert!(left.contains("br i1 %3, label %4, label %8"), "{}", ir);
+        assert!(right.contains("%6 = udiv i8 10, %5"), "{}", ir);
+        assert!(
+            ir.contains("%9 = phi i1 [ false, %0 ], [ %7, %4 ]"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("br i1 %9, label %10, label %12"), "{}", ir);
+    }
+
+    #[test]
+    fn or_skips_its_right_side_when_the_left_is_true() {
+        let ir: String = compile(
+            "logical-or",
+            "fn main() { let x = 0; x = 2; if x == 0 || 10 / x > 1 { println(\"yes\"); } }",
+        )
+        .unwrap();
+
+        let (left, right): (&str, &str) = ir.split_once("\n4:").expect(&ir);
+
+        assert!(!left.contains("udiv"), "{}", ir);
+        assert!(left.contains("br i1 %3, label %8, label %4"), "{}", ir);
+        assert!(right.contains("%6 = udiv i8 10, %5"), "{}", ir);
+        assert!(
+            ir.contains("%9 = phi i1 [ true, %0 ], [ %7, %4 ]"),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn negation_of_an_i32() {
+        let ir: String = compile(
+            "negate-i32",
+            "fn main() { let x i32 = -5; x = -6; let y = -x; println(y); }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("store i32 -5, ptr %1"), "{}", ir);
+        assert!(ir.contains("%4 = sub i32 0, %3"), "{}", ir);
+        assert!(ir.contains("store i32 %4, ptr %2"), "{}", ir);
+    }
+
+    #[test]
+    fn not_of_a_boolean() {
+        let ir: String = compile(
+            "not",
+            "fn main() { let c = true; c = false; let d = !c; let e = !true; println(d, e); }",
+        )
+        .unwrap();
+
+        // '!true' is folded into the initializer, '!c' is computed and stored.
+        assert!(ir.contains("@2 = private global i1 false"), "{}", ir);
+        assert!(ir.contains("%2 = xor i1 %1, true"), "{}", ir);
+        assert!(ir.contains("store i1 %2, ptr @1"), "{}", ir);
+    }
+
+    #[test]
+    fn an_undefined_variable_is_a_scope_error() {
+        let _source: SourceFile = source_file("undefined", "fn main() {\n    println(x);\n}");
+
+        // The parser rejects it first, so the instructions are built by hand.
+        let instructions: [Instruction<'_>; 1] = [Instruction::EntryPoint {
+            body: Box::new(Instruction::Block {
+                stmts: vec![Instruction::Println(vec![Instruction::RefVar {
+                    name: "x",
+                    line: 2,
+                    kind: DataTypes::U8,
+                    ordering: None,
+                }])],
+            }),
+        }];
+
+        match lower(&instructions) {
+            Err(ThrushError::Scope(ThrushErrorKind::VariableNotDefined, _, help, 2)) => {
+                assert_eq!(help, "The variable `x` not found in this scope.");
+            }
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn two_entry_points_are_a_compile_error() {
+        let _source: SourceFile = source_file("two-entry-points", "fn main() {}\n\nfn main() {}");
+
+        // The parser rejects the second one first, so the instructions are built by hand.
+        let entry_point = || Instruction::EntryPoint {
+            body: Box::new(Instruction::Block { stmts: Vec::new() }),
+        };
+
+        match lower(&[entry_point(), entry_point()]) {
+            Err(ThrushError::Compile(error)) => assert_eq!(
+                error,
+                "Multiple entry points defined, 'main' is declared 2 times in this file."
+            ),
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn an_unsupported_instruction_is_a_compile_error() {
+        let printed: ThrushError = compile(
+            "unsupported-print",
+            "fn main() { let a = 1; a = 2; println(a < 3); }",
+        )
+        .unwrap_err();
+
+        match printed {
+            ThrushError::Compile(error) => {
+                assert_eq!(
+                    error,
+                    "The instruction 'Compare' is not supported here yet."
+                )
+            }
+            error => panic!("{:?}", error),
+        }
+
+        let declared: ThrushError = compile(
+            "unsupported-let",
+            "fn main() { let a = 1; a = 2; let b = a < 3; }",
+        )
+        .unwrap_err();
+
+        match declared {
+            ThrushError::Compile(error) => assert_eq!(
+                error,
+                "The instruction 'Compare' of type 'bool' is not supported here yet."
+            ),
+            error => panic!("{:?}", error),
+        }
+    }
+
+    #[test]
+    fn print_interleaves_literals_and_integers_in_one_format_string() {
+        let ir: String = compile(
+            "print-mixed",
+            "fn main() { let x = 1; x = 2; let y = 300; y = 400; print(\"x=\", x, \" y=\", y, \" end\"); }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("c\"x=%hhu y=%hu end\\00\""), "{}", ir);
+        assert!(ir.contains("%4 = zext i8 %3 to i32"), "{}", ir);
+        assert!(ir.contains("%6 = zext i16 %5 to i32"), "{}", ir);
+        assert!(ir.contains("@printf(ptr @0, i32 %4, i32 %6)"), "{}", ir);
+        assert_eq!(
+            ir.matches("call i32 (ptr, ...) @printf").count(),
+            1,
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn an_array_is_stored_element_by_element_and_indexed() {
+        let ir: String = compile(
+            "array",
+            "fn main() { let a i32[3] = [1, 2, 3]; println(a[1]); }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("%1 = alloca [3 x i32], align 4"), "{}", ir);
+        assert!(
+            ir.contains("getelementptr inbounds [3 x i32], ptr %1, i64 0, i64 2"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("store i32 3, ptr %4, align 4"), "{}", ir);
+
+        let (_, read): (&str, &str) = ir.split_once("store i32 3").expect(&ir);
+
+        assert!(
+            read.contains("%5 = getelementptr inbounds [3 x i32], ptr %1, i64 0, i64 1"),
+            "{}",
+            ir
+        );
+        assert!(read.contains("%6 = load i32, ptr %5, align 4"), "{}", ir);
+    }
+
+    #[test]
+    fn an_i32_variable_is_mutated_and_read_back() {
+        let ir: String = compile(
+            "mutate-i32",
+            "fn main() {\n    let x i32 = 1;\n    x = 10;\n    println(x);\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("%1 = alloca i32, align 4"), "{}", ir);
+        assert!(ir.contains("store i32 1, ptr %1, align 4"), "{}", ir);
+        assert!(ir.contains("store i32 10, ptr %1, align 4"), "{}", ir);
+        assert!(ir.contains("%2 = load i32, ptr %1, align 4"), "{}", ir);
+    }
+
+    #[test]
+    fn a_constant_never_assigned_is_propagated() {
+        let ir: String = compile("propagated", "fn main() { let x = 5; println(x); }").unwrap();
+
+        assert!(!ir.contains("alloca"), "{}", ir);
+        assert!(!ir.contains("store"), "{}", ir);
+        assert!(ir.contains("@printf(ptr @0, i32 5)"), "{}", ir);
+    }
+
+    #[test]
+    fn a_constant_assigned_later_keeps_its_storage() {
+        let ir: String = compile(
+            "not-propagated",
+            "fn main() { let x = 5; x = 6; println(x); }",
+        )
+        .unwrap();
+
+        assert!(ir.contains("%1 = alloca i8"), "{}", ir);
+        assert!(ir.contains("store i8 5, ptr %1"), "{}", ir);
+        assert!(ir.contains("store i8 6, ptr %1"), "{}", ir);
+        assert!(ir.contains("%2 = load i8, ptr %1"), "{}", ir);
+    }
+
+    #[test]
+    fn a_parameter_and_a_comparison_are_returned() {
+        let ir: String = compile(
+            "return-values",
+            "fn id(n :: i32) i32 {\n    return n;\n}\n\nfn less(a :: i32, b :: i32) bool {\n    return a < b;\n}\n\nfn main() {\n    println(id(7));\n}",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains("%2 = load i32, ptr %1, align 4\n  ret i32 %2"),
+            "{}",
+            ir
+        );
+        assert!(
+            ir.contains("%5 = icmp slt i32 %3, %4\n  ret i1 %5"),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn a_label_and_an_integer_are_printed_with_one_call() {
+        let ir: String = compile(
+            "print-int-label",
+            "fn main() {\n    let myInt i32 = 5;\n    myInt = 6;\n    println(\"x = \", myInt);\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("c\"x = %d\\0A\\00\""), "{}", ir);
+        assert!(ir.contains("@printf(ptr @0, i32 %2)"), "{}", ir);
+        assert_eq!(
+            ir.matches("call i32 (ptr, ...) @printf").count(),
+            1,
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn a_narrow_signed_integer_is_printed_as_an_int() {
+        let ir: String = compile(
+            "print-narrow",
+            "fn main() {\n    let myInt i8 = -5;\n    myInt = -6;\n    println(\"x = \", myInt);\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("c\"x = %hhd\\0A\\00\""), "{}", ir);
+
+        // Like C, a signed 'i8' is passed to printf as an 'int' of the same value.
+        assert!(ir.contains("%3 = sext i8 %2 to i32"), "{}", ir);
+        assert!(ir.contains("@printf(ptr @0, i32 %3)"), "{}", ir);
+    }
+
+    #[test]
+    fn numbers_converted_to_strings_are_concatenated() {
+        let ir: String = compile(
+            "to-string",
+            "fn main() {\n    println(to_string(42), \"\", to_string(3.14));\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("c\"%s%s\\0A\\00\""), "{}", ir);
+
+        // Like for printf, the 'u8' literal is passed as an 'int'.
+        assert!(
+            ir.contains("@snprintf(ptr %2, i64 320, ptr @1, i32 42)"),
+            "{}",
+            ir
+        );
+        assert!(
+            ir.contains("@snprintf(ptr %1, i64 320, ptr @2, double 3.140000e+00)"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("@printf(ptr @0, ptr %2, ptr %1)"), "{}", ir);
+    }
+
+    #[test]
+    fn the_minimum_divided_by_minus_one_aborts() {
+        let ir: String = compile(
+            "division-overflow",
+            "fn main() {\n    let a i32 = -2147483648;\n    let b i32 = -1;\n    a = -2147483648;\n    b = -1;\n    println(a / b);\n    println(a % 7);\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("%5 = icmp eq i32 %4, -1"), "{}", ir);
+        assert!(ir.contains("%6 = icmp eq i32 %3, -2147483648"), "{}", ir);
+        assert!(ir.contains("%7 = and i1 %6, %5"), "{}", ir);
+        assert!(ir.contains("%8 = xor i1 %7, true"), "{}", ir);
+        assert!(ir.contains("br i1 %8, label %10, label %9"), "{}", ir);
+
+        let (_, abort): (&str, &str) = ir.split_once("\n9:").expect(&ir);
+        let (abort, division): (&str, &str) = abort.split_once("\n10:").expect(&ir);
+
+        assert!(
+            abort.contains("call void @abort()\n  unreachable"),
+            "{}",
+            ir
+        );
+        assert!(division.contains("%11 = sdiv i32 %3, %4"), "{}", ir);
+
+        // The constant divisor of the remainder can't be '-1'.
+        assert_eq!(ir.matches("call void @abort()").count(), 1, "{}", ir);
+        assert!(division.contains("srem i32 %13, 7"), "{}", ir);
+    }
+
+    #[test]
+    fn a_function_with_parameters_is_called_with_its_arguments() {
+        let add: &str = "fn add(a :: i32, b :: i32) i32 {\n    return a + b;\n}\n\n";
+
+        let ir: String = compile(
+            "call-add",
+            &format!("{}fn main() {{\n    println(add(2, 3));\n}}", add),
+        )
+        .unwrap();
+
+        assert!(ir.contains("%5 = add i32 %3, %4\n  ret i32 %5"), "{}", ir);
+        assert!(
+            ir.contains("%1 = call i32 (i32, i32, ...) @add(i32 2, i32 3)"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("@printf(ptr @0, i32 %1)"), "{}", ir);
+
+        match compile(
+            "call-arity",
+            &format!("{}fn main() {{\n    println(add(2));\n}}", add),
+        ) {
+            Err(ThrushError::Compile(error)) => assert_eq!(
+                error,
+                "The function `add` takes 2 arguments but 1 were given at line 6."
+            ),
+            result => panic!("{:?}", result),
+        }
+
+        match compile(
+            "call-mismatch",
+            &format!("{}fn main() {{\n    println(add(2, \"x\"));\n}}", add),
+        ) {
+            Err(ThrushError::Compile(error)) => assert_eq!(
+                error,
+                "The argument 2 of `add` doesn't match the type of its parameter at line 6."
+            ),
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn a_variable_that_cant_be_printed_is_an_error() {
+        match compile(
+            "print-array",
+            "fn main() {\n    let a i32[2] = [1, 2];\n    println(a);\n}",
+        ) {
+            Err(ThrushError::Compile(error)) => assert_eq!(
+                error,
+                "Cannot print the variable `a` of type 'i32[2]' at line 3."
+            ),
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn a_char_is_stored_as_an_i8_and_printed_with_c() {
+        let ir: String = compile(
+            "char",
+            "fn main() {\n    let c char = 'A';\n    println(c);\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("c\"%c\\0A\\00\""), "{}", ir);
+        assert!(ir.contains("@printf(ptr @0, i32 65)"), "{}", ir);
+
+        // A volatile one keeps its storage, where the char is a single byte.
+        let ir: String = compile(
+            "char-volatile",
+            "fn main() {\n    @volatile let c char = 'A';\n    println(c);\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("%1 = alloca i8, align 1"), "{}", ir);
+        assert!(ir.contains("store volatile i8 65, ptr %1"), "{}", ir);
+        assert!(ir.contains("%3 = zext i8 %2 to i32"), "{}", ir);
+    }
+
+    #[test]
+    fn an_f16_is_stored_as_a_half_and_printed_as_a_double() {
+        let ir: String = compile(
+            "f16",
+            "fn main() {\n    @volatile let h f16 = 1.5;\n    println(h);\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("%1 = alloca half, align 2"), "{}", ir);
+        assert!(ir.contains("store volatile half 0xH3E00, ptr %1"), "{}", ir);
+        assert!(ir.contains("%3 = fpext half %2 to double"), "{}", ir);
+        assert!(ir.contains("@printf(ptr @0, double %3)"), "{}", ir);
+    }
+
+    #[test]
+    fn an_unset_environment_variable_is_printed_as_an_empty_string() {
+        let ir: String =
+            compile("getenv", "fn main() {\n    println(getenv(\"PATH\"));\n}").unwrap();
+
+        assert!(ir.contains("declare ptr @getenv(ptr)"), "{}", ir);
+        assert!(ir.contains("%1 = call ptr @getenv(ptr @1)"), "{}", ir);
+        assert!(ir.contains("%2 = icmp eq ptr %1, null"), "{}", ir);
+        assert!(
+            ir.contains("@2 = private unnamed_addr constant [1 x i8] zeroinitializer"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("%3 = select i1 %2, ptr @2, ptr %1"), "{}", ir);
+        assert!(ir.contains("@printf(ptr @0, ptr %3)"), "{}", ir);
+    }
+
+    #[test]
+    fn a_constant_array_is_initialized_with_its_elements() {
+        let ir: String = compile(
+            "constant-array",
+            "const PRIMES i32[5] = [2, 3, 5, 7, 11];\n\nfn main() {\n}",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains(
+                "@PRIMES = private constant [5 x i32] [i32 2, i32 3, i32 5, i32 7, i32 11]"
+            ),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn a_naked_function_has_no_prologue() {
+        let ir: String = compile(
+            "naked",
+            "@naked fn nothing() {\n    unsafe asm(\"ret\");\n}\n\nfn main() {\n    nothing();\n}",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains("call void asm sideeffect \"ret\", \"\"()"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("attributes #0 = { naked noinline }"), "{}", ir);
+        assert!(ir.contains("call void (...) @nothing()"), "{}", ir);
+    }
+
+    #[test]
+    fn inline_assembly_is_called_with_its_operands() {
+        let ir: String = compile(
+            "inline-asm",
+            "fn main() {\n    let n i32 = 1;\n    n = 2;\n    unsafe asm(\"nop\");\n    unsafe asm(\"# $0\", \"r\", n);\n}",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains("call void asm sideeffect \"nop\", \"\"()"),
+            "{}",
+            ir
+        );
+        assert!(
+            ir.contains("call void asm sideeffect \"# $0\", \"r\"(i32 %2)"),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn module_assembly_goes_in_the_module_header() {
+        let ir: String = compile(
+            "module-asm",
+            "unsafe asm(\".globl answer\");\nunsafe asm(\"answer: .long 42\");\n\nfn main() {\n}",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains("module asm \".globl answer\"\nmodule asm \"answer: .long 42\"\n"),
+            "{}",
+            ir
+        );
+        assert!(ir.find("module asm") < ir.find("define"), "{}", ir);
+    }
+
+    #[test]
+    fn a_variable_without_a_value_gets_the_default_of_its_type() {
+        let ir: String = compile(
+            "default-values",
+            "fn main() {\n    let a i32;\n    let b f64;\n    let c bool;\n    let d string;\n    let e ptr;\n    println(a, b, c, d);\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("store i32 0, ptr %1"), "{}", ir);
+        assert!(ir.contains("store double 0.000000e+00, ptr %2"), "{}", ir);
+        assert!(ir.contains("@0 = private global i1 false"), "{}", ir);
+        assert!(
+            ir.contains("@d = private unnamed_addr constant [1 x i8] zeroinitializer"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("store ptr null, ptr %3"), "{}", ir);
+        assert!(
+            ir.contains("@printf(ptr @1, i32 %4, double %5, i32 %7, ptr @d)"),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn argv_takes_any_integer_expression() {
+        let ir: String = compile(
+            "argv",
+            "fn main() {\n    let i i32 = 0;\n    i = 1;\n    println(argv(i - 1));\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("%3 = sub i32 %2, 1"), "{}", ir);
+        assert!(ir.contains("%4 = sext i32 %3 to i64"), "{}", ir);
+        assert!(ir.contains("icmp ult i64 %4, %5"), "{}", ir);
+        assert!(
+            ir.contains("getelementptr inbounds ptr, ptr %argv, i64 %4"),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn the_same_program_is_lowered_to_the_same_ir() {
+        let source: &str = "let a i32 = 1;\nlet b i64 = 2;\nlet c f64 = 3.0;\nlet d string = \"d\";\nlet e string = \"e\";\n\nfn f() {\n    let g string = \"g\";\n    let h string = \"h\";\n    println(g, h);\n}\n\nfn main() {\n    let i string = \"i\";\n    let j string = \"j\";\n    f();\n    println(i, j);\n}";
+
+        let first: String = compile("deterministic", source).unwrap();
+        let second: String = compile("deterministic", source).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn an_empty_string_keeps_its_terminator() {
+        let ir: String = compile(
+            "empty-string",
+            "fn main() {\n    let s string = \"\";\n    println(s);\n}",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains("@s = private unnamed_addr constant [1 x i8] zeroinitializer"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("@printf(ptr @0, ptr @s)"), "{}", ir);
+    }
+
+    #[test]
+    fn an_atomic_increment_loads_and_stores_with_its_ordering() {
+        let ir: String = compile(
+            "atomic",
+            "fn main() {\n    let n i32 = 0;\n    atomic_store(n, atomic_load(n, seq_cst) + 1, seq_cst);\n    fence(seq_cst);\n}",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains("%2 = load atomic i32, ptr %1 seq_cst, align 4"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("%3 = add i32 %2, 1"), "{}", ir);
+        assert!(
+            ir.contains("store atomic i32 %3, ptr %1 seq_cst, align 4"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("fence seq_cst"), "{}", ir);
+    }
+
+    #[test]
+    fn an_f32_is_reinterpreted_as_a_u32() {
+        let ir: String = compile(
+            "bitcast",
+            "fn main() {\n    let f f32 = 1.0;\n    f = 1.5;\n    println(bitcast(f, u32));\n}",
+        )
+        .unwrap();
+
+        assert!(ir.contains("c\"%u\\0A\\00\""), "{}", ir);
+        assert!(ir.contains("%3 = bitcast float %2 to i32"), "{}", ir);
+        assert!(ir.contains("@printf(ptr @0, i32 %3)"), "{}", ir);
+    }
+
+    #[test]
+    fn the_bytes_written_by_println_are_captured() {
+        let ir: String = compile(
+            "print-return",
+            "fn main() {\n    let written i32 = println(\"hi\");\n    println(written);\n}",
+        )
+        .unwrap();
+
+        assert!(
+            ir.contains("%2 = call i32 (ptr, ...) @printf(ptr @0)"),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("store i32 %2, ptr %1"), "{}", ir);
+        assert!(ir.contains("@printf(ptr @1, i32 %3)"), "{}", ir);
+    }
+
+    #[test]
+    fn a_multi_line_literal_keeps_its_line_breaks() {
+        let ir: String = compile(
+            "multi-line",
+            "fn main() {\n    println(\"\"\"one\ntwo\\nthree\"\"\");\n    println(\"four \\\n        five\");\n}",
+        )
+        .unwrap();
+
+        // The line break, the escape and the line ending in a backslash.
+        assert!(
+            ir.contains("[15 x i8] c\"one\\0Atwo\\0Athree\\0A\\00\""),
+            "{}",
+            ir
+        );
+        assert!(ir.contains("[11 x i8] c\"four five\\0A\\00\""), "{}", ir);
+    }
+
+    #[test]
+    fn a_block_initializer_evaluates_to_its_last_expression() {
+        let ir: String = compile(
+            "block-value",
+            "fn main() {\n    let x i32 = { let a i32 = 1; a = 2; a + 3 };\n    println(x);\n}",
+        )
+        .unwrap();
+
+        // The block's local is read before its scope ends, into the storage of `x`.
+        assert!(ir.contains("%3 = load i32, ptr %2"), "{}", ir);
+        assert!(
+            ir.contains("%4 = add i32 %3, 3\n  store i32 %4, ptr %1"),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn a_deferred_statement_runs_before_every_return() {
+        let ir: String = compile(
+            "defer",
+            "fn f() {\n    defer println(\"cleanup\");\n    let n i32 = 1;\n    n = 2;\n    if n > 1 {\n        return;\n    }\n    println(\"rest\");\n}\n\nfn main() {\n    f();\n}",
+        )
+        .unwrap();
+
+        // Both the early return and the end of the function print "cleanup" last.
+        assert!(
+            ir.contains("%5 = call i32 (ptr, ...) @printf(ptr @0)\n  ret void"),
+            "{}",
+            ir
+        );
+        assert!(
+            ir.contains("@printf(ptr @1)\n  %9 = call i32 (ptr, ...) @printf(ptr @2)\n  ret void"),
+            "{}",
+            ir
+        );
+        assert!(
+            ir.contains("@0 = private unnamed_addr constant [9 x i8] c\"cleanup\\0A\\00\""),
+            "{}",
+            ir
+        );
+        assert!(
+            ir.contains("@2 = private unnamed_addr constant [9 x i8] c\"cleanup\\0A\\00\""),
+            "{}",
+            ir
+        );
+    }
+
+    #[test]
+    fn the_illegal_combinations_of_options_are_rejected() {
+        let sanitizer = || vec![String::from("address")];
+
+        let illegal: [(Options, &str); 13] = [
+            (
+                Options {
+                    emit_llvm: true,
+                    emit_object: true,
+                    ..Options::default()
+                },
+                "'--emit-llvm' writes LLVM IR, it can't also emit an object",
+            ),
+            (
+                Options {
+                    emit_llvm: true,
+                    build: true,
+                    ..Options::default()
+                },
+                "'--emit-llvm' writes LLVM IR, it can't also build",
+            ),
+            (
+                Options {
+                    emit_assembly: true,
+                    emit_llvm: true,
+                    ..Options::default()
+                },
+                "'--emit-asm' writes assembly, it can't also write LLVM IR",
+            ),
+            (
+                Options {
+                    emit_assembly: true,
+                    emit_object: true,
+                    ..Options::default()
+                },
+                "'--emit-asm' writes assembly, it can't also write LLVM IR or an object",
+            ),
+            (
+                Options {
+                    emit_assembly: true,
+                    build: true,
+                    ..Options::default()
+                },
+                "'--emit-asm' writes assembly, it can't also build",
+            ),
+            (
+                Options {
+                    emit_assembly: true,
+                    sanitizers: sanitizer(),
+                    ..Options::default()
+                },
+                "it can't write assembly with '--sanitize'",
+            ),
+            (
+                Options {
+                    emit_object: true,
+                    build: true,
+                    ..Options::default()
+                },
+                "'--lib' emits an object, it can't also build",
+            ),
+            (
+                Options {
+                    interpret: true,
+                    build: true,
+                    ..Options::default()
+                },
+                "The interpreter runs the code in memory",
+            ),
+            (
+                Options {
+                    interpret: true,
+                    emit_llvm: true,
+                    ..Options::default()
+                },
+                "The interpreter runs the code in memory",
+            ),
+            (
+                Options {
+                    strip: true,
+                    ..Options::default()
+                },
+                "'--strip' only applies to a linked executable",
+            ),
+            (
+                Options {
+                    interpret: true,
+                    target_triple: TargetTriple::create("wasm32-unknown-unknown"),
+                    ..Options::default()
+                },
+                "it can't target another one",
+            ),
+            (
+                Options {
+                    interpret: true,
+                    sanitizers: sanitizer(),
+                    ..Options::default()
+                },
+                "The interpreter has no sanitizer runtime",
+            ),
+            (
+                Options {
+                    sanitizers: vec![String::from("address"), String::from("thread")],
+                    ..Options::default()
+                },
+                "can't be combined",
+            ),
+        ];
+
+        for (options, expected) in illegal {
+            match options.validate() {
+                Err(ThrushError::Compile(error)) => assert!(error.contains(expected), "{}", error),
+                _ => panic!("{:?} is accepted", options),
+            }
+        }
+    }
+
+    #[test]
+    fn the_legal_options_are_accepted() {
+        let legal: [Options; 5] = [
+            Options::default(),
+            Options {
+                emit_llvm: true,
+                ..Options::default()
+            },
+            Options {
+                emit_assembly: true,
+                ..Options::default()
+            },
+            Options {
+                build: true,
+                strip: true,
+                sanitizers: vec![String::from("address")],
+                ..Options::default()
+            },
+            Options {
+                interpret: true,
+                ..Options::default()
+            },
+        ];
+
+        for options in legal {
+            assert!(options.validate().is_ok(), "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn opt_accepts_the_pipeline_of_every_level() {
+        if !has_opt() {
+            return;
+        }
+
+        let context: Context = Context::create();
+        let module: Module<'_> = main_module(&context);
+        let machine: TargetMachine = host_machine();
+
+        for optimization in [Opt::None, Opt::Low, Opt::Mid, Opt::Mcqueen] {
+            let options: Options = Options {
+                name: temp_name(&format!("opt-{:?}", optimization)),
+                optimization,
+                ..Options::default()
+            };
+
+            module.write_bitcode_to_path(Path::new(&format!("{}.bc", options.name)));
+
+            let optimized: Result<(), ThrushError> =
+                FileBuilder::new(&options, &module, &machine).opt();
+
+            remove_file(format!("{}.bc", options.name)).unwrap();
+
+            if let Err(ThrushError::Compile(error)) = optimized {
+                panic!("{:?}: {}", options.optimization, error);
+            }
+        }
+    }
+
+    #[test]
+    fn only_the_call_marked_inline_is_inlined_at_low() {
+        if !has_opt() {
+            return;
+        }
+
+        let lines: String = (0..12)
+            .map(|line| format!("    println(\"line {}\");\n", line))
+            .collect();
+
+        let ir: String = compile(
+            "inline-call",
+            &format!(
+                "public fn noisy() {{\n{}}}\n\nfn main() {{\n    inline noisy();\n    noisy();\n}}",
+                lines
+            ),
+        )
+        .unwrap();
+
+        assert!(ir.contains("call void (...) @noisy() #0"), "{}", ir);
+        assert!(ir.contains("attributes #0 = { alwaysinline }"), "{}", ir);
+
+        let path: String = format!("{}.ll", temp_name("inline-call"));
+
+        fs::write(&path, &ir).unwrap();
+
+        let output: Output = Command::new("opt")
+            .arg(format!("-passes={}", Opt::Low.passes().join(",")))
+            .arg("-S")
+            .arg(&path)
+            .output()
+            .unwrap();
+
+        remove_file(&path).unwrap();
+
+        let optimized: String = String::from_utf8_lossy(&output.stdout).into_owned();
+        let (_, main): (&str, &str) = optimized.split_once("@main(").expect(&optimized);
+        let (main, _): (&str, &str) = main.split_once("\n}").expect(&optimized);
+
+        // The marked call is replaced by the body of 'noisy', the other one is kept.
+        assert_eq!(main.matches("@noisy(").count(), 1, "{}", optimized);
+        assert_eq!(main.matches("@puts(").count(), 12, "{}", optimized);
+    }
+
+    #[test]
+    fn emit_assembly_writes_the_s_file() {
+        if !has_opt() {
+            return;
+        }
+
+        let options: Options = Options {
+            name: temp_name("emit-asm"),
+            emit_assembly: true,
+            ..Options::default()
+        };
+
+        let context: Context = Context::create();
+        let module: Module<'_> = main_module(&context);
+        let machine: TargetMachine = host_machine();
+
+        assert!(FileBuilder::new(&options, &module, &machine).build());
+
+        let assembly: String = fs::read_to_string(format!("{}.s", options.name)).unwrap();
+
+        remove_file(format!("{}.s", options.name)).unwrap();
+
+        assert!(assembly.lines().any(|line| line == "main:"), "{}", assembly);
+    }
+
+    #[test]
+    fn an_object_is_emitted_for_the_target_triple() {
+        if !has_opt() {
+            return;
+        }
+
+        Target::initialize_x86(&InitializationConfig::default());
+
+        let triple: TargetTriple = TargetTriple::create("x86_64-unknown-linux-gnu");
+
+        let options: Options = Options {
+            name: temp_name("emit-object"),
+            target_triple: TargetTriple::create("x86_64-unknown-linux-gnu"),
+            ..Options::default()
+        };
+
+        let machine: TargetMachine = Target::from_triple(&triple)
+            .unwrap()
+            .create_target_machine(
+                &triple,
+                "",
+                "",
+                inkwell::OptimizationLevel::None,
+                RelocMode::Default,
+                CodeModel::Default,
+            )
+            .unwrap();
+
+        let context: Context = Context::create();
+        let module: Module<'_> = main_module(&context);
+
+        module.set_triple(&triple);
+        module.set_data_layout(&machine.get_target_data().get_data_layout());
+
+        assert!(FileBuilder::new(&options, &module, &machine).build());
+
+        let object: Vec<u8> = fs::read(format!("{}.o", options.name)).unwrap();
+
+        remove_file(format!("{}.o", options.name)).unwrap();
+
+        // A 64-bit little-endian ELF for x86-64, what the data layout of the triple describes.
+        assert_eq!(&object[..4], b"\x7fELF");
+        assert_eq!((object[4], object[5]), (2, 1));
+        assert_eq!(u16::from_le_bytes([object[18], object[19]]), 0x3e);
+    }
+
+    #[test]
+    fn a_failing_tool_reports_its_stderr() {
+        let error: ThrushError = FileBuilder::run(
+            Command::new("sh").args(["-c", "echo 'error: no input files' >&2; exit 1"]),
+            "clang",
+        )
+        .unwrap_err();
+
+        match error {
+            ThrushError::Compile(error) => {
+                assert!(error.contains("exit status: 1"), "{}", error);
+                assert!(error.ends_with("error: no input files"), "{}", error);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn broken_bitcode_surfaces_the_stderr_of_opt() {
+        if !has_opt() {
+            return;
+        }
+
+        let options: Options = Options {
+            name: temp_name("broken-opt"),
+            ..Options::default()
+        };
+
+        fs::write(format!("{}.bc", options.name), "not bitcode").unwrap();
+
+        let context: Context = Context::create();
+        let module: Module<'_> = main_module(&context);
+        let machine: TargetMachine = host_machine();
+
+        let optimized: Result<(), ThrushError> =
+            FileBuilder::new(&options, &module, &machine).opt();
+
+        remove_file(format!("{}.bc", options.name)).unwrap();
+
+        match optimized {
+            Err(ThrushError::Compile(error)) => {
+                assert!(
+                    error.starts_with("Compilation failed. opt failed with"),
+                    "{}",
+                    error
+                );
+                assert!(error.contains(&format!("{}.bc", options.name)), "{}", error);
+                assert!(error.contains("error:"), "{}", error);
+            }
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn broken_bitcode_surfaces_the_stderr_of_clang() {
+        let clang: String = find_clang();
+
+        if Command::new(&clang).arg("--version").output().is_err() {
+            return;
+        }
+
+        let name: String = temp_name("broken-clang");
+
+        fs::write(format!("{}.bc", name), "not bitcode").unwrap();
+
+        let compiled: Result<(), ThrushError> = FileBuilder::run(
+            Command::new(&clang)
+                .arg("-c")
+                .arg(format!("{}.bc", name))
+                .arg("-o")
+                .arg(format!("{}.o", name)),
+            "clang",
+        );
+
+        remove_file(format!("{}.bc", name)).unwrap();
+        let _ = remove_file(format!("{}.o", name));
+
+        match compiled {
+            Err(ThrushError::Compile(error)) => {
+                assert!(
+                    error.starts_with("Compilation failed. clang failed with"),
+                    "{}",
+                    error
+                );
+                assert!(error.contains("error:"), "{}", error);
+            }
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn the_configured_clang_is_the_one_spawned() {
+        if !has_opt() {
+            return;
+        }
+
+        // A clang that only records how it was called.
+        let clang: String = temp_name("recording-clang");
+        let arguments: String = format!("{}.args", clang);
+
+        fs::write(
+            &clang,
+            format!("#!/bin/sh\necho \"$0 $*\" > '{}'\n", arguments),
+        )
+        .unwrap();
+        fs::set_permissions(&clang, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let options: Options = Options {
+            name: temp_name("recorded-link"),
+            build: true,
+            clang_path: clang.clone(),
+            ..Options::default()
+        };
+
+        let context: Context = Context::create();
+        let module: Module<'_> = main_module(&context);
+        let machine: TargetMachine = host_machine();
+
+        let built: bool = FileBuilder::new(&options, &module, &machine).build();
+
+        let called: String = fs::read_to_string(&arguments).unwrap();
+
+        remove_file(&clang).unwrap();
+        remove_file(&arguments).unwrap();
+
+        assert!(built);
+        assert!(called.starts_with(&format!("{} ", clang)), "{}", called);
+        assert!(
+            called.contains(&format!(
+                "--target={} ",
+                options.target_triple.as_str().to_string_lossy()
+            )),
+            "{}",
+            called
+        );
+        assert!(
+            called.ends_with(&format!("{}.bc -o {}\n", options.name, options.name)),
+            "{}",
+            called
+        );
+    }
+
+    #[test]
+    fn a_failing_link_removes_the_bitcode_and_the_output() {
+        if !has_opt() {
+            return;
+        }
+
+        // A clang that leaves a partial executable behind before failing.
+        let clang: String = temp_name("failing-clang");
+
+        fs::write(
+            &clang,
+            "#!/bin/sh\nfor last; do :; done\ntouch \"$last\"\necho 'ld: error' >&2\nexit 1\n",
+        )
+        .unwrap();
+        fs::set_permissions(&clang, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let options: Options = Options {
+            name: temp_name("failing-link"),
+            build: true,
+            clang_path: clang.clone(),
+            ..Options::default()
+        };
+
+        let context: Context = Context::create();
+        let module: Module<'_> = main_module(&context);
+        let machine: TargetMachine = host_machine();
+
+        let built: bool = FileBuilder::new(&options, &module, &machine).build();
+
+        remove_file(&clang).unwrap();
+
+        assert!(!built);
+        assert!(!Path::new(&format!("{}.bc", options.name)).exists());
+        assert!(!Path::new(&options.name).exists());
     }
 }
@@ -4,10 +4,16 @@ use {
     inkwell::{
         builder::Builder,
         context::Context,
-        module::Linkage,
-        types::{ArrayType, BasicMetadataTypeEnum, FloatType, FunctionType, IntType},
-        values::{FloatValue, GlobalValue, IntValue, PointerValue},
+        module::{Linkage, Module},
+        types::{
+            ArrayType, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FloatType, FunctionType,
+            IntType,
+        },
+        values::{BasicValueEnum, FloatValue, GlobalValue, IntValue, PointerValue},
+        AddressSpace, GlobalVisibility,
     },
+    llvm_sys::core::{LLVMAppendModuleInlineAsm, LLVMBFloatTypeInContext},
+    std::ffi::c_char,
 };
 
 pub fn datatype_integer_to_type<'ctx>(context: &'ctx Context, kind: &DataTypes) -> IntType<'ctx> {
@@ -22,12 +28,20 @@ pub fn datatype_integer_to_type<'ctx>(context: &'ctx Context, kind: &DataTypes)
         DataTypes::U32 => context.i32_type(),
         DataTypes::U64 => context.i64_type(),
 
+        DataTypes::Char => context.i8_type(),
+
         _ => unreachable!(),
     }
 }
 
+pub fn bf16_type(context: &Context) -> FloatType<'_> {
+    unsafe { FloatType::new(LLVMBFloatTypeInContext(context.raw())) }
+}
+
 pub fn datatype_float_to_type<'ctx>(context: &'ctx Context, kind: &DataTypes) -> FloatType<'ctx> {
     match kind {
+        DataTypes::F16 => context.f16_type(),
+        DataTypes::BF16 => bf16_type(context),
         DataTypes::F32 => context.f32_type(),
         DataTypes::F64 => context.f64_type(),
 
@@ -41,39 +55,91 @@ pub fn build_const_float<'ctx>(
     num: f64,
 ) -> FloatValue<'ctx> {
     match kind {
-        DataTypes::F32 => context.f32_type().const_float(num),
+        DataTypes::F16 => context.f16_type().const_float(num),
+        DataTypes::BF16 => bf16_type(context).const_float(num),
+        // Round through 'f32' so the constant matches a C 'float' literal.
+        DataTypes::F32 => context.f32_type().const_float((num as f32) as f64),
         DataTypes::F64 => context.f64_type().const_float(num),
 
         _ => unreachable!(),
     }
 }
 
+/// Signed literals are only lexed after a '-', so `num` is their magnitude.
 pub fn build_const_integer<'ctx>(
     context: &'ctx Context,
     kind: &'ctx DataTypes,
-    num: f64,
+    num: u64,
 ) -> IntValue<'ctx> {
     match kind {
-        DataTypes::U8 => context.i8_type().const_int(num as u64, false),
-        DataTypes::U16 => context.i16_type().const_int(num as u64, false),
-        DataTypes::U32 => context.i32_type().const_int(num as u64, false),
-        DataTypes::U64 => context.i64_type().const_int(num as u64, false),
-        DataTypes::I8 => context.i8_type().const_int(num as u64, true).const_neg(),
-        DataTypes::I16 => context.i16_type().const_int(num as u64, true).const_neg(),
-        DataTypes::I32 => context.i32_type().const_int(num as u64, true).const_neg(),
-        DataTypes::I64 => context.i64_type().const_int(num as u64, true).const_neg(),
+        DataTypes::U8 => context.i8_type().const_int(num, false),
+        DataTypes::U16 => context.i16_type().const_int(num, false),
+        DataTypes::U32 => context.i32_type().const_int(num, false),
+        DataTypes::U64 => context.i64_type().const_int(num, false),
+        DataTypes::I8 => context.i8_type().const_int(num, false).const_neg(),
+        DataTypes::I16 => context.i16_type().const_int(num, false).const_neg(),
+        DataTypes::I32 => context.i32_type().const_int(num, false).const_neg(),
+        DataTypes::I64 => context.i64_type().const_int(num, false).const_neg(),
 
         _ => unreachable!(),
     }
 }
 
+/// The value of a variable declared without an initializer: zero, 'false', an empty
+/// C string or a null pointer.
+pub fn build_default_value<'ctx>(context: &'ctx Context, kind: &DataTypes) -> BasicValueEnum<'ctx> {
+    match kind {
+        DataTypes::I8
+        | DataTypes::I16
+        | DataTypes::I32
+        | DataTypes::I64
+        | DataTypes::U8
+        | DataTypes::U16
+        | DataTypes::U32
+        | DataTypes::U64
+        | DataTypes::Char => datatype_integer_to_type(context, kind).const_zero().into(),
+
+        DataTypes::F16 | DataTypes::BF16 | DataTypes::F32 | DataTypes::F64 => {
+            datatype_float_to_type(context, kind).const_zero().into()
+        }
+
+        DataTypes::Bool => context.bool_type().const_zero().into(),
+        DataTypes::String => context.const_string(b"", true).into(),
+        DataTypes::Ptr => context
+            .ptr_type(AddressSpace::default())
+            .const_null()
+            .into(),
+        DataTypes::Array(element, size) => datatype_to_basic_type_enum(context, element)
+            .array_type(*size)
+            .const_zero()
+            .into(),
+
+        _ => unreachable!(),
+    }
+}
+
+/// The natural alignment of a scalar: its size in bytes, a byte for booleans.
+/// An array is aligned like its elements.
+pub fn datatype_alignment(kind: &DataTypes) -> u32 {
+    match kind {
+        DataTypes::Bool => 1,
+        DataTypes::Array(element, _) => datatype_alignment(element),
+        _ => kind.bit_width() / 8,
+    }
+}
+
 pub fn build_alloca_with_integer<'a, 'ctx>(
     builder: &'a Builder<'ctx>,
     kind: IntType<'ctx>,
+    alignment: u32,
 ) -> PointerValue<'ctx> {
     let alloca: PointerValue<'ctx> = builder.build_alloca(kind, "").unwrap();
 
-    alloca.as_instruction().unwrap().set_alignment(4).unwrap();
+    alloca
+        .as_instruction()
+        .unwrap()
+        .set_alignment(alignment)
+        .unwrap();
 
     alloca
 }
@@ -81,10 +147,15 @@ pub fn build_alloca_with_integer<'a, 'ctx>(
 pub fn build_alloca_with_float<'a, 'ctx>(
     builder: &'a Builder<'ctx>,
     kind: FloatType<'ctx>,
+    alignment: u32,
 ) -> PointerValue<'ctx> {
     let alloca: PointerValue<'ctx> = builder.build_alloca(kind, "").unwrap();
 
-    alloca.as_instruction().unwrap().set_alignment(4).unwrap();
+    alloca
+        .as_instruction()
+        .unwrap()
+        .set_alignment(alignment)
+        .unwrap();
 
     alloca
 }
@@ -104,19 +175,58 @@ pub fn build_int_array_type_from_size(
     }
 }
 
-pub fn set_globals_options<'ctx>(
-    context: &'ctx Context,
-    global: GlobalValue<'ctx>,
-    initializer: Option<&Instruction>,
-) {
-    if let Some(Instruction::String(string)) = initializer {
-        global.set_initializer(&context.const_string(string.as_ref(), false))
+pub fn printf_spec(kind: &DataTypes) -> &'static str {
+    match kind {
+        DataTypes::U8 => "%hhu",
+        DataTypes::U16 => "%hu",
+        DataTypes::U32 => "%u",
+        DataTypes::U64 => "%llu",
+        DataTypes::I8 => "%hhd",
+        DataTypes::I16 => "%hd",
+        DataTypes::I32 | DataTypes::Integer => "%d",
+        DataTypes::I64 => "%lld",
+
+        DataTypes::F16 | DataTypes::BF16 | DataTypes::F32 | DataTypes::F64 | DataTypes::Float => {
+            "%f"
+        }
+
+        DataTypes::Bool => "%d",
+        DataTypes::Char => "%c",
+        DataTypes::String => "%s",
+        DataTypes::Ptr => "%p",
+
+        DataTypes::Void | DataTypes::Array(..) => unreachable!(),
+    }
+}
+
+/// Length-prefixed like the Itanium ABI, so a unit and a name can't run into each other:
+/// the function 'helper' of 'math.th' becomes '_TN4math6helperE'.
+pub fn mangle_symbol(unit: &str, name: &str) -> String {
+    format!("_TN{}{}{}{}E", unit.len(), unit, name.len(), name)
+}
+
+/// LLVM puts every block on its own line, while inkwell can only replace the whole module assembly.
+pub fn append_module_asm(module: &Module<'_>, asm: &str) {
+    unsafe {
+        LLVMAppendModuleInlineAsm(
+            module.as_mut_ptr(),
+            asm.as_ptr() as *const c_char,
+            asm.len(),
+        )
     }
+}
+
+/// Private symbols must keep the default visibility, so an explicit visibility
+/// also exports the symbol with external linkage.
+pub fn set_symbol_visibility(global: GlobalValue<'_>, visibility: Option<GlobalVisibility>) {
+    match visibility {
+        Some(visibility) => {
+            global.set_linkage(Linkage::External);
+            global.set_visibility(visibility);
+        }
 
-    global.set_linkage(Linkage::Private);
-    global.set_constant(true);
-    global.set_unnamed_addr(true);
-    global.set_alignment(1);
+        None => global.set_linkage(Linkage::Private),
+    }
 }
 
 pub fn datatype_to_fn_type<'ctx>(
@@ -145,6 +255,7 @@ pub fn datatype_to_fn_type<'ctx>(
             DataTypes::U16 => context.i16_type().fn_type(&param_types, true),
             DataTypes::U32 => context.i32_type().fn_type(&param_types, true),
             DataTypes::U64 => context.i64_type().fn_type(&param_types, true),
+            DataTypes::Char => context.i8_type().fn_type(&param_types, true),
             DataTypes::Void => context.void_type().fn_type(&param_types, true),
             DataTypes::String => context
                 .i8_type()
@@ -152,8 +263,13 @@ pub fn datatype_to_fn_type<'ctx>(
                 .fn_type(&param_types, true),
 
             DataTypes::Bool => context.bool_type().fn_type(&param_types, true),
+            DataTypes::F16 => context.f16_type().fn_type(&param_types, true),
+            DataTypes::BF16 => bf16_type(context).fn_type(&param_types, true),
             DataTypes::F32 => context.f32_type().fn_type(&param_types, true),
             DataTypes::F64 => context.f64_type().fn_type(&param_types, true),
+            DataTypes::Ptr => context
+                .ptr_type(AddressSpace::default())
+                .fn_type(&param_types, true),
 
             _ => unimplemented!()
         },
@@ -162,6 +278,24 @@ pub fn datatype_to_fn_type<'ctx>(
     }
 }
 
+pub fn datatype_to_basic_type_enum<'ctx>(
+    context: &'ctx Context,
+    kind: &DataTypes,
+) -> BasicTypeEnum<'ctx> {
+    match kind {
+        DataTypes::F16 | DataTypes::BF16 | DataTypes::F32 | DataTypes::F64 => {
+            datatype_float_to_type(context, kind).into()
+        }
+        DataTypes::Bool => context.bool_type().into(),
+        DataTypes::Ptr => context.ptr_type(AddressSpace::default()).into(),
+        DataTypes::Array(element, size) => datatype_to_basic_type_enum(context, element)
+            .array_type(*size)
+            .into(),
+
+        _ => datatype_integer_to_type(context, kind).into(),
+    }
+}
+
 pub fn datatype_to_basicmetadata_type_enum<'ctx>(
     context: &'ctx Context,
     kind: &DataTypes,
@@ -175,10 +309,101 @@ pub fn datatype_to_basicmetadata_type_enum<'ctx>(
         DataTypes::U16 => BasicMetadataTypeEnum::IntType(context.i16_type()),
         DataTypes::U32 => BasicMetadataTypeEnum::IntType(context.i32_type()),
         DataTypes::U64 => BasicMetadataTypeEnum::IntType(context.i64_type()),
+        DataTypes::Char => BasicMetadataTypeEnum::IntType(context.i8_type()),
         DataTypes::Bool => BasicMetadataTypeEnum::IntType(context.bool_type()),
+        DataTypes::F16 => BasicMetadataTypeEnum::FloatType(context.f16_type()),
+        DataTypes::BF16 => BasicMetadataTypeEnum::FloatType(bf16_type(context)),
         DataTypes::F32 => BasicMetadataTypeEnum::FloatType(context.f32_type()),
         DataTypes::F64 => BasicMetadataTypeEnum::FloatType(context.f64_type()),
+        DataTypes::Ptr => {
+            BasicMetadataTypeEnum::PointerType(context.ptr_type(AddressSpace::default()))
+        }
 
         _ => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alignment_follows_the_width_of_the_type() {
+        assert_eq!(datatype_alignment(&DataTypes::Bool), 1);
+        assert_eq!(datatype_alignment(&DataTypes::I8), 1);
+        assert_eq!(datatype_alignment(&DataTypes::U16), 2);
+        assert_eq!(datatype_alignment(&DataTypes::I32), 4);
+        assert_eq!(datatype_alignment(&DataTypes::I64), 8);
+        assert_eq!(datatype_alignment(&DataTypes::U64), 8);
+        assert_eq!(datatype_alignment(&DataTypes::F64), 8);
+    }
+
+    #[test]
+    fn every_numeric_type_has_its_own_printf_spec() {
+        let specs: [(DataTypes, &str); 8] = [
+            (DataTypes::U8, "%hhu"),
+            (DataTypes::U16, "%hu"),
+            (DataTypes::U32, "%u"),
+            (DataTypes::U64, "%llu"),
+            (DataTypes::I8, "%hhd"),
+            (DataTypes::I16, "%hd"),
+            (DataTypes::I32, "%d"),
+            (DataTypes::I64, "%lld"),
+        ];
+
+        for (kind, spec) in specs {
+            assert_eq!(printf_spec(&kind), spec, "{}", kind);
+        }
+
+        for kind in [DataTypes::F16, DataTypes::BF16, DataTypes::F32, DataTypes::F64] {
+            assert_eq!(printf_spec(&kind), "%f", "{}", kind);
+        }
+    }
+
+    #[test]
+    fn an_f32_literal_holds_the_f32_bit_pattern() {
+        let (single, double): (DataTypes, DataTypes) = (DataTypes::F32, DataTypes::F64);
+
+        let context: Context = Context::create();
+
+        let (rounded, _): (f64, bool) = build_const_float(&context, &single, 0.1)
+            .get_constant()
+            .unwrap();
+
+        assert_eq!((rounded as f32).to_bits(), 0.1f32.to_bits());
+        assert_eq!(rounded, 0.1f32 as f64);
+        assert_ne!(rounded, 0.1);
+
+        assert_eq!(
+            build_const_float(&context, &double, 0.1).get_constant(),
+            Some((0.1, false))
+        );
+    }
+
+    #[test]
+    fn integer_literals_keep_every_bit() {
+        let (unsigned, signed): (DataTypes, DataTypes) = (DataTypes::U64, DataTypes::I64);
+
+        let context: Context = Context::create();
+
+        assert_eq!(
+            build_const_integer(&context, &unsigned, u64::MAX).get_zero_extended_constant(),
+            Some(u64::MAX)
+        );
+        assert_eq!(
+            build_const_integer(&context, &unsigned, i64::MAX as u64).get_zero_extended_constant(),
+            Some(i64::MAX as u64)
+        );
+
+        // A signed literal is the magnitude of a negative number.
+        assert_eq!(
+            build_const_integer(&context, &signed, i64::MIN.unsigned_abs())
+                .get_sign_extended_constant(),
+            Some(i64::MIN)
+        );
+        assert_eq!(
+            build_const_integer(&context, &signed, 5).get_sign_extended_constant(),
+            Some(-5)
+        );
+    }
+}
@@ -1,3 +1,5 @@
+pub mod cache;
 pub mod compiler;
 pub mod llvm;
 pub mod objects;
+pub mod target;
@@ -1,7 +1,18 @@
-use {super::super::frontend::lexer::DataTypes, inkwell::values::BasicValueEnum};
+use {
+    super::super::frontend::lexer::DataTypes,
+    inkwell::values::{BasicValueEnum, PointerValue},
+};
 
 #[derive(Debug, Clone)]
 pub struct ThrushBasicValueEnum<'ctx> {
     pub kind: DataTypes,
     pub value: BasicValueEnum<'ctx>,
 }
+
+/// A variable kept in memory, every read loads it again to see the last store.
+#[derive(Debug, Clone)]
+pub struct ThrushLocal<'ctx> {
+    pub kind: DataTypes,
+    pub ptr: PointerValue<'ctx>,
+    pub volatile: bool,
+}
@@ -0,0 +1,169 @@
+use {
+    inkwell::targets::{CodeModel, RelocMode},
+    std::{collections::HashMap, fs::read_to_string, iter::Peekable, path::Path, str::Chars},
+};
+
+/// A custom target described by a JSON file, passed with `--target-json`.
+///
+/// The keys follow the rustc target specs: `llvm-target` and `data-layout` are
+/// required, `cpu`, `features`, `code-model` and `relocation-model` are optional.
+#[derive(Debug)]
+pub struct TargetSpec {
+    pub triple: String,
+    pub data_layout: String,
+    pub cpu: String,
+    pub features: String,
+    pub code_model: CodeModel,
+    pub reloc_mode: RelocMode,
+}
+
+impl TargetSpec {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content: String = read_to_string(path).map_err(|error| {
+            format!(
+                "Cannot read the target spec '{}': {}.",
+                path.display(),
+                error
+            )
+        })?;
+
+        let mut fields: HashMap<String, String> = parse_object(&content).map_err(|error| {
+            format!(
+                "The target spec '{}' is malformed: {}.",
+                path.display(),
+                error
+            )
+        })?;
+
+        let mut required = |key: &str| -> Result<String, String> {
+            fields.remove(key).ok_or_else(|| {
+                format!(
+                    "The target spec '{}' is missing the required field '{}'.",
+                    path.display(),
+                    key
+                )
+            })
+        };
+
+        let triple: String = required("llvm-target")?;
+        let data_layout: String = required("data-layout")?;
+
+        let code_model: CodeModel = match fields.remove("code-model").as_deref() {
+            None | Some("default") => CodeModel::Default,
+            Some("small") => CodeModel::Small,
+            Some("kernel") => CodeModel::Kernel,
+            Some("medium") => CodeModel::Medium,
+            Some("large") => CodeModel::Large,
+            Some(model) => {
+                return Err(format!(
+                    "The code model '{}' in the target spec '{}' is not supported.",
+                    model,
+                    path.display()
+                ));
+            }
+        };
+
+        let reloc_mode: RelocMode = match fields.remove("relocation-model").as_deref() {
+            None | Some("default") => RelocMode::Default,
+            Some("static") => RelocMode::Static,
+            Some("pic") => RelocMode::PIC,
+            Some("dynamic-no-pic") => RelocMode::DynamicNoPic,
+            Some(mode) => {
+                return Err(format!(
+                    "The relocation model '{}' in the target spec '{}' is not supported.",
+                    mode,
+                    path.display()
+                ));
+            }
+        };
+
+        Ok(Self {
+            triple,
+            data_layout,
+            cpu: fields.remove("cpu").unwrap_or_default(),
+            features: fields.remove("features").unwrap_or_default(),
+            code_model,
+            reloc_mode,
+        })
+    }
+}
+
+/// Parses a flat JSON object whose values are all strings, which is all a target spec needs.
+fn parse_object(content: &str) -> Result<HashMap<String, String>, String> {
+    let mut chars: Peekable<Chars> = content.chars().peekable();
+    let mut fields: HashMap<String, String> = HashMap::new();
+
+    expect(&mut chars, '{')?;
+
+    if skip_whitespace(&mut chars) == Some('}') {
+        chars.next();
+    } else {
+        loop {
+            let key: String = parse_string(&mut chars)?;
+
+            expect(&mut chars, ':')?;
+
+            let value: String = parse_string(&mut chars)?;
+
+            if fields.insert(key.clone(), value).is_some() {
+                return Err(format!("duplicated field '{}'", key));
+            }
+
+            match skip_whitespace(&mut chars) {
+                Some(',') => {
+                    chars.next();
+                }
+                Some('}') => {
+                    chars.next();
+                    break;
+                }
+                _ => return Err(String::from("expected ',' or '}' after a field")),
+            }
+        }
+    }
+
+    if skip_whitespace(&mut chars).is_some() {
+        return Err(String::from("unexpected content after the object"));
+    }
+
+    Ok(fields)
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    expect(chars, '"')?;
+
+    let mut string: String = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some('"') => string.push('"'),
+                Some('\\') => string.push('\\'),
+                Some('/') => string.push('/'),
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                _ => return Err(String::from("unsupported escape sequence in a string")),
+            },
+            Some(ch) => string.push(ch),
+            None => return Err(String::from("unterminated string")),
+        }
+    }
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Result<(), String> {
+    match skip_whitespace(chars) {
+        Some(ch) if ch == expected => {
+            chars.next();
+            Ok(())
+        }
+        Some(ch) => Err(format!("expected '{}' but found '{}'", expected, ch)),
+        None => Err(format!("expected '{}' but the file ended", expected)),
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) -> Option<char> {
+    while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+
+    chars.peek().copied()
+}
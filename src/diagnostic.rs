@@ -4,47 +4,58 @@ use {
         NAME,
     },
     colored::Colorize,
-    std::{
-        fs::File,
-        io::{BufRead, BufReader},
-    },
+    std::fs,
 };
 
 #[derive(Debug)]
 pub struct Diagnostic {
-    buffer: String,
-    drawer: String,
+    path: String,
+    source: String,
     lines: Vec<String>,
 }
 
 impl Diagnostic {
     pub fn new(path: &str) -> Self {
-        let file: File = File::open(path).unwrap();
-        let lines: Vec<String> = BufReader::new(file)
+        let source: String = fs::read_to_string(path).unwrap();
+        let lines: Vec<String> = source
             .lines()
-            .map(|line| line.unwrap().to_string())
+            // A lone '\r' at the end of a line would widen the underline.
+            .map(|line| line.trim_end_matches('\r').to_string())
             .collect();
 
         Self {
-            buffer: String::new(),
-            drawer: String::new(),
+            path: path.to_string(),
+            source,
             lines,
         }
     }
 
     pub fn report(&mut self, error: &ThrushError) {
+        let (error, column): (&ThrushError, Option<usize>) = match error {
+            ThrushError::Located(error, offset) => (&**error, self.column(*offset)),
+            error => (error, None),
+        };
+
+        let (error, related): (&ThrushError, Option<(&str, usize)>) = match error {
+            ThrushError::Related(error, note, line) => (&**error, Some((note.as_str(), *line))),
+            error => (error, None),
+        };
+
         if let ThrushError::Parse(
             ThrushErrorKind::ParsedNumber
             | ThrushErrorKind::UnreachableNumber
             | ThrushErrorKind::SyntaxError
             | ThrushErrorKind::UnreachableVariable
-            | ThrushErrorKind::VariableNotDefined,
+            | ThrushErrorKind::VariableNotDefined
+            | ThrushErrorKind::CircularInclude
+            | ThrushErrorKind::TypeMismatch
+            | ThrushErrorKind::Redefinition,
             title,
             help,
             line,
         ) = error
         {
-            self.print_report(title, help, *line);
+            self.print_report(title, help, *line, column, related);
         } else if let ThrushError::Lex(
             ThrushErrorKind::SyntaxError
             | ThrushErrorKind::ParsedNumber
@@ -55,45 +66,50 @@ impl Diagnostic {
             line,
         ) = error
         {
-            self.print_report(title, help, *line);
+            self.print_report(title, help, *line, column, related);
         } else if let ThrushError::Scope(
-            ThrushErrorKind::UnreachableVariable | ThrushErrorKind::VariableNotDefined,
+            ThrushErrorKind::UnreachableVariable
+            | ThrushErrorKind::VariableNotDefined
+            | ThrushErrorKind::FunctionNotDefined,
             title,
             help,
             line,
         ) = error
         {
-            self.print_report(title, help, *line);
+            self.print_report(title, help, *line, column, related);
+        } else if let ThrushError::Compile(title) = error {
+            self.print_compile_report(title);
         }
     }
 
-    fn print_report(&mut self, title: &str, help: &str, line: usize) {
-        self.print_header(line, title);
+    fn print_compile_report(&mut self, title: &str) {
+        println!("\n{}\n", NAME.lock().unwrap().bold().bright_red());
 
-        let content: &str = if line == self.lines.len() - 1 {
-            self.lines.last().unwrap().trim()
-        } else {
-            self.lines[line - 1].trim()
-        };
+        println!("{} {}\n", "ERROR".bold().bright_red().underline(), title);
+    }
 
-        self.buffer.push_str("  ");
-        self.drawer.push_str(&format!("{} | ", line));
-        self.buffer.push_str(&format!("{}\n", content));
+    fn print_report(
+        &mut self,
+        title: &str,
+        help: &str,
+        line: usize,
+        column: Option<usize>,
+        related: Option<(&str, usize)>,
+    ) {
+        self.print_header(line, column, title);
+        self.print_snippet(line);
 
-        println!("|\n|");
+        if let Some((note, line)) = related {
+            println!(
+                "\n{}{} {}",
+                "Note".bold().bright_blue(),
+                ":".bold(),
+                note.bold()
+            );
 
-        for _ in 0..content.len() + 6 {
-            self.drawer
-                .push_str("─".bold().bright_red().to_string().as_str());
+            self.print_snippet(line);
         }
 
-        self.buffer.push_str(&self.drawer);
-
-        println!("{}", self.buffer);
-
-        self.drawer.clear();
-        self.buffer.clear();
-
         println!(
             "\n{}{} {}\n",
             "Help".bold().bright_green(),
@@ -102,9 +118,100 @@ impl Diagnostic {
         );
     }
 
-    fn print_header(&mut self, line: usize, title: &str) {
-        println!("\n{} {}\n", NAME.lock().unwrap().bold().bright_red(), line);
+    /// Prints the line with an underline as wide as its code.
+    fn print_snippet(&self, line: usize) {
+        println!("|\n|");
+        println!("{}", self.snippet(line));
+    }
+
+    fn snippet(&self, line: usize) -> String {
+        // An error at the end of the file can be past its last line.
+        let content: &str = self
+            .lines
+            .get(line.saturating_sub(1))
+            .or(self.lines.last())
+            .map_or("", |content| content.trim());
+
+        let mut drawer: String = format!("{} | ", line);
+
+        for _ in 0..content.len() + 6 {
+            drawer.push_str("─".bold().bright_red().to_string().as_str());
+        }
+
+        format!("  {}\n{}", content, drawer)
+    }
+
+    /// The column of a byte of the source, counted in characters from 1 so tabs and
+    /// multibyte characters don't shift it.
+    fn column(&self, offset: usize) -> Option<usize> {
+        let before: &str = self.source.get(..offset)?;
+        let start: usize = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        Some(before[start..].chars().count() + 1)
+    }
+
+    /// Prints the location as 'path:line:column', the form editors and terminals
+    /// can jump to. Without the token of the error, only the line is known.
+    fn print_header(&mut self, line: usize, column: Option<usize>, title: &str) {
+        let location: String = match column {
+            Some(column) => format!("{}:{}:{}", self.path, line, column),
+            None => format!("{}:{}", self.path, line),
+        };
+
+        println!("\n{}\n", location.bold().bright_red());
 
         println!("{} {}\n", "ERROR".bold().bright_red().underline(), title);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            backend::compiler::tests::{source_file, SourceFile},
+            PATH,
+        },
+        *,
+    };
+
+    #[test]
+    fn the_column_counts_characters_from_the_start_of_the_line() {
+        let source: &str = "fn main() {\n\tlet é = 1;\n}";
+        let _source: SourceFile = source_file("column", source);
+
+        let diagnostic: Diagnostic = Diagnostic::new(&PATH.lock().unwrap());
+
+        // The '=' after a tab and a two byte character.
+        assert_eq!(diagnostic.column(source.find('=').unwrap()), Some(8));
+        assert_eq!(diagnostic.column(0), Some(1));
+        assert_eq!(diagnostic.column(source.len() + 1), None);
+    }
+
+    #[test]
+    fn a_crlf_line_is_underlined_as_wide_as_its_code() {
+        let _source: SourceFile = source_file(
+            "crlf",
+            "fn main() {\r\n\tlet x = 1;\r\n\tprintln(x);\r\n}\r\n",
+        );
+
+        let diagnostic: Diagnostic = Diagnostic::new(&PATH.lock().unwrap());
+
+        let snippet: String = diagnostic.snippet(2);
+        let (content, underline): (&str, &str) = snippet.split_once('\n').unwrap();
+
+        assert_eq!(content, "  let x = 1;");
+        assert!(underline.starts_with("2 | "), "{}", underline);
+        assert_eq!(underline.matches('─').count(), "let x = 1;".len() + 6);
+    }
+
+    #[test]
+    fn a_line_past_the_end_shows_the_last_line() {
+        let _source: SourceFile = source_file("past-the-end", "fn main() {\n    let x = 1;\n}");
+
+        let diagnostic: Diagnostic = Diagnostic::new(&PATH.lock().unwrap());
+
+        assert!(diagnostic.snippet(2).starts_with("  let x = 1;\n"));
+        assert!(diagnostic.snippet(3).starts_with("  }\n"));
+        assert!(diagnostic.snippet(5).starts_with("  }\n"));
+    }
+}
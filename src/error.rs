@@ -4,6 +4,10 @@ pub enum ThrushError {
     Parse(ThrushErrorKind, String, String, usize),
     Lex(ThrushErrorKind, String, String, usize),
     Scope(ThrushErrorKind, String, String, usize),
+    /// An error with a second location that explains it, with its note and line.
+    Related(Box<ThrushError>, String, usize),
+    /// An error at the token that caused it, with the byte offset of the token in the source.
+    Located(Box<ThrushError>, usize),
     #[default]
     None,
 }
@@ -17,4 +21,8 @@ pub enum ThrushErrorKind {
     UnknownChar,
     UnreachableVariable,
     VariableNotDefined,
+    FunctionNotDefined,
+    CircularInclude,
+    TypeMismatch,
+    Redefinition,
 }
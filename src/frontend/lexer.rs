@@ -5,7 +5,7 @@ use {
         PATH
     },
     core::str,
-    std::num::ParseFloatError,
+    std::num::{ParseFloatError, ParseIntError},
 };
 
 pub struct Lexer<'a> {
@@ -31,9 +31,9 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    pub fn lex(&mut self) -> Result<&[Token], String> {
+    pub fn lex(&mut self, max_errors: usize) -> Result<&[Token], String> {
         while !self.end() {
-            if self.errors.len() >= 50 {
+            if self.errors.len() >= max_errors {
                 break;
             }
 
@@ -41,7 +41,7 @@ impl<'a> Lexer<'a> {
 
             match self.scan() {
                 Ok(()) => {}
-                Err(e) => self.errors.push(e),
+                Err(e) => self.errors.push(ThrushError::Located(Box::new(e), self.start)),
             }
         }
 
@@ -53,6 +53,7 @@ impl<'a> Lexer<'a> {
             return Err(String::from("Compilation proccess ended with errors."));
         };
 
+        self.start = self.current;
         self.make(TokenKind::Eof);
 
         Ok(self.tokens.as_slice())
@@ -112,9 +113,11 @@ impl<'a> Lexer<'a> {
             b'>' => self.make(TokenKind::Greater),
             b'|' if self.char_match(b'|') => self.make(TokenKind::Or),
             b'&' if self.char_match(b'&') => self.make(TokenKind::And),
+            b'@' => self.make(TokenKind::At),
             b' ' | b'\r' | b'\t' => {}
             b'\n' => self.line += 1,
             b'"' => self.string()?,
+            b'\'' => self.character()?,
             b'0'..=b'9' => self.integer()?,
             b'a'..=b'z' | b'A'..=b'Z' | b'_' => self.identifier()?,
             _ => {
@@ -130,6 +133,77 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
+    /// A character literal like `'A'` or `'\n'`, or else a loop label. Characters are a
+    /// single byte, so only ASCII fits in one.
+    fn character(&mut self) -> Result<(), ThrushError> {
+        let byte: u8 = match (self.peek(), self.peek_next()) {
+            (b'\\', escape) => {
+                self.current = (self.current + 2).min(self.code.len());
+
+                match escape {
+                    b'n' => b'\n',
+                    b't' => b'\t',
+                    b'r' => b'\r',
+                    b'0' => b'\0',
+                    b'\\' => b'\\',
+                    b'\'' => b'\'',
+                    _ => {
+                        return Err(ThrushError::Lex(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            String::from(
+                                "Unknown escape in a character. Use '\\n', '\\t', '\\r', '\\0', '\\\\' or '\\''.",
+                            ),
+                            self.line,
+                        ));
+                    }
+                }
+            }
+
+            (byte, b'\'') if byte != b'\'' && byte != b'\n' => self.advance(),
+
+            _ => return self.label(),
+        };
+
+        if !self.char_match(b'\'') {
+            return Err(ThrushError::Lex(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Unterminated character. Did you forget to close it with a '?"),
+                self.line,
+            ));
+        }
+
+        self.tokens.push(Token {
+            kind: TokenKind::Char(byte),
+            lexeme: None,
+            line: self.line,
+            span: self.span(),
+        });
+
+        Ok(())
+    }
+
+    /// A loop label like `'outer`, the quote is kept in the lexeme.
+    fn label(&mut self) -> Result<(), ThrushError> {
+        if !self.is_alpha(self.peek()) {
+            return Err(ThrushError::Lex(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Expected the name of a label after '. Like 'outer: loop { ... }."),
+                self.line,
+            ));
+        }
+
+        while self.is_alpha(self.peek()) || self.peek().is_ascii_digit() {
+            self.advance();
+        }
+
+        self.make(TokenKind::Label);
+
+        Ok(())
+    }
+
     fn identifier(&mut self) -> Result<(), ThrushError> {
 
         while self.is_alpha(self.peek()) || self.peek().is_ascii_digit() {
@@ -138,12 +212,14 @@ impl<'a> Lexer<'a> {
 
         match str::from_utf8(&self.code[self.start..self.current]).unwrap() {
             "let" => self.make(TokenKind::Let),
+            "static" => self.make(TokenKind::Static),
             "fn" => self.make(TokenKind::Fn),
             "if" => self.make(TokenKind::If),
             "elif" => self.make(TokenKind::Elif),
             "else" => self.make(TokenKind::Else),
             "for" => self.make(TokenKind::For),
             "while" => self.make(TokenKind::While),
+            "loop" => self.make(TokenKind::Loop),
             "true" => self.make(TokenKind::True),
             "false" => self.make(TokenKind::False),
             "or" => self.make(TokenKind::Or),
@@ -155,11 +231,28 @@ impl<'a> Lexer<'a> {
             "continue" => self.make(TokenKind::Continue),
             "println" => self.make(TokenKind::Println),
             "print" => self.make(TokenKind::Print),
+            "eprintln" => self.make(TokenKind::EPrintln),
+            "eprint" => self.make(TokenKind::EPrint),
+            "argc" => self.make(TokenKind::Argc),
+            "argv" => self.make(TokenKind::Argv),
+            "getenv" => self.make(TokenKind::GetEnv),
+            "to_string" => self.make(TokenKind::ToString),
+            "include" => self.make(TokenKind::Include),
+            "bitcast" => self.make(TokenKind::BitCast),
+            "as" => self.make(TokenKind::As),
+            "defer" => self.make(TokenKind::Defer),
+            "fence" => self.make(TokenKind::Fence),
+            "atomic_load" => self.make(TokenKind::AtomicLoad),
+            "atomic_store" => self.make(TokenKind::AtomicStore),
+            "unsafe" => self.make(TokenKind::Unsafe),
+            "asm" => self.make(TokenKind::Asm),
             "super" => self.make(TokenKind::Super),
             "this" => self.make(TokenKind::This),
             "extends" => self.make(TokenKind::Extends),
             "public" => self.make(TokenKind::Public),
             "null" => self.make(TokenKind::Null),
+            "restrict" => self.make(TokenKind::Restrict),
+            "inline" => self.make(TokenKind::Inline),
 
             "u8" => self.make(TokenKind::DataType(DataTypes::U8)),
             "u16" => self.make(TokenKind::DataType(DataTypes::U16)),
@@ -171,6 +264,8 @@ impl<'a> Lexer<'a> {
             "i32" => self.make(TokenKind::DataType(DataTypes::I32)),
             "i64" => self.make(TokenKind::DataType(DataTypes::I64)),
 
+            "f16" => self.make(TokenKind::DataType(DataTypes::F16)),
+            "bf16" => self.make(TokenKind::DataType(DataTypes::BF16)),
             "f32" => self.make(TokenKind::DataType(DataTypes::F32)),
             "f64" => self.make(TokenKind::DataType(DataTypes::F64)),
 
@@ -180,6 +275,10 @@ impl<'a> Lexer<'a> {
 
             "void" => self.make(TokenKind::DataType(DataTypes::Void)),
 
+            "ptr" => self.make(TokenKind::DataType(DataTypes::Ptr)),
+
+            "char" => self.make(TokenKind::DataType(DataTypes::Char)),
+
             "float" => self.make(TokenKind::DataType(DataTypes::Float)),
             "integer" => self.make(TokenKind::DataType(DataTypes::Integer)),
 
@@ -188,6 +287,7 @@ impl<'a> Lexer<'a> {
                     kind: TokenKind::Identifier,
                     lexeme: Some(self.lexeme()),
                     line: self.line,
+                    span: self.span(),
                 });
             }
         }
@@ -207,50 +307,102 @@ impl<'a> Lexer<'a> {
         let kind: DataTypes =
             self.eval_integer_type(self.lexeme(), self.line)?;
 
-        let num: Result<f64, ParseFloatError> = self.lexeme().parse::<f64>();
+        // Integers are kept exact, an 'f64' would round anything past 2^53.
+        let token: TokenKind = if kind.is_float() {
+            let num: Result<f64, ParseFloatError> = self.lexeme().parse::<f64>();
 
-        if num.is_err() {
-            return Err(ThrushError::Parse(
-                ThrushErrorKind::ParsedNumber,
+            if num.is_err() {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::ParsedNumber,
+                    String::from("The number is too big for a float."),
+                    String::from(
+                        "Did you provide a valid number with the correct format and not out of bounds?",
+                    ),
+                    self.line,
+                ));
+            }
 
-                String::from("The number is too big for an integer."),
-                String::from(
-                    "Did you provide a valid number with the correct format and not out of bounds?",
-                ),
-                self.line,
-            ));
-        }
+            TokenKind::Float(kind, num.unwrap())
+        } else {
+            let num: Result<u64, ParseIntError> = self.lexeme().parse::<u64>();
+
+            if num.is_err() {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::ParsedNumber,
+                    String::from("The number is too big for an integer."),
+                    String::from(
+                        "Did you provide a valid number with the correct format and not out of bounds?",
+                    ),
+                    self.line,
+                ));
+            }
+
+            TokenKind::Integer(kind, num.unwrap())
+        };
 
         self.tokens.push(Token {
-            kind: TokenKind::Integer(kind, num.unwrap()),
+            kind: token,
             lexeme: None,
-            line: self.line
+            line: self.line,
+            span: self.span(),
         });
 
         Ok(())
     }
 
     fn string(&mut self) -> Result<(), ThrushError> {
+        let line: usize = self.line;
 
-        while self.peek() != b'"' && !self.end() {
-            self.advance();
+        // Triple-quoted strings keep the line breaks written inside them.
+        let triple: bool = self.peek() == b'"' && self.peek_next() == b'"';
+
+        if triple {
+            self.current += 2;
         }
 
-        if self.peek() != b'"' {
-            return Err(ThrushError::Lex(
-                ThrushErrorKind::SyntaxError,
-                String::from("Syntax Error"),
-                String::from(
-                    "Unterminated string. Did you forget to close the string with a '\"'?",
-                ),
-                self.line,
-            ));
+        let mut content: Vec<u8> = Vec::new();
+
+        loop {
+            if self.end() {
+                return Err(ThrushError::Lex(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    String::from(
+                        "Unterminated string. Did you forget to close the string with a '\"'?",
+                    ),
+                    line,
+                ));
+            }
+
+            if self.peek() == b'"'
+                && (!triple
+                    || (self.peek_next() == b'"' && self.code.get(self.current + 2) == Some(&b'"')))
+            {
+                break;
+            }
+
+            match self.advance() {
+                // A trailing backslash joins the line with the next one, minus its indentation.
+                b'\\' if matches!(self.peek(), b'\n' | b'\r') => {
+                    self.char_match(b'\r');
+                    self.char_match(b'\n');
+                    self.line += 1;
+
+                    while matches!(self.peek(), b' ' | b'\t') {
+                        self.advance();
+                    }
+                }
+                b'\n' => {
+                    self.line += 1;
+                    content.push(b'\n');
+                }
+                ch => content.push(ch),
+            }
         }
 
-        self.advance();
+        self.current += if triple { 3 } else { 1 };
 
-        let mut string: String =
-            String::from_utf8_lossy(&self.code[self.start + 1..self.current - 1]).to_string();
+        let mut string: String = String::from_utf8_lossy(&content).to_string();
 
         string.push('\0');
 
@@ -259,7 +411,8 @@ impl<'a> Lexer<'a> {
         self.tokens.push(Token {
             kind: TokenKind::String,
             lexeme: Some(string),
-            line: self.line,
+            line,
+            span: self.span(),
         });
 
         Ok(())
@@ -387,11 +540,19 @@ impl<'a> Lexer<'a> {
         String::from_utf8_lossy(&self.code[self.start..self.current]).to_string()
     }
 
+    fn span(&self) -> TokenSpan {
+        TokenSpan {
+            start: self.start,
+            end: self.current,
+        }
+    }
+
     fn make(&mut self, kind: TokenKind) {
         self.tokens.push(Token {
             kind,
             lexeme: Some(self.lexeme()),
             line: self.line,
+            span: self.span(),
         });
     }
 }
@@ -401,6 +562,31 @@ pub struct Token {
     pub lexeme: Option<String>,
     pub kind: TokenKind,
     pub line: usize,
+    pub span: TokenSpan,
+}
+
+/// The byte offsets of a token in its file, `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// One token per line as `{line} {start}..{end} {kind}` and the lexeme if it has one,
+/// `--dump-tokens` prints this so the output can be diffed between versions.
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}..{} {:?}",
+            self.line, self.span.start, self.span.end, self.kind
+        )?;
+
+        match &self.lexeme {
+            Some(lexeme) => write!(f, " {:?}", lexeme),
+            None => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -432,10 +618,14 @@ pub enum TokenKind {
     LessEqual,    // ' <= '
     PlusPlus,     // ' ++ '
     MinusMinus,   // ' -- '
+    At,           // ' @ '
 
     // --- Literals ---
     Identifier,
-    Integer(DataTypes, f64),
+    Label,
+    Integer(DataTypes, u64),
+    Float(DataTypes, f64),
+    Char(u8),
     DataType(DataTypes),
     String,
 
@@ -455,14 +645,33 @@ pub enum TokenKind {
     Or,
     Println,
     Print,
+    EPrintln,
+    EPrint,
+    Argc,
+    Argv,
+    GetEnv,
+    ToString,
+    Include,
+    BitCast,
+    As,
+    Defer,
+    Fence,
+    AtomicLoad,
+    AtomicStore,
+    Unsafe,
+    Asm,
     Return,
     Super,
     This,
     True,
     Let,
+    Static,
     Const,
     While,
+    Loop,
     Extends,
+    Restrict,
+    Inline,
 
     Eof,
 }
@@ -496,7 +705,9 @@ impl std::fmt::Display for TokenKind {
             TokenKind::LessEqual => write!(f, "<="),
             TokenKind::PlusPlus => write!(f, "++"),
             TokenKind::MinusMinus => write!(f, "--"),
+            TokenKind::At => write!(f, "@"),
             TokenKind::Identifier => write!(f, "Identifier"),
+            TokenKind::Label => write!(f, "Label"),
             TokenKind::And => write!(f, "and"),
             TokenKind::Struct => write!(f, "struct"),
             TokenKind::Else => write!(f, "else"),
@@ -512,15 +723,36 @@ impl std::fmt::Display for TokenKind {
             TokenKind::Or => write!(f, "or"),
             TokenKind::Println => write!(f, "println"),
             TokenKind::Print => write!(f, "print"),
+            TokenKind::EPrintln => write!(f, "eprintln"),
+            TokenKind::EPrint => write!(f, "eprint"),
+            TokenKind::Argc => write!(f, "argc"),
+            TokenKind::Argv => write!(f, "argv"),
+            TokenKind::GetEnv => write!(f, "getenv"),
+            TokenKind::ToString => write!(f, "to_string"),
+            TokenKind::Include => write!(f, "include"),
+            TokenKind::BitCast => write!(f, "bitcast"),
+            TokenKind::As => write!(f, "as"),
+            TokenKind::Defer => write!(f, "defer"),
+            TokenKind::Fence => write!(f, "fence"),
+            TokenKind::AtomicLoad => write!(f, "atomic_load"),
+            TokenKind::AtomicStore => write!(f, "atomic_store"),
+            TokenKind::Unsafe => write!(f, "unsafe"),
+            TokenKind::Asm => write!(f, "asm"),
             TokenKind::Return => write!(f, "return"),
             TokenKind::Super => write!(f, "super"),
             TokenKind::This => write!(f, "this"),
             TokenKind::True => write!(f, "true"),
             TokenKind::Let => write!(f, "let"),
+            TokenKind::Static => write!(f, "static"),
             TokenKind::Const => write!(f, "const"),
             TokenKind::While => write!(f, "while"),
+            TokenKind::Loop => write!(f, "loop"),
             TokenKind::Extends => write!(f, "extends"),
+            TokenKind::Restrict => write!(f, "restrict"),
+            TokenKind::Inline => write!(f, "inline"),
             TokenKind::Integer(_, _) => write!(f, "Integer"),
+            TokenKind::Float(_, _) => write!(f, "Float"),
+            TokenKind::Char(_) => write!(f, "Char"),
             TokenKind::String => write!(f, "String"),
             TokenKind::Eof => write!(f, "EOF"),
             TokenKind::DataType(datatype) => write!(f, "{}", datatype),
@@ -542,6 +774,8 @@ pub enum DataTypes {
     Integer,
 
     // Floating Point DataTypes
+    F16,
+    BF16,
     F32,
     F64,
     Float,
@@ -552,6 +786,15 @@ pub enum DataTypes {
     // String DataTypes
     String,
 
+    // Pointer DataTypes
+    Ptr,
+
+    // Character DataTypes
+    Char,
+
+    // Array DataTypes, the type of the elements and their count
+    Array(Box<DataTypes>, u32),
+
     // Void Type
     Void,
 }
@@ -567,11 +810,16 @@ impl std::fmt::Display for DataTypes {
             DataTypes::I16 => write!(f, "i16"),
             DataTypes::I32 => write!(f, "i32"),
             DataTypes::I64 => write!(f, "i64"),
+            DataTypes::F16 => write!(f, "f16"),
+            DataTypes::BF16 => write!(f, "bf16"),
             DataTypes::F32 => write!(f, "f32"),
             DataTypes::F64 => write!(f, "f64"),
             DataTypes::Bool => write!(f, "bool"),
             DataTypes::String => write!(f, "string"),
             DataTypes::Void => write!(f, "void"),
+            DataTypes::Ptr => write!(f, "ptr"),
+            DataTypes::Char => write!(f, "char"),
+            DataTypes::Array(kind, size) => write!(f, "{}[{}]", kind, size),
             DataTypes::Float => write!(f, "float"),
             DataTypes::Integer => write!(f, "integer")
         }
@@ -589,13 +837,85 @@ impl DataTypes {
             DataTypes::I16 => DataTypes::I16,
             DataTypes::I32 => DataTypes::I32,
             DataTypes::I64 => DataTypes::I64,
+            DataTypes::F16 => DataTypes::F16,
+            DataTypes::BF16 => DataTypes::BF16,
             DataTypes::F32 => DataTypes::F32,
             DataTypes::F64 => DataTypes::F64,
             DataTypes::Bool => DataTypes::Bool,
             DataTypes::String => DataTypes::String,
             DataTypes::Void => DataTypes::Void,
+            DataTypes::Ptr => DataTypes::Ptr,
+            DataTypes::Char => DataTypes::Char,
+            DataTypes::Array(kind, size) => DataTypes::Array(kind.clone(), *size),
             DataTypes::Integer => DataTypes::Integer,
             DataTypes::Float => DataTypes::Float
         }
     }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            DataTypes::I8
+                | DataTypes::I16
+                | DataTypes::I32
+                | DataTypes::I64
+                | DataTypes::U8
+                | DataTypes::U16
+                | DataTypes::U32
+                | DataTypes::U64
+        )
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self,
+            DataTypes::F16 | DataTypes::BF16 | DataTypes::F32 | DataTypes::F64
+        )
+    }
+
+    pub fn bit_width(&self) -> u32 {
+        match self {
+            DataTypes::I8 | DataTypes::U8 | DataTypes::Char => 8,
+            DataTypes::I16 | DataTypes::U16 | DataTypes::F16 | DataTypes::BF16 => 16,
+            DataTypes::I32 | DataTypes::U32 | DataTypes::F32 => 32,
+            DataTypes::I64 | DataTypes::U64 | DataTypes::F64 => 64,
+
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            DataTypes::I8 | DataTypes::I16 | DataTypes::I32 | DataTypes::I64
+        )
+    }
+
+    pub fn min_integer(&self) -> i64 {
+        if self.is_signed() {
+            -(self.max_integer() as i64) - 1
+        } else {
+            0
+        }
+    }
+
+    pub fn max_integer(&self) -> u64 {
+        if self.is_signed() {
+            u64::MAX >> (65 - self.bit_width())
+        } else {
+            u64::MAX >> (64 - self.bit_width())
+        }
+    }
+
+    /// The largest finite value, the smallest one is its negation.
+    pub fn max_float(&self) -> f64 {
+        match self {
+            DataTypes::F16 => 65_504.0,
+            DataTypes::BF16 => 3.389_531_389_251_535_5e38,
+            DataTypes::F32 => f32::MAX as f64,
+            DataTypes::F64 => f64::MAX,
+
+            _ => unreachable!(),
+        }
+    }
 }
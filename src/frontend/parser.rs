@@ -9,6 +9,7 @@ use {
         lexer::{DataTypes, Token, TokenKind},
     },
     ahash::AHashMap as HashMap,
+    inkwell::{AtomicOrdering, GlobalVisibility},
 };
 
 const VALID_INTEGER_TYPES: [DataTypes; 8] = [
@@ -21,9 +22,32 @@ const VALID_INTEGER_TYPES: [DataTypes; 8] = [
     DataTypes::I32,
     DataTypes::I64,
 ];
-const VALID_FLOAT_TYPES: [DataTypes; 2] = [DataTypes::F32, DataTypes::F64];
+const VALID_FLOAT_TYPES: [DataTypes; 4] = [
+    DataTypes::F16,
+    DataTypes::BF16,
+    DataTypes::F32,
+    DataTypes::F64,
+];
+
+// LLVM only accepts the orderings that synchronize something on a fence.
+const FENCE_ORDERINGS: [(&str, AtomicOrdering); 4] = [
+    ("acquire", AtomicOrdering::Acquire),
+    ("release", AtomicOrdering::Release),
+    ("acq_rel", AtomicOrdering::AcquireRelease),
+    ("seq_cst", AtomicOrdering::SequentiallyConsistent),
+];
 
-const C_FMTS: [&str; 2] = ["%s", "%d"];
+// A load can't release and a store can't acquire.
+const LOAD_ORDERINGS: [(&str, AtomicOrdering); 3] = [
+    ("relaxed", AtomicOrdering::Monotonic),
+    ("acquire", AtomicOrdering::Acquire),
+    ("seq_cst", AtomicOrdering::SequentiallyConsistent),
+];
+const STORE_ORDERINGS: [(&str, AtomicOrdering); 3] = [
+    ("relaxed", AtomicOrdering::Monotonic),
+    ("release", AtomicOrdering::Release),
+    ("seq_cst", AtomicOrdering::SequentiallyConsistent),
+];
 
 pub struct Parser<'instr, 'a> {
     stmts: Vec<Instruction<'instr>>,
@@ -31,14 +55,21 @@ pub struct Parser<'instr, 'a> {
     pub tokens: Option<&'instr [Token]>,
     pub options: Option<&'a Options>,
     function: u16,
+    loops: Vec<Option<&'instr str>>,
     ret: Option<DataTypes>,
     current: usize,
     globals: HashMap<&'instr str, DataTypes>,
-    locals: Vec<HashMap<&'instr str, DataTypes>>,
+    locals: Vec<HashMap<&'instr str, (DataTypes, usize)>>,
     scope: usize,
     scoper: ThrushScoper<'instr>,
     diagnostics: Diagnostic,
     has_entry_point: bool,
+    in_entry_point: bool,
+    visibility: Option<GlobalVisibility>,
+    export: bool,
+    thread_local: bool,
+    volatile: bool,
+    naked: bool,
 }
 
 impl<'instr, 'a> Parser<'instr, 'a> {
@@ -51,12 +82,19 @@ impl<'instr, 'a> Parser<'instr, 'a> {
             current: 0,
             ret: None,
             function: 0,
+            loops: Vec::new(),
             globals: HashMap::new(),
             locals: vec![HashMap::new()],
             scope: 0,
             scoper: ThrushScoper::new(),
             diagnostics: Diagnostic::new(&PATH.lock().unwrap()),
             has_entry_point: false,
+            in_entry_point: false,
+            visibility: None,
+            export: false,
+            thread_local: false,
+            volatile: false,
+            naked: false,
         }
     }
 
@@ -67,11 +105,11 @@ impl<'instr, 'a> Parser<'instr, 'a> {
                     self.stmts.push(instr);
                 }
                 Err(e) => {
-                    if self.errors.len() >= 50 {
+                    self.errors.push(e);
+
+                    if self.errors.len() >= self.options.unwrap().max_errors() {
                         break;
                     }
-
-                    self.errors.push(e)
                 }
             }
         }
@@ -90,27 +128,112 @@ impl<'instr, 'a> Parser<'instr, 'a> {
             return Err(String::from("Compilation proccess ended with errors."));
         }
 
-        self.scoper.analyze()?;
+        self.scoper.analyze(self.options.unwrap().max_errors())?;
 
         Ok(self.stmts.as_slice())
     }
 
     fn parse(&mut self) -> Result<Instruction<'instr>, ThrushError> {
         match &self.peek().kind {
-            TokenKind::Println => Ok(self.println()?),
-            TokenKind::Print => Ok(self.print()?),
+            TokenKind::Println => Ok(self.println(false)?),
+            TokenKind::Print => Ok(self.print(false)?),
+            TokenKind::EPrintln => Ok(self.println(true)?),
+            TokenKind::EPrint => Ok(self.print(true)?),
             TokenKind::Fn => Ok(self.function(false)?),
             TokenKind::LBrace => Ok(self.block()?),
             TokenKind::Return => Ok(self.ret()?),
+            TokenKind::If => Ok(self.if_else()?),
+            TokenKind::While => Ok(self.while_loop(None)?),
+            TokenKind::Loop => Ok(self.infinite_loop(None)?),
+            TokenKind::Label => Ok(self.labeled_loop()?),
+            TokenKind::Break => Ok(self.break_loop()?),
+            TokenKind::Defer => Ok(self.defer()?),
+            TokenKind::Fence => Ok(self.fence()?),
+            TokenKind::Unsafe => Ok(self.inline_asm()?),
             TokenKind::Public => Ok(self.public()?),
             TokenKind::Let => Ok(self.variable()?),
+            TokenKind::Static => Ok(self.static_variable()?),
+            TokenKind::Const => Ok(self.constant()?),
+            TokenKind::At => Ok(self.attributes()?),
             _ => Ok(self.expr()?),
         }
     }
 
+    fn attributes(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        while self.match_token(TokenKind::At)? {
+            let attribute: &'instr Token = self.consume(
+                TokenKind::Identifier,
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Expected attribute name after '@'."),
+            )?;
+
+            match attribute.lexeme.as_ref().unwrap().as_str() {
+                "default" => self.visibility = Some(GlobalVisibility::Default),
+                "hidden" => self.visibility = Some(GlobalVisibility::Hidden),
+                "protected" => self.visibility = Some(GlobalVisibility::Protected),
+                "export" => self.export = true,
+                "thread_local" => self.thread_local = true,
+                "volatile" => self.volatile = true,
+                "naked" => self.naked = true,
+                "extern" => {
+                    let abi: &'instr Token = self.consume(
+                        TokenKind::String,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected the ABI of the function. Like '@extern \"C\"'."),
+                    )?;
+
+                    if abi.lexeme.as_ref().unwrap().trim_end_matches('\0') != "C" {
+                        return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            String::from("The only supported ABI is '@extern \"C\"'."),
+                            abi.line,
+                        ));
+                    }
+
+                    self.export = true;
+                }
+                name => {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        format!(
+                            "Unknown attribute '@{}'. Expected '@default', '@hidden', '@protected', '@thread_local', '@volatile', '@naked', '@export' or '@extern \"C\"'.",
+                            name
+                        ),
+                        attribute.line,
+                    ));
+                }
+            }
+        }
+
+        match &self.peek().kind {
+            TokenKind::Fn
+            | TokenKind::Public
+            | TokenKind::Let
+            | TokenKind::Static
+            | TokenKind::Const => self.parse(),
+            _ => Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Attributes can only be applied to functions and variables."),
+                self.peek().line,
+            )),
+        }
+    }
+
     fn variable(&mut self) -> Result<Instruction<'instr>, ThrushError> {
         self.only_advance()?;
 
+        let visibility: Option<GlobalVisibility> = self.visibility.take();
+        let thread_local: bool = self.thread_local;
+        let volatile: bool = self.volatile;
+
+        self.thread_local = false;
+        self.volatile = false;
+
         let name: &'instr Token = self.consume(
             TokenKind::Identifier,
             ThrushErrorKind::SyntaxError,
@@ -118,11 +241,55 @@ impl<'instr, 'a> Parser<'instr, 'a> {
             String::from("Expected let <name>."),
         )?;
 
+        if visibility.is_some() && self.scope != 0 {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Only variables in the global scope can have a visibility attribute."),
+                name.line,
+            ));
+        }
+
+        if thread_local && self.scope != 0 {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Only variables in the global scope can be '@thread_local'."),
+                name.line,
+            ));
+        }
+
+        if self.export {
+            self.export = false;
+
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Only functions can be exported with '@export' or '@extern \"C\"'."),
+                name.line,
+            ));
+        }
+
+        if self.naked {
+            self.naked = false;
+
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Only functions can be '@naked'."),
+                name.line,
+            ));
+        }
+
         let mut kind: Option<DataTypes> = match &self.peek().kind {
             TokenKind::DataType(kind) => {
                 self.only_advance()?;
 
-                Some(kind.defer())
+                if self.peek().kind == TokenKind::LeftBracket {
+                    Some(self.array_type(kind.defer(), name.line)?)
+                } else {
+                    Some(kind.defer())
+                }
             }
 
             TokenKind::Eq => None,
@@ -162,11 +329,21 @@ impl<'instr, 'a> Parser<'instr, 'a> {
                 String::from("Expected ';'."),
             )?;
 
+            self.define_local(
+                name.lexeme.as_ref().unwrap(),
+                kind.as_ref().unwrap().defer(),
+                name.line,
+            )?;
+
             return Ok(Instruction::Var {
                 name: name.lexeme.as_ref().unwrap(),
                 kind: kind.unwrap(),
                 value: None,
                 line: name.line,
+                visibility,
+                thread_local,
+                volatile,
+                is_static: false,
             });
         }
 
@@ -177,11 +354,118 @@ impl<'instr, 'a> Parser<'instr, 'a> {
             String::from("Expected '=' for the variable definition."),
         )?;
 
-        let value: Instruction<'instr> = self.parse()?;
+        let mut value: Instruction<'instr> = self.parse()?;
+
+        // Without a declared type, the literals of an array default to 'i32' and 'f64'.
+        if let Instruction::Array {
+            kind: DataTypes::Array(element, size),
+            ..
+        } = &value
+        {
+            let declared: DataTypes = kind.take().unwrap_or_else(|| {
+                let element: DataTypes = match **element {
+                    DataTypes::Integer => DataTypes::I32,
+                    DataTypes::Float => DataTypes::F64,
+                    ref element => element.defer(),
+                };
+
+                DataTypes::Array(Box::new(element), *size)
+            });
+
+            Self::check_array(&mut value, &declared, name.line)?;
+
+            kind = Some(declared);
+        }
+
+        // Float literals are lexed as 'f64', narrow them to the declared float type.
+        if let (Some(kind), Instruction::Float(data_type, num)) = (&kind, &value) {
+            if VALID_FLOAT_TYPES.contains(kind) && VALID_FLOAT_TYPES.contains(data_type) {
+                value = Instruction::Float(kind.defer(), *num);
+            }
+        }
+
+        // A block initializer evaluates to its last expression, which must be a number.
+        if let Instruction::Block { stmts } = &mut value {
+            // Arithmetic takes the declared type, like a plain initializer.
+            if let Some(
+                Instruction::BinaryOp {
+                    kind: data_type, ..
+                }
+                | Instruction::Unary {
+                    kind: data_type, ..
+                },
+            ) = stmts.last_mut()
+            {
+                let declared: DataTypes = kind.as_ref().unwrap_or(data_type).defer();
+
+                if let Some(resolved) = Self::resolve_arithmetic(&declared, data_type) {
+                    *data_type = resolved;
+                }
+            }
+
+            if !matches!(
+                stmts.last(),
+                Some(
+                    Instruction::Integer(kind, _)
+                        | Instruction::Float(kind, _)
+                        | Instruction::RefVar { kind, .. }
+                        | Instruction::BinaryOp { kind, .. }
+                        | Instruction::Unary { kind, .. }
+                ) if VALID_INTEGER_TYPES.contains(kind) || VALID_FLOAT_TYPES.contains(kind)
+            ) {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    String::from(
+                        "The block must end with a number to initialize the variable. Like '{ let a i32 = 1; a }'.",
+                    ),
+                    name.line,
+                ));
+            }
+        }
+
+        // An arithmetic expression takes the declared type, its literals adapt to it.
+        if let Instruction::BinaryOp {
+            kind: data_type, ..
+        }
+        | Instruction::Unary {
+            kind: data_type, ..
+        } = &mut value
+        {
+            let declared: DataTypes = kind.as_ref().unwrap_or(data_type).defer();
+
+            match Self::resolve_arithmetic(&declared, data_type) {
+                Some(resolved) => {
+                    *data_type = resolved.defer();
+                    kind = Some(resolved);
+                }
+
+                None => {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        format!(
+                            "Variable type mismatch. Expected '{}' but found '{}'.",
+                            declared, data_type
+                        ),
+                        name.line,
+                    ));
+                }
+            }
+
+            Self::check_negation(&value, kind.as_ref().unwrap(), name.line)?;
+        }
+
+        if let Some(kind) = &kind {
+            Self::check_literal_range(&value, kind, name.line)?;
+        }
 
         if kind.is_some() {
             match &value {
-                Instruction::Integer(data_type, _) => {
+                Instruction::BinaryOp { .. } => {}
+                Instruction::Unary { .. } => {}
+
+                Instruction::Integer(data_type, _) | Instruction::Float(data_type, _) => {
                     match kind.as_ref().unwrap() {
                         DataTypes::Integer => {
                             if !VALID_INTEGER_TYPES.contains(data_type) {
@@ -218,7 +502,13 @@ impl<'instr, 'a> Parser<'instr, 'a> {
                         _ => {}
                     }
 
-                    if data_type != kind.as_ref().unwrap() {
+                    // A positive literal fits any integer type that holds it, the range is
+                    // checked above. Only the sign is known from the lexer.
+                    let widened: bool = VALID_INTEGER_TYPES.contains(data_type)
+                        && VALID_INTEGER_TYPES.contains(kind.as_ref().unwrap())
+                        && (!data_type.is_signed() || kind.as_ref().unwrap().is_signed());
+
+                    if data_type != kind.as_ref().unwrap() && !widened {
                         self.consume(
                             TokenKind::SemiColon,
                             ThrushErrorKind::SyntaxError,
@@ -261,6 +551,82 @@ impl<'instr, 'a> Parser<'instr, 'a> {
                     }
                 }
 
+                Instruction::Block { .. }
+                | Instruction::Call { .. }
+                | Instruction::Cast { .. }
+                | Instruction::Index { .. } => {
+                    let data_type: DataTypes = value.get_data_type();
+
+                    match kind.as_ref().unwrap() {
+                        DataTypes::Integer if VALID_INTEGER_TYPES.contains(&data_type) => {
+                            kind = Some(data_type.defer());
+                        }
+                        DataTypes::Float if VALID_FLOAT_TYPES.contains(&data_type) => {
+                            kind = Some(data_type.defer());
+                        }
+                        _ => {}
+                    }
+
+                    if &data_type != kind.as_ref().unwrap() {
+                        return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            format!(
+                                "Variable type mismatch. Expected '{}' but found '{}'.",
+                                kind.unwrap(),
+                                data_type
+                            ),
+                            name.line,
+                        ));
+                    }
+                }
+
+                Instruction::Print(_)
+                | Instruction::Println(_)
+                | Instruction::EPrint(_)
+                | Instruction::EPrintln(_) => {
+                    if kind.as_ref().unwrap() == &DataTypes::Integer {
+                        kind = Some(DataTypes::I32);
+                    }
+
+                    if kind.as_ref().unwrap() != &DataTypes::I32 {
+                        return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            format!(
+                                "Variable type mismatch. Expected '{}' but found '{}', the number of bytes written.",
+                                kind.as_ref().unwrap(),
+                                DataTypes::I32
+                            ),
+                            name.line,
+                        ));
+                    }
+                }
+
+                Instruction::Char(_) => {
+                    if kind.as_ref().unwrap() != &DataTypes::Char {
+                        self.consume(
+                            TokenKind::SemiColon,
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            String::from("Expected ';'."),
+                        )?;
+
+                        return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            format!(
+                                "Variable type mismatch. Expected '{}' but found '{}'.",
+                                kind.as_ref().unwrap(),
+                                DataTypes::Char
+                            ),
+                            name.line,
+                        ));
+                    }
+                }
+
+                Instruction::Array { .. } => {}
+
                 Instruction::Boolean(_) => {
                     if kind.as_ref().unwrap() != &DataTypes::Bool {
                         self.consume(
@@ -287,12 +653,25 @@ impl<'instr, 'a> Parser<'instr, 'a> {
             }
         }
 
+        // 'print' and 'println' consume the ';' themselves.
+        let printed: bool = matches!(
+            value,
+            Instruction::Print(_)
+                | Instruction::Println(_)
+                | Instruction::EPrint(_)
+                | Instruction::EPrintln(_)
+        );
+
         let variable: Instruction<'_> = if kind.as_ref().is_none() {
             Instruction::Var {
                 name: name.lexeme.as_ref().unwrap(),
                 kind: value.get_data_type(),
                 value: Some(Box::new(value)),
                 line: name.line,
+                visibility,
+                thread_local,
+                volatile,
+                is_static: false,
             }
         } else {
             Instruction::Var {
@@ -300,368 +679,672 @@ impl<'instr, 'a> Parser<'instr, 'a> {
                 kind: kind.unwrap(),
                 value: Some(Box::new(value)),
                 line: name.line,
+                visibility,
+                thread_local,
+                volatile,
+                is_static: false,
             }
         };
 
-        self.define_local(name.lexeme.as_ref().unwrap(), variable.get_kind().unwrap());
-
-        self.consume(
-            TokenKind::SemiColon,
-            ThrushErrorKind::SyntaxError,
-            String::from("Syntax Error"),
-            String::from("Expected ';'."),
+        self.define_local(
+            name.lexeme.as_ref().unwrap(),
+            variable.get_kind().unwrap(),
+            name.line,
         )?;
 
-        Ok(variable)
-    }
-
-    fn public(&mut self) -> Result<Instruction<'instr>, ThrushError> {
-        self.only_advance()?;
-
-        match &self.peek().kind {
-            TokenKind::Fn => Ok(self.function(true)?),
-            _ => unimplemented!(),
-        }
-    }
-
-    fn ret(&mut self) -> Result<Instruction<'instr>, ThrushError> {
-        self.only_advance()?;
-
-        if self.function == 0 {
-            return Err(ThrushError::Parse(
-                ThrushErrorKind::SyntaxError,
-                String::from("Syntax Error"),
-                String::from("Return statement outside of function. Invoke this keyword in scope of function definition."),
-                self.peek().line,
-            ));
-        }
-
-        if self.peek().kind == TokenKind::SemiColon {
+        if !printed {
             self.consume(
                 TokenKind::SemiColon,
                 ThrushErrorKind::SyntaxError,
                 String::from("Syntax Error"),
                 String::from("Expected ';'."),
             )?;
-
-            return Ok(Instruction::Return(Box::new(Instruction::Null)));
         }
 
-        let value: Instruction<'instr> = self.parse()?;
-
-        match &value {
-            Instruction::Integer(kind, _) => match kind {
-                DataTypes::U8 => self.ret = Some(DataTypes::U8),
-                DataTypes::U16 => self.ret = Some(DataTypes::U16),
-                DataTypes::U32 => self.ret = Some(DataTypes::U32),
-                DataTypes::U64 => self.ret = Some(DataTypes::U64),
+        Ok(variable)
+    }
 
-                DataTypes::I8 => self.ret = Some(DataTypes::I8),
-                DataTypes::I16 => self.ret = Some(DataTypes::I16),
-                DataTypes::I32 => self.ret = Some(DataTypes::I32),
-                DataTypes::I64 => self.ret = Some(DataTypes::I64),
+    /// The type of a fixed-size array, the size goes after the type of its elements
+    /// like in 'let a i32[3] = [1, 2, 3];'.
+    fn array_type(&mut self, element: DataTypes, line: usize) -> Result<DataTypes, ThrushError> {
+        self.only_advance()?;
 
-                DataTypes::F32 => self.ret = Some(DataTypes::F32),
-                DataTypes::F64 => self.ret = Some(DataTypes::F64),
+        if !VALID_INTEGER_TYPES.contains(&element)
+            && !VALID_FLOAT_TYPES.contains(&element)
+            && element != DataTypes::Char
+        {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!(
+                    "Arrays can only hold numbers and characters, but found '{}'.",
+                    element
+                ),
+                line,
+            ));
+        }
 
-                _ => unreachable!(),
-            },
+        let size: u32 = match &self.peek().kind {
+            TokenKind::Integer(kind, size)
+                if VALID_INTEGER_TYPES.contains(kind) && *size > 0 && *size <= u32::MAX as u64 =>
+            {
+                self.only_advance()?;
 
-            Instruction::String(_) => self.ret = Some(DataTypes::String),
-            Instruction::Boolean(_) => self.ret = Some(DataTypes::Bool),
+                *size as u32
+            }
 
-            _ => unreachable!(),
-        }
+            _ => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    String::from(
+                        "Expected the size of the array as a positive integer. Like 'let a i32[3] = [1, 2, 3];'.",
+                    ),
+                    line,
+                ));
+            }
+        };
 
         self.consume(
-            TokenKind::SemiColon,
+            TokenKind::RightBracket,
             ThrushErrorKind::SyntaxError,
             String::from("Syntax Error"),
-            String::from("Expected ';'."),
+            String::from("Expected ']'."),
         )?;
 
-        Ok(Instruction::Return(Box::new(value)))
+        Ok(DataTypes::Array(Box::new(element), size))
     }
 
-    fn block(&mut self) -> Result<Instruction<'instr>, ThrushError> {
-        self.only_advance()?;
+    /// The elements of an array literal take the type of the array like the value of a
+    /// variable: literals adapt to it and everything else must already have it.
+    fn check_array(
+        value: &mut Instruction<'instr>,
+        kind: &DataTypes,
+        line: usize,
+    ) -> Result<(), ThrushError> {
+        let (element, size): (&DataTypes, u32) = match kind {
+            DataTypes::Array(element, size) => (&**element, *size),
 
-        self.begin_scope();
+            _ => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    format!(
+                        "Variable type mismatch. Expected '{}' but found an array.",
+                        kind
+                    ),
+                    line,
+                ));
+            }
+        };
 
-        let mut stmts: Vec<Instruction> = Vec::new();
+        if let Instruction::Array {
+            elements,
+            kind: array_kind,
+        } = value
+        {
+            if elements.len() != size as usize {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    format!(
+                        "The array is declared with {} values but has {}.",
+                        size,
+                        elements.len()
+                    ),
+                    line,
+                ));
+            }
 
-        while !self.match_token(TokenKind::RBrace)? {
-            stmts.push(self.parse()?)
-        }
+            for instr in elements.iter_mut() {
+                if let Instruction::BinaryOp {
+                    kind: data_type, ..
+                }
+                | Instruction::Unary {
+                    kind: data_type, ..
+                } = instr
+                {
+                    if let Some(resolved) = Self::resolve_arithmetic(element, data_type) {
+                        *data_type = resolved;
+                    }
 
-        self.end_scope();
+                    Self::check_negation(instr, element, line)?;
+                }
 
-        self.scoper.scope(stmts.clone());
+                Self::check_literal_range(instr, element, line)?;
 
-        Ok(Instruction::Block { stmts })
+                let found: DataTypes = match instr {
+                    Instruction::Integer(..) if VALID_INTEGER_TYPES.contains(element) => {
+                        element.defer()
+                    }
+                    Instruction::Float(..) if VALID_FLOAT_TYPES.contains(element) => {
+                        element.defer()
+                    }
+
+                    Instruction::Integer(..)
+                    | Instruction::Float(..)
+                    | Instruction::Char(_)
+                    | Instruction::RefVar { .. }
+                    | Instruction::Call { .. }
+                    | Instruction::BinaryOp { .. }
+                    | Instruction::Unary { .. }
+                    | Instruction::Cast { .. }
+                    | Instruction::Index { .. } => instr.get_data_type(),
+
+                    _ => {
+                        return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            String::from("The elements of an array must be numbers or characters."),
+                            line,
+                        ));
+                    }
+                };
+
+                if found != *element {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        format!(
+                            "The elements of a '{}' array must be '{}', but found '{}'.",
+                            kind, element, found
+                        ),
+                        line,
+                    ));
+                }
+            }
+
+            *array_kind = kind.defer();
+        }
+
+        Ok(())
     }
 
-    fn function(&mut self, is_public: bool) -> Result<Instruction<'instr>, ThrushError> {
-        self.only_advance()?;
+    /// A 'static' local is declared like a 'let', but keeps its value across calls.
+    fn static_variable(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let line: usize = self.peek().line;
 
-        if self.scope != 0 {
+        if self.function == 0 {
             return Err(ThrushError::Parse(
                 ThrushErrorKind::SyntaxError,
                 String::from("Syntax Error"),
                 String::from(
-                    "The functions must go in the global scope. Rewrite it in the global scope.",
+                    "Static variable outside of function. Use 'let' for global variables.",
                 ),
-                self.previous().line,
+                line,
             ));
         }
 
-        self.begin_function();
+        let mut variable: Instruction<'instr> = self.variable()?;
 
-        let name: &'instr Token = self.consume(
-            TokenKind::Identifier,
-            ThrushErrorKind::SyntaxError,
-            String::from("Expected function name"),
-            String::from("Expected def <name>."),
-        )?;
+        if let Instruction::Var {
+            kind,
+            value,
+            is_static,
+            ..
+        } = &mut variable
+        {
+            if !VALID_INTEGER_TYPES.contains(kind) && !VALID_FLOAT_TYPES.contains(kind) {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    format!("Static variables must be numbers, but found '{}'.", kind),
+                    line,
+                ));
+            }
 
-        if name.lexeme.as_ref().unwrap() == "main" && self.options.unwrap().is_main {
-            if self.has_entry_point {
+            if !matches!(
+                value.as_deref(),
+                None | Some(Instruction::Integer(..) | Instruction::Float(..))
+            ) {
                 return Err(ThrushError::Parse(
                     ThrushErrorKind::SyntaxError,
-                    String::from("Duplicated Entry Point"),
-                    String::from("The language not support two entry points, remove one."),
-                    name.line,
+                    String::from("Syntax Error"),
+                    String::from(
+                        "Static variables can only be initialized with a number literal. Like 'static a i32 = 0;'.",
+                    ),
+                    line,
                 ));
             }
 
-            self.consume(
-                TokenKind::LParen,
+            *is_static = true;
+        }
+
+        Ok(variable)
+    }
+
+    fn public(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        self.only_advance()?;
+
+        match &self.peek().kind {
+            TokenKind::Fn => Ok(self.function(true)?),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn constant(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        self.only_advance()?;
+
+        let visibility: Option<GlobalVisibility> = self.visibility.take();
+        let misplaced: bool = self.thread_local || self.volatile || self.export || self.naked;
+
+        self.thread_local = false;
+        self.volatile = false;
+        self.export = false;
+        self.naked = false;
+
+        let name: &'instr Token = self.consume(
+            TokenKind::Identifier,
+            ThrushErrorKind::SyntaxError,
+            String::from("Expected constant name"),
+            String::from("Expected const <name>."),
+        )?;
+
+        if misplaced {
+            return Err(ThrushError::Parse(
                 ThrushErrorKind::SyntaxError,
                 String::from("Syntax Error"),
-                String::from("Expected '('."),
-            )?;
+                String::from("Constant arrays only accept a visibility attribute."),
+                name.line,
+            ));
+        }
 
-            self.consume(
-                TokenKind::RParen,
+        if self.scope != 0 {
+            return Err(ThrushError::Parse(
                 ThrushErrorKind::SyntaxError,
                 String::from("Syntax Error"),
-                String::from("Expected ')'."),
-            )?;
+                String::from("Constant arrays must go in the global scope."),
+                name.line,
+            ));
+        }
 
-            if self.peek().kind != TokenKind::LBrace {
-                return Err(ThrushError::Parse(
-                    ThrushErrorKind::SyntaxError,
-                    String::from("Syntax Error"),
-                    String::from("Expected '{'."),
-                    self.peek().line,
-                ));
-            }
+        let kind: DataTypes = match &self.peek().kind {
+            TokenKind::DataType(kind) if VALID_INTEGER_TYPES.contains(kind) => {
+                self.only_advance()?;
 
-            if self.peek().kind == TokenKind::LBrace {
-                self.has_entry_point = true;
+                kind.defer()
+            }
 
-                return Ok(Instruction::EntryPoint {
-                    body: Box::new(self.block()?),
-                });
-            } else {
+            _ => {
                 return Err(ThrushError::Parse(
                     ThrushErrorKind::SyntaxError,
                     String::from("Syntax Error"),
-                    String::from("Expected 'block' for the function body."),
-                    self.peek().line,
+                    String::from("Expected an integer type for the constant array. Like 'const PRIMES i32[5] = [2, 3, 5, 7, 11];'."),
+                    name.line,
                 ));
             }
-        }
+        };
 
         self.consume(
-            TokenKind::LParen,
+            TokenKind::LeftBracket,
             ThrushErrorKind::SyntaxError,
             String::from("Syntax Error"),
-            String::from("Expected '('."),
+            String::from("Expected '[' with the size of the array."),
         )?;
 
-        let mut params: Vec<Instruction> = Vec::with_capacity(8);
-
-        while !self.match_token(TokenKind::RParen)? {
-            if self.match_token(TokenKind::Comma)? {
-                continue;
-            }
+        let size: usize = match &self.peek().kind {
+            TokenKind::Integer(kind, size) if VALID_INTEGER_TYPES.contains(kind) => {
+                self.only_advance()?;
 
-            if params.len() >= 8 {
-                return Err(ThrushError::Parse(
-                    ThrushErrorKind::TooManyArguments,
-                    String::from("Syntax Error"),
-                    String::from("Too many arguments for the function. The maximum number of arguments is 8."),
-                    self.peek().line,
-                ));
+                *size as usize
             }
 
-            if !self.match_token(TokenKind::Identifier)? {
+            _ => {
                 return Err(ThrushError::Parse(
                     ThrushErrorKind::SyntaxError,
                     String::from("Syntax Error"),
-                    String::from("Expected argument name."),
-                    self.peek().line,
+                    String::from("Expected the size of the array as an integer."),
+                    name.line,
                 ));
             }
+        };
 
-            let ident: &str = self.previous().lexeme.as_ref().unwrap();
+        self.consume(
+            TokenKind::RightBracket,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected ']'."),
+        )?;
 
-            if !self.match_token(TokenKind::ColonColon)? {
-                return Err(ThrushError::Parse(
-                    ThrushErrorKind::SyntaxError,
-                    String::from("Syntax Error"),
-                    String::from("Expected '::'."),
-                    self.peek().line,
-                ));
-            }
+        self.consume(
+            TokenKind::Eq,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected '=' for the constant definition."),
+        )?;
 
-            let kind: DataTypes = match &self.peek().kind {
-                TokenKind::DataType(kind) => {
-                    self.only_advance()?;
+        self.consume(
+            TokenKind::LeftBracket,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected '[' with the values of the array."),
+        )?;
 
-                    kind.defer()
+        let mut values: Vec<u64> = Vec::with_capacity(size);
+
+        while !self.match_token(TokenKind::RightBracket)? {
+            if self.match_token(TokenKind::Comma)? {
+                continue;
+            }
+
+            match self.primary()? {
+                Instruction::Integer(value_kind, value)
+                    if VALID_INTEGER_TYPES.contains(&value_kind) =>
+                {
+                    values.push(value);
                 }
+
                 _ => {
                     return Err(ThrushError::Parse(
                         ThrushErrorKind::SyntaxError,
                         String::from("Syntax Error"),
-                        String::from("Expected argument type."),
-                        self.peek().line,
+                        String::from("The values of a constant array must be integer literals."),
+                        self.previous().line,
                     ));
                 }
-            };
+            }
+        }
+
+        if values.len() != size {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!(
+                    "The array '{}' is declared with {} values but has {}.",
+                    name.lexeme.as_ref().unwrap(),
+                    size,
+                    values.len()
+                ),
+                name.line,
+            ));
+        }
+
+        self.consume(
+            TokenKind::SemiColon,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected ';'."),
+        )?;
+
+        Ok(Instruction::ConstArray {
+            name: name.lexeme.as_ref().unwrap(),
+            kind,
+            values,
+            visibility,
+        })
+    }
+
+    fn ret(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        self.only_advance()?;
 
-            params.push(Instruction::Param { name: ident, kind })
+        if self.function == 0 {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Return statement outside of function. Invoke this keyword in scope of function definition."),
+                self.peek().line,
+            ));
         }
 
-        if self.peek().kind == TokenKind::Colon {
+        if self.peek().kind == TokenKind::SemiColon {
             self.consume(
-                TokenKind::Colon,
+                TokenKind::SemiColon,
                 ThrushErrorKind::SyntaxError,
                 String::from("Syntax Error"),
-                String::from("Missing return type. Expected ':' followed by return type."),
+                String::from("Expected ';'."),
             )?;
+
+            return Ok(Instruction::Return(Box::new(Instruction::Null)));
         }
 
-        let return_kind: Option<DataTypes> = match &self.peek().kind {
-            TokenKind::DataType(kind) => {
-                self.only_advance()?;
-                Some(kind.defer())
-            }
-            _ => None,
-        };
+        let value: Instruction<'instr> = Self::concrete_arithmetic(self.parse()?);
 
-        let body: Box<Instruction> = Box::new(self.block()?);
+        match &value {
+            Instruction::Integer(kind, _) | Instruction::Float(kind, _) => match kind {
+                DataTypes::U8 => self.ret = Some(DataTypes::U8),
+                DataTypes::U16 => self.ret = Some(DataTypes::U16),
+                DataTypes::U32 => self.ret = Some(DataTypes::U32),
+                DataTypes::U64 => self.ret = Some(DataTypes::U64),
 
-        match &return_kind {
-            Some(kind) => {
-                if self.ret.is_none() {
-                    return Err(ThrushError::Parse(
-                        ThrushErrorKind::SyntaxError,
-                        String::from("Syntax Error"),
-                        format!("Missing return statement with type '{}', you should add a return statement with type '{}'.", kind, kind),
-                        name.line,
-                    ));
-                }
+                DataTypes::I8 => self.ret = Some(DataTypes::I8),
+                DataTypes::I16 => self.ret = Some(DataTypes::I16),
+                DataTypes::I32 => self.ret = Some(DataTypes::I32),
+                DataTypes::I64 => self.ret = Some(DataTypes::I64),
 
-                match kind != self.ret.as_ref().unwrap() {
-                    true => {
-                        return Err(ThrushError::Parse(
-                            ThrushErrorKind::SyntaxError,
-                            String::from("Syntax Error"),
-                            format!(
-                                "Expected return type of '{}', found '{}'. You should write a return statement with type '{}'.",
-                                kind,
-                                self.ret.as_ref().unwrap(),
-                                kind
-                            ),
-                            name.line,
-                        ))
-                    }
+                DataTypes::F16 => self.ret = Some(DataTypes::F16),
+                DataTypes::BF16 => self.ret = Some(DataTypes::BF16),
+                DataTypes::F32 => self.ret = Some(DataTypes::F32),
+                DataTypes::F64 => self.ret = Some(DataTypes::F64),
 
-                    false => {}
-                }
+                _ => return Err(self.unreturnable(&value)),
+            },
+
+            Instruction::String(_) => self.ret = Some(DataTypes::String),
+            Instruction::Boolean(_)
+            | Instruction::Compare { .. }
+            | Instruction::LogicalAnd { .. }
+            | Instruction::LogicalOr { .. } => self.ret = Some(DataTypes::Bool),
+            Instruction::Char(_) => self.ret = Some(DataTypes::Char),
+            Instruction::ArgCount => self.ret = Some(DataTypes::I32),
+            Instruction::ArgAt(_) | Instruction::GetEnv(_) | Instruction::ToString(_) => {
+                self.ret = Some(DataTypes::String)
             }
+            Instruction::BinaryOp { kind, .. }
+            | Instruction::Unary { kind, .. }
+            | Instruction::Call { kind, .. }
+            | Instruction::Cast { kind, .. }
+            | Instruction::BitCast { kind, .. }
+            | Instruction::RefVar { kind, .. }
+            | Instruction::Index { kind, .. } => self.ret = Some(kind.defer()),
+
+            _ => return Err(self.unreturnable(&value)),
+        }
+
+        self.consume(
+            TokenKind::SemiColon,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected ';'."),
+        )?;
+
+        Ok(Instruction::Return(Box::new(value)))
+    }
+
+    fn unreturnable(&self, value: &Instruction) -> ThrushError {
+        ThrushError::Parse(
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            format!(
+                "A value of kind '{}' can't be returned from a function.",
+                value.variant_name()
+            ),
+            self.previous().line,
+        )
+    }
+
+    fn if_else(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let line: usize = self.advance()?.line;
 
-            None => {}
+        if self.function == 0 {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("If statement outside of function. Use it in the body of a function."),
+                line,
+            ));
         }
 
-        self.end_function();
+        let condition: Instruction<'instr> = self.condition("if", line)?;
 
-        match &return_kind {
-            Some(kind) => {
-                self.define_global(name.lexeme.as_ref().unwrap(), kind.defer());
-            }
+        let then_block: Box<Instruction<'instr>> = Box::new(self.block()?);
 
-            None => {
-                self.define_global(name.lexeme.as_ref().unwrap(), DataTypes::Void);
+        let else_block: Option<Box<Instruction<'instr>>> = if self.match_token(TokenKind::Else)? {
+            match &self.peek().kind {
+                TokenKind::If => Some(Box::new(self.if_else()?)),
+                TokenKind::LBrace => Some(Box::new(self.block()?)),
+
+                _ => {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected '{' or 'if' after 'else'."),
+                        self.peek().line,
+                    ));
+                }
             }
+        } else {
+            None
+        };
+
+        Ok(Instruction::If {
+            condition: Box::new(condition),
+            then_block,
+            else_block,
+        })
+    }
+
+    fn while_loop(
+        &mut self,
+        label: Option<&'instr str>,
+    ) -> Result<Instruction<'instr>, ThrushError> {
+        let line: usize = self.advance()?.line;
+
+        if self.function == 0 {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("While loop outside of function. Use it in the body of a function."),
+                line,
+            ));
         }
 
-        Ok(Instruction::Function {
-            name: name.lexeme.as_ref().unwrap(),
-            params,
-            body,
-            return_kind,
-            is_public,
+        let condition: Instruction<'instr> = self.condition("while", line)?;
+
+        self.loops.push(label);
+        let body: Result<Instruction<'instr>, ThrushError> = self.block();
+        self.loops.pop();
+
+        Ok(Instruction::While {
+            condition: Box::new(condition),
+            body: Box::new(body?),
+            label,
         })
     }
 
-    fn print(&mut self) -> Result<Instruction<'instr>, ThrushError> {
-        self.only_advance()?;
+    /// 'loop' only ends with a 'break' or a 'return', it needs at least one of them.
+    fn infinite_loop(
+        &mut self,
+        label: Option<&'instr str>,
+    ) -> Result<Instruction<'instr>, ThrushError> {
+        let line: usize = self.advance()?.line;
+
+        if self.function == 0 {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Loop outside of function. Use it in the body of a function."),
+                line,
+            ));
+        }
+
+        if self.peek().kind != TokenKind::LBrace {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Expected '{' after 'loop'."),
+                self.peek().line,
+            ));
+        }
+
+        self.loops.push(label);
+        let body: Result<Instruction<'instr>, ThrushError> = self.block();
+        self.loops.pop();
+
+        let body: Instruction<'instr> = body?;
+
+        if !body.has_break() && !body.has_return() {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("The 'loop' never ends. Add a 'break' or a 'return' to its body."),
+                line,
+            ));
+        }
+
+        Ok(Instruction::Loop {
+            body: Box::new(body),
+            label,
+        })
+    }
+
+    /// `'outer: loop { ... }` names the loop, so a 'break' in a nested loop can leave it.
+    fn labeled_loop(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let label: &'instr Token = self.advance()?;
+        let name: &'instr str = label.lexeme.as_deref().unwrap();
 
         self.consume(
-            TokenKind::LParen,
+            TokenKind::Colon,
             ThrushErrorKind::SyntaxError,
             String::from("Syntax Error"),
-            String::from("Expected '('."),
+            String::from("Expected ':' after the label."),
         )?;
 
-        let mut args: Vec<Instruction<'instr>> = Vec::with_capacity(24);
-
-        while !self.match_token(TokenKind::RParen)? {
-            if self.match_token(TokenKind::Comma)? {
-                continue;
-            }
+        if self.loops.contains(&Some(name)) {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!(
+                    "The label '{}' is already used by an enclosing loop, give this one another name.",
+                    name
+                ),
+                label.line,
+            ));
+        }
 
-            if args.len() >= 24 {
-                return Err(ThrushError::Parse(
-                    ThrushErrorKind::TooManyArguments,
-                    String::from("Syntax Error"),
-                    String::from("Expected ')'. Too many arguments. Max is 24."),
-                    self.peek().line,
-                ));
-            }
+        match self.peek().kind {
+            TokenKind::While => self.while_loop(Some(name)),
+            TokenKind::Loop => self.infinite_loop(Some(name)),
 
-            args.push(self.expr()?);
+            _ => Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!(
+                    "Only a 'loop' or a 'while' can have a label. Like '{}: loop {{ ... }}'.",
+                    name
+                ),
+                label.line,
+            )),
         }
+    }
 
-        if args.is_empty() && self.match_token(TokenKind::SemiColon)? {
+    fn break_loop(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let line: usize = self.advance()?.line;
+
+        if self.loops.is_empty() {
             return Err(ThrushError::Parse(
                 ThrushErrorKind::SyntaxError,
                 String::from("Syntax Error"),
-                String::from(
-                    "Expected at least 1 argument for 'println' call. Like 'print(`Hi!`);'",
-                ),
-                self.peek().line,
+                String::from("'break' outside of a loop. Use it in a 'loop' or a 'while'."),
+                line,
             ));
-        } else if let Instruction::String(str) = &args[0] {
-            if args.len() <= 1 && C_FMTS.iter().any(|fmt| str.contains(*fmt)) {
-                self.consume(
-                    TokenKind::SemiColon,
-                    ThrushErrorKind::SyntaxError,
-                    String::from("Syntax Error"),
-                    String::from("Expected ';'."),
-                )?;
+        }
 
+        let label: Option<&'instr str> = if self.peek().kind == TokenKind::Label {
+            self.advance()?.lexeme.as_deref()
+        } else {
+            None
+        };
+
+        if let Some(label) = label {
+            if !self.loops.contains(&Some(label)) {
                 return Err(ThrushError::Parse(
                     ThrushErrorKind::SyntaxError,
                     String::from("Syntax Error"),
-                    String::from(
-                        "Expected at least 2 arguments for 'println' call. Like 'print(`%d`, 2);'",
+                    format!(
+                        "The label '{}' doesn't name a loop around this 'break'.",
+                        label
                     ),
-                    self.peek().line,
+                    line,
                 ));
             }
         }
@@ -673,29 +1356,107 @@ impl<'instr, 'a> Parser<'instr, 'a> {
             String::from("Expected ';'."),
         )?;
 
-        args.iter().try_for_each(|arg| match arg {
-            Instruction::String(str) => {
-                if str.contains("\n") {
-                    return Err(ThrushError::Parse(
-                        ThrushErrorKind::SyntaxError,
-                        String::from("Syntax Error"),
-                        String::from(
-                            "You can't print strings that contain newlines. Use 'println' instead.",
-                        ),
-                        self.peek().line,
-                    ));
-                }
+        Ok(Instruction::Break(label))
+    }
 
-                Ok(())
+    /// The condition of an 'if' or a 'while', followed by the '{' of its block.
+    fn condition(
+        &mut self,
+        keyword: &str,
+        line: usize,
+    ) -> Result<Instruction<'instr>, ThrushError> {
+        let condition: Instruction<'instr> = Self::concrete_arithmetic(self.expr()?);
+
+        match &condition {
+            Instruction::Boolean(_)
+            | Instruction::Integer(..)
+            | Instruction::Float(..)
+            | Instruction::BinaryOp { .. }
+            | Instruction::Unary { .. }
+            | Instruction::Compare { .. }
+            | Instruction::LogicalAnd { .. }
+            | Instruction::LogicalOr { .. } => {}
+
+            Instruction::RefVar { kind, .. } | Instruction::Call { kind, .. }
+                if *kind == DataTypes::Bool
+                    || VALID_INTEGER_TYPES.contains(kind)
+                    || VALID_FLOAT_TYPES.contains(kind) => {}
+
+            Instruction::Cast { .. } => {}
+
+            _ => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    format!(
+                        "The condition of '{}' must be a boolean or a number.",
+                        keyword
+                    ),
+                    line,
+                ));
             }
-            _ => Ok(()),
-        })?;
+        }
 
-        Ok(Instruction::Print(args))
+        if self.peek().kind != TokenKind::LBrace {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!("Expected '{{' after the condition of the '{}'.", keyword),
+                self.peek().line,
+            ));
+        }
+
+        Ok(condition)
     }
 
-    fn println(&mut self) -> Result<Instruction<'instr>, ThrushError> {
-        self.only_advance()?;
+    fn defer(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let line: usize = self.advance()?.line;
+
+        if self.function == 0 {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from(
+                    "Defer statement outside of function. Use it in the body of a function.",
+                ),
+                line,
+            ));
+        }
+
+        let stmt: Instruction<'instr> = self.statement()?;
+
+        // A deferred statement runs while the scope unwinds, so it can't leave it
+        // again or declare anything that would outlive it.
+        if matches!(
+            stmt,
+            Instruction::Return(_) | Instruction::Var { .. } | Instruction::Defer(_)
+        ) || stmt.has_return()
+            || stmt.has_break()
+        {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from(
+                    "A deferred statement can't be a 'return', 'let' or 'defer', nor contain a 'return' or a 'break'.",
+                ),
+                line,
+            ));
+        }
+
+        Ok(Instruction::Defer(Box::new(stmt)))
+    }
+
+    fn fence(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let line: usize = self.advance()?.line;
+
+        if self.function == 0 {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Fence outside of function. Use it in the body of a function."),
+                line,
+            ));
+        }
 
         self.consume(
             TokenKind::LParen,
@@ -704,204 +1465,1925 @@ impl<'instr, 'a> Parser<'instr, 'a> {
             String::from("Expected '('."),
         )?;
 
-        let mut args: Vec<Instruction<'instr>> = Vec::with_capacity(24);
+        let ordering: AtomicOrdering =
+            self.memory_ordering("fence", "fence(seq_cst);", &FENCE_ORDERINGS)?;
 
-        while !self.match_token(TokenKind::RParen)? {
-            if self.match_token(TokenKind::Comma)? {
-                continue;
-            }
+        self.consume(
+            TokenKind::RParen,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected ')'."),
+        )?;
 
-            if args.len() >= 24 {
-                return Err(ThrushError::Parse(
-                    ThrushErrorKind::TooManyArguments,
-                    String::from("Syntax Error"),
-                    String::from("Expected ')'. Too many arguments. Max is 24."),
-                    self.peek().line,
-                ));
-            }
+        self.consume(
+            TokenKind::SemiColon,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected ';'."),
+        )?;
 
-            let expr: Instruction<'_> = match self.expr()? {
-                Instruction::String(mut str) => {
-                    str.push('\n');
-                    Instruction::String(str)
-                }
-                expr => expr,
-            };
+        Ok(Instruction::Fence(ordering))
+    }
+
+    fn memory_ordering(
+        &mut self,
+        operation: &str,
+        example: &str,
+        orderings: &[(&str, AtomicOrdering)],
+    ) -> Result<AtomicOrdering, ThrushError> {
+        let ordering: &'instr Token = self.consume(
+            TokenKind::Identifier,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            format!("Expected the memory ordering. Like '{}'.", example),
+        )?;
+
+        let name: &str = ordering.lexeme.as_ref().unwrap();
 
-            args.push(expr);
+        if let Some((_, ordering)) = orderings.iter().find(|(known, _)| *known == name) {
+            return Ok(*ordering);
         }
 
-        if args.is_empty() && self.match_token(TokenKind::SemiColon)? {
+        let names: Vec<String> = orderings
+            .iter()
+            .map(|(name, _)| format!("'{}'", name))
+            .collect();
+
+        Err(ThrushError::Parse(
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            format!(
+                "Unknown {} ordering '{}'. Expected {} or {}.",
+                operation,
+                name,
+                names[..names.len() - 1].join(", "),
+                names[names.len() - 1]
+            ),
+            ordering.line,
+        ))
+    }
+
+    /// The variable of 'atomic_load' or 'atomic_store', LLVM only has atomic
+    /// accesses for numbers.
+    fn atomic_variable(&mut self, example: &str) -> Result<DataTypes, ThrushError> {
+        let name: &'instr Token = self.consume(
+            TokenKind::Identifier,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            format!("Expected a variable. Like '{}'.", example),
+        )?;
+
+        let kind: DataTypes = self.find_variable(name.lexeme.as_ref().unwrap());
+
+        if kind != DataTypes::Void && !kind.is_integer() && !kind.is_float() {
             return Err(ThrushError::Parse(
-                ThrushErrorKind::SyntaxError,
-                String::from("Syntax Error"),
-                String::from(
-                    "Expected at least 1 argument for 'println' call. Like 'println(`Hi!`);'",
+                ThrushErrorKind::TypeMismatch,
+                String::from("Type Mismatch"),
+                format!(
+                    "The variable `{}` of type '{}' can't be accessed atomically, only integers and floats can.",
+                    name.lexeme.as_ref().unwrap(),
+                    kind
                 ),
-                self.peek().line,
+                name.line,
+            ));
+        }
+
+        Ok(kind)
+    }
+
+    fn block(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        self.only_advance()?;
+
+        self.begin_scope();
+
+        let mut stmts: Vec<Instruction> = Vec::new();
+        let mut terminated: bool = false;
+
+        while !self.match_token(TokenKind::RBrace)? {
+            let line: usize = self.peek().line;
+            let stmt: Instruction<'instr> = self.statement()?;
+
+            // Dead statements are dropped so no unreachable IR is generated.
+            if terminated {
+                logging::log(
+                    logging::LogType::WARN,
+                    &format!(
+                        "Unreachable code at line {}, it comes after a 'return' or a 'break' and will be ignored.",
+                        line
+                    ),
+                );
+
+                continue;
+            }
+
+            terminated = stmt.terminates() || matches!(stmt, Instruction::Break(_));
+
+            stmts.push(stmt);
+        }
+
+        self.end_scope();
+
+        self.scoper.scope(stmts.clone());
+
+        Ok(Instruction::Block { stmts })
+    }
+
+    fn function(&mut self, is_public: bool) -> Result<Instruction<'instr>, ThrushError> {
+        self.only_advance()?;
+
+        if self.scope != 0 {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from(
+                    "The functions must go in the global scope. Rewrite it in the global scope.",
+                ),
+                self.previous().line,
             ));
-        } else if let Instruction::String(str) = &args[0] {
-            if args.len() <= 1 && C_FMTS.iter().any(|fmt| str.trim().contains(*fmt)) {
-                self.consume(
-                    TokenKind::SemiColon,
+        }
+
+        self.begin_function();
+
+        let visibility: Option<GlobalVisibility> = self.visibility.take();
+        let export: bool = self.export;
+        let naked: bool = self.naked;
+
+        self.export = false;
+        self.naked = false;
+
+        let name: &'instr Token = self.consume(
+            TokenKind::Identifier,
+            ThrushErrorKind::SyntaxError,
+            String::from("Expected function name"),
+            String::from("Expected def <name>."),
+        )?;
+
+        if name.lexeme.as_ref().unwrap() == "main" && self.options.unwrap().is_main {
+            if visibility.is_some() || export || naked {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    String::from("The entry point can't have attributes."),
+                    name.line,
+                ));
+            }
+
+            if self.has_entry_point {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Duplicated Entry Point"),
+                    String::from("The language not support two entry points, remove one."),
+                    name.line,
+                ));
+            }
+
+            self.consume(
+                TokenKind::LParen,
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Expected '('."),
+            )?;
+
+            self.consume(
+                TokenKind::RParen,
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Expected ')'."),
+            )?;
+
+            if self.peek().kind != TokenKind::LBrace {
+                return Err(ThrushError::Parse(
                     ThrushErrorKind::SyntaxError,
                     String::from("Syntax Error"),
-                    String::from("Expected ';'."),
-                )?;
+                    String::from("Expected '{'."),
+                    self.peek().line,
+                ));
+            }
+
+            if self.peek().kind == TokenKind::LBrace {
+                self.has_entry_point = true;
+
+                self.in_entry_point = true;
+                let body: Result<Instruction<'instr>, ThrushError> = self.block();
+                self.in_entry_point = false;
 
+                return Ok(Instruction::EntryPoint {
+                    body: Box::new(body?),
+                });
+            } else {
                 return Err(ThrushError::Parse(
                     ThrushErrorKind::SyntaxError,
                     String::from("Syntax Error"),
-                    String::from(
-                        "Expected at least 2 arguments for 'println' call. Like 'println(`%d`, 2);'",
-                    ),
+                    String::from("Expected 'block' for the function body."),
+                    self.peek().line,
+                ));
+            }
+        }
+
+        self.consume(
+            TokenKind::LParen,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected '('."),
+        )?;
+
+        let mut params: Vec<Instruction> = Vec::with_capacity(8);
+
+        while !self.match_token(TokenKind::RParen)? {
+            if self.match_token(TokenKind::Comma)? {
+                continue;
+            }
+
+            if params.len() >= 8 {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::TooManyArguments,
+                    String::from("Syntax Error"),
+                    String::from("Too many arguments for the function. The maximum number of arguments is 8."),
+                    self.peek().line,
+                ));
+            }
+
+            if !self.match_token(TokenKind::Identifier)? {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    String::from("Expected argument name."),
+                    self.peek().line,
+                ));
+            }
 
+            let ident: &str = self.previous().lexeme.as_ref().unwrap();
+
+            if !self.match_token(TokenKind::ColonColon)? {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    String::from("Expected '::'."),
                     self.peek().line,
                 ));
             }
+
+            let restrict: bool = self.match_token(TokenKind::Restrict)?;
+
+            let kind: DataTypes = match &self.peek().kind {
+                TokenKind::DataType(kind) => {
+                    self.only_advance()?;
+
+                    kind.defer()
+                }
+                _ => {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected argument type."),
+                        self.peek().line,
+                    ));
+                }
+            };
+
+            if restrict && kind != DataTypes::Ptr {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    format!(
+                        "Only arguments of type 'ptr' can be marked as 'restrict', found '{}'.",
+                        kind
+                    ),
+                    self.previous().line,
+                ));
+            }
+
+            params.push(Instruction::Param {
+                name: ident,
+                kind,
+                restrict,
+            })
+        }
+
+        if self.peek().kind == TokenKind::Colon {
+            self.consume(
+                TokenKind::Colon,
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Missing return type. Expected ':' followed by return type."),
+            )?;
+        }
+
+        let return_kind: Option<DataTypes> = match &self.peek().kind {
+            TokenKind::DataType(kind) => {
+                self.only_advance()?;
+                Some(kind.defer())
+            }
+            _ => None,
+        };
+
+        // The parameters live in a scope of their own, around the body.
+        self.begin_scope();
+
+        for param in params.iter() {
+            if let Instruction::Param {
+                name: param, kind, ..
+            } = param
+            {
+                self.define_local(param, kind.defer(), name.line)?;
+            }
+        }
+
+        let body: Box<Instruction> = Box::new(self.block()?);
+
+        self.end_scope();
+
+        // Without a prologue the body can't touch the stack, only assembly is safe there.
+        if naked {
+            if let Instruction::Block { stmts } = body.as_ref() {
+                if !stmts.iter().all(|stmt| {
+                    matches!(stmt, Instruction::InlineAsm { operands, .. } if operands.is_empty())
+                }) {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from(
+                            "A '@naked' function can only contain 'unsafe asm' statements without operands.",
+                        ),
+                        name.line,
+                    ));
+                }
+            }
+        }
+
+        match &return_kind {
+            // A naked function returns from its own assembly.
+            Some(kind) if !naked => {
+                if self.ret.is_none() {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        format!("Missing return statement with type '{}', you should add a return statement with type '{}'.", kind, kind),
+                        name.line,
+                    ));
+                }
+
+                match kind != self.ret.as_ref().unwrap() {
+                    true => {
+                        return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            format!(
+                                "Expected return type of '{}', found '{}'. You should write a return statement with type '{}'.",
+                                kind,
+                                self.ret.as_ref().unwrap(),
+                                kind
+                            ),
+                            name.line,
+                        ))
+                    }
+
+                    false => {}
+                }
+
+                // An 'if' without an 'else', or with a branch that falls through, can reach
+                // the end of the body without a value.
+                if !body.terminates() {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        format!(
+                            "Not every path of the function returns, you should add a return statement with type '{}' at the end.",
+                            kind
+                        ),
+                        name.line,
+                    ));
+                }
+            }
+
+            _ => {}
+        }
+
+        self.end_function();
+
+        match &return_kind {
+            Some(kind) => {
+                self.define_global(name.lexeme.as_ref().unwrap(), kind.defer());
+            }
+
+            None => {
+                self.define_global(name.lexeme.as_ref().unwrap(), DataTypes::Void);
+            }
+        }
+
+        Ok(Instruction::Function {
+            name: name.lexeme.as_ref().unwrap(),
+            params,
+            body,
+            return_kind,
+            is_public,
+            visibility,
+            export,
+            naked,
+        })
+    }
+
+    fn inline_asm(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let line: usize = self.advance()?.line;
+
+        self.consume(
+            TokenKind::Asm,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected 'asm' after 'unsafe', only inline assembly is unsafe."),
+        )?;
+
+        self.consume(
+            TokenKind::LParen,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected '('."),
+        )?;
+
+        let template: &'instr Token = self.consume(
+            TokenKind::String,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected the assembly template. Like 'unsafe asm(\"nop\");'."),
+        )?;
+
+        // Outside of a function the assembly goes to the top of the module, for
+        // symbols or sections defined by hand.
+        if self.function == 0 {
+            self.consume(
+                TokenKind::RParen,
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Expected ')'. Module assembly has no constraints or operands."),
+            )?;
+
+            self.consume(
+                TokenKind::SemiColon,
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Expected ';'."),
+            )?;
+
+            return Ok(Instruction::ModuleAsm(
+                template
+                    .lexeme
+                    .as_ref()
+                    .unwrap()
+                    .trim_end_matches('\0')
+                    .to_string(),
+            ));
+        }
+
+        let constraints: String = if self.match_token(TokenKind::Comma)? {
+            self.consume(
+                TokenKind::String,
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Expected the constraints of the assembly as a string."),
+            )?
+            .lexeme
+            .as_ref()
+            .unwrap()
+            .trim_end_matches('\0')
+            .to_string()
+        } else {
+            String::new()
+        };
+
+        let mut operands: Vec<Instruction<'instr>> = Vec::with_capacity(10);
+
+        while self.match_token(TokenKind::Comma)? {
+            let operand: Instruction<'instr> = Self::concrete_arithmetic(self.expr()?);
+
+            match &operand {
+                Instruction::Integer(..)
+                | Instruction::Float(..)
+                | Instruction::BinaryOp { .. } => {}
+
+                Instruction::RefVar { kind, .. }
+                    if VALID_INTEGER_TYPES.contains(kind) || VALID_FLOAT_TYPES.contains(kind) => {}
+
+                _ => {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("The operands of the assembly must be integers or floats."),
+                        line,
+                    ));
+                }
+            }
+
+            operands.push(operand);
+        }
+
+        self.consume(
+            TokenKind::RParen,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected ')'."),
+        )?;
+
+        self.consume(
+            TokenKind::SemiColon,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected ';'."),
+        )?;
+
+        if constraints.contains('=') {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from(
+                    "Output constraints are not supported, the assembly can only read its operands.",
+                ),
+                line,
+            ));
+        }
+
+        // Every constraint but the '~{..}' clobbers takes one operand.
+        let inputs: usize = constraints
+            .split(',')
+            .map(str::trim)
+            .filter(|constraint| !constraint.is_empty() && !constraint.starts_with('~'))
+            .count();
+
+        if inputs != operands.len() {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!(
+                    "The assembly has {} input constraints but {} operands.",
+                    inputs,
+                    operands.len()
+                ),
+                line,
+            ));
+        }
+
+        Ok(Instruction::InlineAsm {
+            template: template
+                .lexeme
+                .as_ref()
+                .unwrap()
+                .trim_end_matches('\0')
+                .to_string(),
+            constraints,
+            operands,
+            // Like GCC, assembly without outputs is implicitly volatile.
+            side_effects: true,
+        })
+    }
+
+    fn print(&mut self, stderr: bool) -> Result<Instruction<'instr>, ThrushError> {
+        self.only_advance()?;
+
+        self.consume(
+            TokenKind::LParen,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected '('."),
+        )?;
+
+        let mut args: Vec<Instruction<'instr>> = Vec::with_capacity(24);
+
+        while !self.match_token(TokenKind::RParen)? {
+            if self.match_token(TokenKind::Comma)? {
+                continue;
+            }
+
+            if args.len() >= 24 {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::TooManyArguments,
+                    String::from("Syntax Error"),
+                    String::from("Expected ')'. Too many arguments. Max is 24."),
+                    self.peek().line,
+                ));
+            }
+
+            args.push(Self::concrete_arithmetic(self.expr()?));
+        }
+
+        if args.is_empty() && self.match_token(TokenKind::SemiColon)? {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from(
+                    "Expected at least 1 argument for 'println' call. Like 'print(`Hi!`);'",
+                ),
+                self.peek().line,
+            ));
+        }
+
+        self.consume(
+            TokenKind::SemiColon,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected ';'."),
+        )?;
+
+        args.iter().try_for_each(|arg| match arg {
+            Instruction::String(str) => {
+                if str.contains("\n") {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from(
+                            "You can't print strings that contain newlines. Use 'println' instead.",
+                        ),
+                        self.peek().line,
+                    ));
+                }
+
+                Ok(())
+            }
+            _ => Ok(()),
+        })?;
+
+        if stderr {
+            return Ok(Instruction::EPrint(args));
+        }
+
+        Ok(Instruction::Print(args))
+    }
+
+    /// With `stderr` the arguments are written to the standard error, like 'eprintln'.
+    fn println(&mut self, stderr: bool) -> Result<Instruction<'instr>, ThrushError> {
+        self.only_advance()?;
+
+        self.consume(
+            TokenKind::LParen,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected '('."),
+        )?;
+
+        let mut args: Vec<Instruction<'instr>> = Vec::with_capacity(24);
+
+        while !self.match_token(TokenKind::RParen)? {
+            if self.match_token(TokenKind::Comma)? {
+                continue;
+            }
+
+            if args.len() >= 24 {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::TooManyArguments,
+                    String::from("Syntax Error"),
+                    String::from("Expected ')'. Too many arguments. Max is 24."),
+                    self.peek().line,
+                ));
+            }
+
+            args.push(Self::concrete_arithmetic(self.expr()?));
+        }
+
+        if args.is_empty() && self.match_token(TokenKind::SemiColon)? {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from(
+                    "Expected at least 1 argument for 'println' call. Like 'println(`Hi!`);'",
+                ),
+                self.peek().line,
+            ));
+        }
+
+        self.consume(
+            TokenKind::SemiColon,
+            ThrushErrorKind::SyntaxError,
+            String::from("Syntax Error"),
+            String::from("Expected ';'."),
+        )?;
+
+        if stderr {
+            return Ok(Instruction::EPrintln(args));
+        }
+
+        Ok(Instruction::Println(args))
+    }
+
+    fn expr(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        self.expression()
+    }
+
+    fn expression(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let expr: Instruction = self.or()?;
+
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let mut expr: Instruction = self.and()?;
+
+        while self.match_token(TokenKind::Or)? {
+            let op: &'instr Token = self.previous();
+            let right: Instruction = self.and()?;
+
+            self.check_logical_operand(&expr, op)?;
+            self.check_logical_operand(&right, op)?;
+
+            expr = Instruction::LogicalOr {
+                left: Box::new(expr),
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let mut expr: Instruction = self.comparison()?;
+
+        while self.match_token(TokenKind::And)? {
+            let op: &'instr Token = self.previous();
+            let right: Instruction = self.comparison()?;
+
+            self.check_logical_operand(&expr, op)?;
+            self.check_logical_operand(&right, op)?;
+
+            expr = Instruction::LogicalAnd {
+                left: Box::new(expr),
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    /// 'and', 'or' and '!' only take booleans, a number must be compared first.
+    fn check_logical_operand(
+        &self,
+        instr: &Instruction<'instr>,
+        op: &'instr Token,
+    ) -> Result<(), ThrushError> {
+        match instr {
+            Instruction::Boolean(_)
+            | Instruction::Compare { .. }
+            | Instruction::LogicalAnd { .. }
+            | Instruction::LogicalOr { .. } => Ok(()),
+
+            Instruction::RefVar { kind, .. }
+            | Instruction::Call { kind, .. }
+            | Instruction::Unary { kind, .. }
+                if *kind == DataTypes::Bool =>
+            {
+                Ok(())
+            }
+
+            _ => Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!(
+                    "The operands of '{}' must be booleans. Compare a number first, like 'x != 0'.",
+                    op.kind
+                ),
+                op.line,
+            )),
+        }
+    }
+
+    /// Comparisons don't chain, 'a < b < c' would compare a boolean with a number.
+    fn comparison(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let mut expr: Instruction = self.term()?;
+
+        if matches!(
+            self.peek().kind,
+            TokenKind::EqEq
+                | TokenKind::BangEqual
+                | TokenKind::Less
+                | TokenKind::LessEqual
+                | TokenKind::Greater
+                | TokenKind::GreaterEqual
+        ) {
+            let op: &'instr Token = self.advance()?;
+            let right: Instruction = self.term()?;
+
+            let kind: DataTypes = self.operands_kind(&expr, op, &right)?;
+            let kind: DataTypes = Self::resolve_arithmetic(&kind, &kind).unwrap();
+
+            Self::check_literal_range(&expr, &kind, op.line)?;
+            Self::check_literal_range(&right, &kind, op.line)?;
+
+            expr = Instruction::Compare {
+                left: Box::new(expr),
+                op: op.kind.clone(),
+                right: Box::new(right),
+                kind,
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let mut expr: Instruction = self.factor()?;
+
+        while matches!(self.peek().kind, TokenKind::Plus | TokenKind::Minus) {
+            let op: &'instr Token = self.advance()?;
+            let right: Instruction = self.factor()?;
+
+            expr = self.binary(expr, op, right)?;
+        }
+
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let mut expr: Instruction = self.cast()?;
+
+        while matches!(
+            self.peek().kind,
+            TokenKind::Star | TokenKind::Slash | TokenKind::Arith
+        ) {
+            let op: &'instr Token = self.advance()?;
+            let right: Instruction = self.cast()?;
+
+            expr = self.binary(expr, op, right)?;
+        }
+
+        Ok(expr)
+    }
+
+    /// 'as' converts between integer types and binds tighter than the arithmetic operators.
+    fn cast(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let mut expr: Instruction = self.unary()?;
+
+        while self.match_token(TokenKind::As)? {
+            match &expr {
+                Instruction::Integer(..) => {}
+
+                Instruction::RefVar { kind, .. }
+                | Instruction::Call { kind, .. }
+                | Instruction::BinaryOp { kind, .. }
+                | Instruction::Unary { kind, .. }
+                | Instruction::Cast { kind, .. }
+                | Instruction::Index { kind, .. }
+                    if VALID_INTEGER_TYPES.contains(kind)
+                        || matches!(kind, DataTypes::Integer | DataTypes::Ptr) => {}
+
+                _ => {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from(
+                            "Only integers and pointers can be converted with 'as'. Use 'bitcast' to reinterpret other numbers.",
+                        ),
+                        self.previous().line,
+                    ));
+                }
+            }
+
+            // An address goes through an integer, like 'p as u64' and 'n as ptr'.
+            let kind: DataTypes = match &self.peek().kind {
+                TokenKind::DataType(kind)
+                    if VALID_INTEGER_TYPES.contains(kind) || *kind == DataTypes::Ptr =>
+                {
+                    kind.defer()
+                }
+
+                _ => {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from(
+                            "Expected an integer type or 'ptr' after 'as'. Like 'n as u32'.",
+                        ),
+                        self.peek().line,
+                    ));
+                }
+            };
+
+            self.only_advance()?;
+
+            expr = Instruction::Cast {
+                value: Box::new(Self::concrete_arithmetic(expr)),
+                kind,
+            };
+        }
+
+        Ok(expr)
+    }
+
+    /// '-' negates a signed integer or a float, '!' inverts a boolean.
+    fn unary(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        if !matches!(self.peek().kind, TokenKind::Minus | TokenKind::Bang) {
+            return self.primary();
+        }
+
+        let op: &'instr Token = self.advance()?;
+        let operand: Instruction = self.unary()?;
+
+        let kind: DataTypes = if op.kind == TokenKind::Bang {
+            self.check_logical_operand(&operand, op)?;
+
+            DataTypes::Bool
+        } else {
+            self.arithmetic_kind(&operand, op)?
+        };
+
+        let unary: Instruction<'instr> = Instruction::Unary {
+            op: op.kind.clone(),
+            operand: Box::new(operand),
+            kind: kind.defer(),
+        };
+
+        Self::check_negation(&unary, &kind, op.line)?;
+
+        Ok(unary)
+    }
+
+    /// Unsigned integers have no negative values, so they can't be negated. A negated
+    /// literal gets its type where it is used, which is checked again there.
+    fn check_negation(
+        instr: &Instruction<'instr>,
+        kind: &DataTypes,
+        line: usize,
+    ) -> Result<(), ThrushError> {
+        match instr {
+            Instruction::Unary {
+                op: TokenKind::Minus,
+                ..
+            } if VALID_INTEGER_TYPES.contains(kind) && !kind.is_signed() => {
+                Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    format!(
+                        "Cannot negate a value of type '{}', it has no negative values. Convert it to a signed type with 'as' first.",
+                        kind
+                    ),
+                    line,
+                ))
+            }
+
+            Instruction::BinaryOp { left, right, .. } => {
+                Self::check_negation(left, kind, line)?;
+                Self::check_negation(right, kind, line)
+            }
+
+            _ => Ok(()),
+        }
+    }
+
+    /// A literal takes the type it is used as and would be truncated if it doesn't fit it,
+    /// a negated one may reach the minimum of a signed type.
+    fn check_literal_range(
+        instr: &Instruction<'instr>,
+        kind: &DataTypes,
+        line: usize,
+    ) -> Result<(), ThrushError> {
+        let literal: String = match instr {
+            Instruction::Unary {
+                op: TokenKind::Minus,
+                operand,
+                ..
+            } if kind.is_signed() => match &**operand {
+                Instruction::Integer(_, num) if *num > kind.max_integer() + 1 => {
+                    format!("-{}", num)
+                }
+                Instruction::Integer(..) => return Ok(()),
+
+                _ => return Self::check_literal_range(operand, kind, line),
+            },
+
+            Instruction::Unary { operand, .. } => {
+                return Self::check_literal_range(operand, kind, line)
+            }
+
+            Instruction::BinaryOp { left, right, .. } => {
+                Self::check_literal_range(left, kind, line)?;
+                return Self::check_literal_range(right, kind, line);
+            }
+
+            Instruction::Integer(_, num)
+                if VALID_INTEGER_TYPES.contains(kind) && *num > kind.max_integer() =>
+            {
+                num.to_string()
+            }
+            Instruction::Integer(_, num) if kind.is_float() && *num as f64 > kind.max_float() => {
+                num.to_string()
+            }
+            Instruction::Float(_, num) if kind.is_float() && num.abs() > kind.max_float() => {
+                num.to_string()
+            }
+
+            _ => return Ok(()),
+        };
+
+        let range: String = if kind.is_float() {
+            format!("-{0:e} to {0:e}", kind.max_float())
+        } else {
+            format!("{} to {}", kind.min_integer(), kind.max_integer())
+        };
+
+        Err(ThrushError::Parse(
+            ThrushErrorKind::UnreachableNumber,
+            String::from("The number is out of bounds."),
+            format!(
+                "The literal '{}' doesn't fit in '{}', its values range from {}.",
+                literal, kind, range
+            ),
+            line,
+        ))
+    }
+
+    fn binary(
+        &self,
+        left: Instruction<'instr>,
+        op: &'instr Token,
+        right: Instruction<'instr>,
+    ) -> Result<Instruction<'instr>, ThrushError> {
+        let kind: DataTypes = self.operands_kind(&left, op, &right)?;
+
+        Self::check_literal_range(&left, &kind, op.line)?;
+        Self::check_literal_range(&right, &kind, op.line)?;
+
+        Ok(Instruction::BinaryOp {
+            left: Box::new(left),
+            op: op.kind.clone(),
+            right: Box::new(right),
+            kind,
+        })
+    }
+
+    /// The type both sides of `op` share, a literal takes the type of the other side.
+    fn operands_kind(
+        &self,
+        left: &Instruction<'instr>,
+        op: &'instr Token,
+        right: &Instruction<'instr>,
+    ) -> Result<DataTypes, ThrushError> {
+        Ok(
+            match (
+                self.arithmetic_kind(left, op)?,
+                self.arithmetic_kind(right, op)?,
+            ) {
+                (lhs, rhs) if lhs == rhs => lhs,
+                (DataTypes::Integer, kind) | (kind, DataTypes::Integer)
+                    if VALID_INTEGER_TYPES.contains(&kind) =>
+                {
+                    kind
+                }
+                (DataTypes::Float, kind) | (kind, DataTypes::Float)
+                    if VALID_FLOAT_TYPES.contains(&kind) =>
+                {
+                    kind
+                }
+                (lhs, rhs) => {
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        format!(
+                        "Cannot apply '{}' to '{}' and '{}', both sides must have the same type.",
+                        op.kind, lhs, rhs
+                    ),
+                        op.line,
+                    ));
+                }
+            },
+        )
+    }
+
+    /// Literals are 'integer' or 'float' until the expression gets a type, the
+    /// lexer's sign on a literal after '-' is ignored since the operator subtracts.
+    fn arithmetic_kind(
+        &self,
+        instr: &Instruction<'instr>,
+        op: &'instr Token,
+    ) -> Result<DataTypes, ThrushError> {
+        match instr {
+            Instruction::Float(..) => Ok(DataTypes::Float),
+            Instruction::Integer(..) => Ok(DataTypes::Integer),
+            Instruction::RefVar { kind, .. }
+            | Instruction::BinaryOp { kind, .. }
+            | Instruction::Unary { kind, .. }
+            | Instruction::Call { kind, .. }
+            | Instruction::Cast { kind, .. }
+            | Instruction::Index { kind, .. }
+                if VALID_INTEGER_TYPES.contains(kind)
+                    || VALID_FLOAT_TYPES.contains(kind)
+                    || matches!(kind, DataTypes::Integer | DataTypes::Float) =>
+            {
+                Ok(kind.defer())
+            }
+
+            _ => Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!("The operands of '{}' must be numbers.", op.kind),
+                op.line,
+            )),
+        }
+    }
+
+    /// The type an arithmetic expression of type `found` gets when it is used
+    /// as `declared`, literal-only expressions default to 'i32' or 'f64'.
+    fn resolve_arithmetic(declared: &DataTypes, found: &DataTypes) -> Option<DataTypes> {
+        match (declared, found) {
+            (DataTypes::Integer, DataTypes::Integer) => Some(DataTypes::I32),
+            (DataTypes::Float, DataTypes::Float) => Some(DataTypes::F64),
+            (DataTypes::Integer, found) if VALID_INTEGER_TYPES.contains(found) => {
+                Some(found.defer())
+            }
+            (DataTypes::Float, found) if VALID_FLOAT_TYPES.contains(found) => Some(found.defer()),
+            (declared, DataTypes::Integer) if VALID_INTEGER_TYPES.contains(declared) => {
+                Some(declared.defer())
+            }
+            (declared, DataTypes::Float) if VALID_FLOAT_TYPES.contains(declared) => {
+                Some(declared.defer())
+            }
+            (declared, found) if declared == found => Some(declared.defer()),
+
+            _ => None,
+        }
+    }
+
+    fn concrete_arithmetic(mut instr: Instruction<'instr>) -> Instruction<'instr> {
+        if let Instruction::BinaryOp { kind, .. } | Instruction::Unary { kind, .. } = &mut instr {
+            *kind = Self::resolve_arithmetic(kind, kind).unwrap();
+        }
+
+        instr
+    }
+
+    fn primary(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let primary: Instruction = match &self.peek().kind {
+            TokenKind::String => {
+                Instruction::String(self.advance()?.lexeme.as_ref().unwrap().to_string())
+            }
+            kind => match kind {
+                TokenKind::Integer(kind, num) => {
+                    self.only_advance()?;
+
+                    Instruction::Integer(kind.defer(), *num)
+                }
+
+                TokenKind::Float(kind, num) => {
+                    self.only_advance()?;
+
+                    Instruction::Float(kind.defer(), *num)
+                }
+
+                TokenKind::Char(byte) => {
+                    self.only_advance()?;
+
+                    Instruction::Char(*byte)
+                }
+
+                TokenKind::Identifier => {
+                    self.only_advance()?;
+
+                    if self.peek().kind == TokenKind::LParen {
+                        return self.call();
+                    }
+
+                    let kind: DataTypes =
+                        self.find_variable(self.previous().lexeme.as_ref().unwrap());
+
+                    if self.peek().kind == TokenKind::LeftBracket {
+                        return self.index(kind);
+                    }
+
+                    if self.peek().kind == TokenKind::Eq {
+                        return self.assignment(kind);
+                    }
+
+                    Instruction::RefVar {
+                        name: self.previous().lexeme.as_ref().unwrap(),
+                        line: self.previous().line,
+                        kind,
+                        ordering: None,
+                    }
+                }
+
+                TokenKind::AtomicLoad => {
+                    self.only_advance()?;
+
+                    self.consume(
+                        TokenKind::LParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected '('."),
+                    )?;
+
+                    let kind: DataTypes = self.atomic_variable("atomic_load(n, seq_cst)")?;
+                    let name: &'instr Token = self.previous();
+
+                    self.consume(
+                        TokenKind::Comma,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ','."),
+                    )?;
+
+                    let ordering: AtomicOrdering =
+                        self.memory_ordering("load", "atomic_load(n, seq_cst)", &LOAD_ORDERINGS)?;
+
+                    self.consume(
+                        TokenKind::RParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ')'."),
+                    )?;
+
+                    Instruction::RefVar {
+                        name: name.lexeme.as_ref().unwrap(),
+                        line: name.line,
+                        kind,
+                        ordering: Some(ordering),
+                    }
+                }
+
+                // Checked like the assignment 'n = value', the ordering comes after the value.
+                TokenKind::AtomicStore => {
+                    self.only_advance()?;
+
+                    self.consume(
+                        TokenKind::LParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected '('."),
+                    )?;
+
+                    let kind: DataTypes = self.atomic_variable("atomic_store(n, 1, seq_cst)")?;
+
+                    if self.peek().kind != TokenKind::Comma {
+                        return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            String::from("Expected ','."),
+                            self.peek().line,
+                        ));
+                    }
+
+                    let mut store: Instruction<'instr> = self.assignment(kind)?;
+
+                    self.consume(
+                        TokenKind::Comma,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ','."),
+                    )?;
+
+                    let ordering: AtomicOrdering = self.memory_ordering(
+                        "store",
+                        "atomic_store(n, 1, seq_cst)",
+                        &STORE_ORDERINGS,
+                    )?;
+
+                    self.consume(
+                        TokenKind::RParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ')'."),
+                    )?;
+
+                    if let Instruction::MutVar {
+                        ordering: store_ordering,
+                        ..
+                    } = &mut store
+                    {
+                        *store_ordering = Some(ordering);
+                    }
+
+                    return Ok(store);
+                }
+
+                // Only this call is inlined, the other calls of the function are left to 'opt'.
+                TokenKind::Inline => {
+                    self.only_advance()?;
+
+                    self.consume(
+                        TokenKind::Identifier,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected a call after 'inline'. Like 'inline add(1, 2)'."),
+                    )?;
+
+                    if self.peek().kind != TokenKind::LParen {
+                        return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            String::from(
+                                "Expected a call after 'inline'. Like 'inline add(1, 2)'.",
+                            ),
+                            self.previous().line,
+                        ));
+                    }
+
+                    match self.call()? {
+                        Instruction::Call {
+                            name,
+                            args,
+                            kind,
+                            line,
+                            ..
+                        } => Instruction::Call {
+                            name,
+                            args,
+                            kind,
+                            line,
+                            inline: true,
+                        },
+
+                        _ => unreachable!(),
+                    }
+                }
+
+                TokenKind::Argc => {
+                    self.only_advance()?;
+                    self.check_entry_point_builtin()?;
+
+                    self.consume(
+                        TokenKind::LParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected '('."),
+                    )?;
+
+                    self.consume(
+                        TokenKind::RParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ')'."),
+                    )?;
+
+                    Instruction::ArgCount
+                }
+
+                TokenKind::Argv => {
+                    self.only_advance()?;
+                    self.check_entry_point_builtin()?;
+
+                    self.consume(
+                        TokenKind::LParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected '('."),
+                    )?;
+
+                    let index: Instruction<'instr> = Self::concrete_arithmetic(self.expr()?);
+
+                    match &index {
+                        Instruction::Integer(kind, _)
+                        | Instruction::RefVar { kind, .. }
+                        | Instruction::Call { kind, .. }
+                        | Instruction::BinaryOp { kind, .. }
+                        | Instruction::Unary { kind, .. }
+                        | Instruction::Cast { kind, .. }
+                        | Instruction::Index { kind, .. }
+                            if VALID_INTEGER_TYPES.contains(kind) => {}
+
+                        _ => {
+                            return Err(ThrushError::Parse(
+                                ThrushErrorKind::SyntaxError,
+                                String::from("Syntax Error"),
+                                String::from(
+                                    "Expected an integer index for 'argv'. Like 'argv(1)'.",
+                                ),
+                                self.previous().line,
+                            ));
+                        }
+                    }
+
+                    self.consume(
+                        TokenKind::RParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ')'."),
+                    )?;
+
+                    Instruction::ArgAt(Box::new(index))
+                }
+
+                TokenKind::GetEnv => {
+                    self.only_advance()?;
+
+                    self.consume(
+                        TokenKind::LParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected '('."),
+                    )?;
+
+                    let name: &'instr Token = self.consume(
+                        TokenKind::String,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from(
+                            "Expected the variable name as a string. Like 'getenv(\"PATH\")'.",
+                        ),
+                    )?;
+
+                    self.consume(
+                        TokenKind::RParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ')'."),
+                    )?;
+
+                    Instruction::GetEnv(name.lexeme.as_ref().unwrap().to_string())
+                }
+
+                TokenKind::ToString => {
+                    self.only_advance()?;
+
+                    self.consume(
+                        TokenKind::LParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected '('."),
+                    )?;
+
+                    let value: Instruction<'instr> = Self::concrete_arithmetic(self.expr()?);
+
+                    match &value {
+                        Instruction::Integer(kind, _)
+                        | Instruction::Float(kind, _)
+                        | Instruction::RefVar { kind, .. }
+                        | Instruction::BinaryOp { kind, .. }
+                        | Instruction::Unary { kind, .. }
+                        | Instruction::Cast { kind, .. }
+                            if VALID_INTEGER_TYPES.contains(kind)
+                                || VALID_FLOAT_TYPES.contains(kind) => {}
+
+                        _ => {
+                            return Err(ThrushError::Parse(
+                                ThrushErrorKind::SyntaxError,
+                                String::from("Syntax Error"),
+                                String::from(
+                                    "Expected a numeric value for 'to_string'. Like 'to_string(42)'.",
+                                ),
+                                self.previous().line,
+                            ));
+                        }
+                    }
+
+                    self.consume(
+                        TokenKind::RParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ')'."),
+                    )?;
+
+                    Instruction::ToString(Box::new(value))
+                }
+
+                TokenKind::BitCast => {
+                    self.only_advance()?;
+
+                    self.consume(
+                        TokenKind::LParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected '('."),
+                    )?;
+
+                    let value: Instruction<'instr> = self.expr()?;
+
+                    let from: DataTypes = match &value {
+                        Instruction::Integer(kind, _)
+                        | Instruction::Float(kind, _)
+                        | Instruction::RefVar { kind, .. }
+                            if VALID_INTEGER_TYPES.contains(kind)
+                                || VALID_FLOAT_TYPES.contains(kind) =>
+                        {
+                            kind.defer()
+                        }
+
+                        _ => {
+                            return Err(ThrushError::Parse(
+                                ThrushErrorKind::SyntaxError,
+                                String::from("Syntax Error"),
+                                String::from(
+                                    "Expected a numeric value for 'bitcast'. Like 'bitcast(1.5, u64)'.",
+                                ),
+                                self.previous().line,
+                            ));
+                        }
+                    };
+
+                    self.consume(
+                        TokenKind::Comma,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ','."),
+                    )?;
+
+                    let kind: DataTypes = match &self.peek().kind {
+                        TokenKind::DataType(kind)
+                            if VALID_INTEGER_TYPES.contains(kind)
+                                || VALID_FLOAT_TYPES.contains(kind) =>
+                        {
+                            kind.defer()
+                        }
+
+                        _ => {
+                            return Err(ThrushError::Parse(
+                                ThrushErrorKind::SyntaxError,
+                                String::from("Syntax Error"),
+                                String::from(
+                                    "Expected a numeric type to reinterpret the value as. Like 'bitcast(1.5, u64)'.",
+                                ),
+                                self.peek().line,
+                            ));
+                        }
+                    };
+
+                    self.only_advance()?;
+
+                    if from.bit_width() != kind.bit_width() {
+                        return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            format!(
+                                "Cannot reinterpret '{}' as '{}', the types have different sizes.",
+                                from, kind
+                            ),
+                            self.previous().line,
+                        ));
+                    }
+
+                    self.consume(
+                        TokenKind::RParen,
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected ')'."),
+                    )?;
+
+                    Instruction::BitCast {
+                        value: Box::new(value),
+                        kind,
+                    }
+                }
+
+                TokenKind::LeftBracket => self.array()?,
+
+                TokenKind::True => {
+                    self.only_advance()?;
+
+                    Instruction::Boolean(true)
+                }
+
+                TokenKind::False => {
+                    self.only_advance()?;
+
+                    Instruction::Boolean(false)
+                }
+
+                TokenKind::RParen | TokenKind::RBrace => {
+                    self.only_advance()?;
+
+                    return Err(ThrushError::Parse(
+                            ThrushErrorKind::SyntaxError,
+                            String::from("Syntax Error"),
+                            format!("Expected expression, found '{}'. Is this a function call or an function definition?", kind),
+                            self.peek().line,
+                        ));
+                }
+
+                kind => {
+                    self.only_advance()?;
+
+                    println!("{:?}", self.previous());
+
+                    return Err(ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        format!("Unexpected code '{}', check the code and review the syntax rules in the documentation.", kind),
+                        self.peek().line,
+                    ));
+                }
+            },
+        };
+
+        Ok(primary)
+    }
+
+    /// An array literal like '[1, 2, 3]', its elements are checked against the type of
+    /// the variable it initializes. Until then its literals are 'integer' or 'float'.
+    fn array(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let line: usize = self.advance()?.line;
+
+        let mut elements: Vec<Instruction<'instr>> = Vec::with_capacity(8);
+
+        while !self.match_token(TokenKind::RightBracket)? {
+            if self.match_token(TokenKind::Comma)? {
+                continue;
+            }
+
+            elements.push(self.expr()?);
+        }
+
+        let element: DataTypes = match elements.first() {
+            Some(Instruction::Integer(..)) => DataTypes::Integer,
+            Some(Instruction::Float(..)) => DataTypes::Float,
+            Some(Instruction::Char(_)) => DataTypes::Char,
+            Some(
+                Instruction::RefVar { kind, .. }
+                | Instruction::Call { kind, .. }
+                | Instruction::BinaryOp { kind, .. }
+                | Instruction::Unary { kind, .. }
+                | Instruction::Cast { kind, .. }
+                | Instruction::Index { kind, .. },
+            ) => kind.defer(),
+
+            Some(_) => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    String::from("The elements of an array must be numbers or characters."),
+                    line,
+                ));
+            }
+
+            None => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    String::from("An array needs at least one element."),
+                    line,
+                ));
+            }
+        };
+
+        Ok(Instruction::Array {
+            kind: DataTypes::Array(Box::new(element), elements.len() as u32),
+            elements,
+        })
+    }
+
+    /// An element of an array, like 'a[1]'. A constant index is checked against the
+    /// size of the array here, any other one when the program runs.
+    fn index(&mut self, kind: DataTypes) -> Result<Instruction<'instr>, ThrushError> {
+        let name: &'instr Token = self.previous();
+
+        self.only_advance()?;
+
+        let (element, size): (DataTypes, u32) = match kind {
+            DataTypes::Array(element, size) => (*element, size),
+
+            DataTypes::Void => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::VariableNotDefined,
+                    String::from("Undefined Variable"),
+                    format!(
+                        "The variable `{}` is not defined before this index.",
+                        name.lexeme.as_ref().unwrap()
+                    ),
+                    name.line,
+                ));
+            }
+
+            kind => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::TypeMismatch,
+                    String::from("Type Mismatch"),
+                    format!(
+                        "Only arrays can be indexed, but `{}` has type '{}'.",
+                        name.lexeme.as_ref().unwrap(),
+                        kind
+                    ),
+                    name.line,
+                ));
+            }
+        };
+
+        let index: Instruction<'instr> = Self::concrete_arithmetic(self.expr()?);
+
+        match &index {
+            Instruction::Integer(_, num) if *num >= size as u64 => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::UnreachableNumber,
+                    String::from("The index is out of bounds."),
+                    format!(
+                        "The index {} is out of bounds of `{}`, which has {} elements.",
+                        num,
+                        name.lexeme.as_ref().unwrap(),
+                        size
+                    ),
+                    name.line,
+                ));
+            }
+
+            Instruction::Integer(..) => {}
+
+            Instruction::RefVar { kind, .. }
+            | Instruction::Call { kind, .. }
+            | Instruction::BinaryOp { kind, .. }
+            | Instruction::Unary { kind, .. }
+            | Instruction::Cast { kind, .. }
+            | Instruction::Index { kind, .. }
+                if VALID_INTEGER_TYPES.contains(kind) => {}
+
+            _ => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    format!(
+                        "Expected an integer index for `{}`. Like '{}[1]'.",
+                        name.lexeme.as_ref().unwrap(),
+                        name.lexeme.as_ref().unwrap()
+                    ),
+                    name.line,
+                ));
+            }
         }
 
         self.consume(
-            TokenKind::SemiColon,
+            TokenKind::RightBracket,
             ThrushErrorKind::SyntaxError,
             String::from("Syntax Error"),
-            String::from("Expected ';'."),
+            String::from("Expected ']'."),
         )?;
 
-        Ok(Instruction::Println(args))
-    }
-
-    fn expr(&mut self) -> Result<Instruction<'instr>, ThrushError> {
-        self.expression()
+        Ok(Instruction::Index {
+            array: name.lexeme.as_ref().unwrap(),
+            index: Box::new(index),
+            kind: element,
+            line: name.line,
+        })
     }
 
-    fn expression(&mut self) -> Result<Instruction<'instr>, ThrushError> {
-        let expr: Instruction = self.primary()?;
+    /// The assigned value must have the type of the variable, its literals and
+    /// arithmetic adapt to it like in the declaration.
+    fn assignment(&mut self, kind: DataTypes) -> Result<Instruction<'instr>, ThrushError> {
+        let name: &'instr Token = self.previous();
 
-        Ok(expr)
-    }
+        self.only_advance()?;
 
-    fn primary(&mut self) -> Result<Instruction<'instr>, ThrushError> {
-        let primary: Instruction = match &self.peek().kind {
-            TokenKind::String => {
-                Instruction::String(self.advance()?.lexeme.as_ref().unwrap().to_string())
-            }
-            kind => match kind {
-                TokenKind::Integer(kind, num) => {
-                    self.only_advance()?;
+        if kind == DataTypes::Void {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::VariableNotDefined,
+                String::from("Undefined Variable"),
+                format!(
+                    "The variable `{}` is not defined before this assignment.",
+                    name.lexeme.as_ref().unwrap()
+                ),
+                name.line,
+            ));
+        }
 
-                    match kind {
-                        DataTypes::I8 => Instruction::Integer(DataTypes::I8, (*num as i8).into()),
-                        DataTypes::I16 => {
-                            Instruction::Integer(DataTypes::I16, (*num as i16).into())
-                        }
-                        DataTypes::I32 => {
-                            Instruction::Integer(DataTypes::I32, (*num as i32).into())
-                        }
-                        DataTypes::I64 => {
-                            Instruction::Integer(DataTypes::I64, (*num as i64) as f64)
-                        }
+        let mut value: Instruction<'instr> = self.expr()?;
 
-                        DataTypes::U8 => Instruction::Integer(DataTypes::U8, (*num as u8).into()),
-                        DataTypes::U16 => Instruction::Integer(DataTypes::U8, (*num as u16).into()),
-                        DataTypes::U32 => {
-                            Instruction::Integer(DataTypes::U32, (*num as u32).into())
-                        }
-                        DataTypes::U64 => {
-                            Instruction::Integer(DataTypes::U64, (*num as u64) as f64)
-                        }
+        if let Instruction::BinaryOp {
+            kind: data_type, ..
+        }
+        | Instruction::Unary {
+            kind: data_type, ..
+        } = &mut value
+        {
+            if let Some(resolved) = Self::resolve_arithmetic(&kind, data_type) {
+                *data_type = resolved;
+            }
 
-                        DataTypes::F32 => {
-                            Instruction::Integer(DataTypes::F32, (*num as f32).into())
-                        }
+            Self::check_negation(&value, &kind, name.line)?;
+        }
 
-                        DataTypes::F64 => Instruction::Integer(DataTypes::F64, *num),
+        Self::check_literal_range(&value, &kind, name.line)?;
 
-                        _ => unreachable!(),
-                    }
-                }
+        let found: DataTypes = match &value {
+            Instruction::Integer(..) if VALID_INTEGER_TYPES.contains(&kind) => kind.defer(),
+            Instruction::Float(..) if VALID_FLOAT_TYPES.contains(&kind) => kind.defer(),
 
-                TokenKind::Identifier => {
-                    self.only_advance()?;
+            Instruction::Integer(..)
+            | Instruction::Float(..)
+            | Instruction::Boolean(_)
+            | Instruction::RefVar { .. }
+            | Instruction::Call { .. }
+            | Instruction::BinaryOp { .. }
+            | Instruction::Unary { .. }
+            | Instruction::Cast { .. }
+            | Instruction::Index { .. } => value.get_data_type(),
 
-                    let kind: DataTypes =
-                        self.find_variable(self.previous().lexeme.as_ref().unwrap());
+            _ => {
+                return Err(ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    String::from("Only numbers and booleans can be assigned to a variable."),
+                    name.line,
+                ));
+            }
+        };
 
-                    if self.peek().kind == TokenKind::Eq {
-                        let name: &str = self.previous().lexeme.as_ref().unwrap();
-                        self.only_advance()?;
-
-                        let expr: Instruction<'instr> = self.expr()?;
-
-                        /* match kind.1 {
-                            Scope::Global => match self.globals.get(name) {
-                                None => {}
-                                Some(instr) => {
-                                    todo!()
-                                }
-                            },
-
-                            Scope::Local => match self.locals[self.scope].get(name) {
-                                None => {}
-                                Some(instr) => {
-                                    todo!()
-                                }
-                            },
-                        } */
-
-                        return Ok(Instruction::MutVar {
-                            name,
-                            value: Box::new(expr),
-                            kind,
-                        });
-                    }
+        if found != kind {
+            let error: ThrushError = ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!(
+                    "Variable type mismatch. Expected '{}' but found '{}'.",
+                    kind, found
+                ),
+                name.line,
+            );
 
-                    Instruction::RefVar {
-                        name: self.previous().lexeme.as_ref().unwrap(),
-                        line: self.previous().line,
-                        kind,
-                    }
-                }
+            return Err(match self.find_declaration(name.lexeme.as_ref().unwrap()) {
+                Some(line) => ThrushError::Related(
+                    Box::new(error),
+                    format!(
+                        "The variable `{}` is declared as '{}' here.",
+                        name.lexeme.as_ref().unwrap(),
+                        kind
+                    ),
+                    line,
+                ),
+                None => error,
+            });
+        }
 
-                TokenKind::True => {
-                    self.only_advance()?;
+        Ok(Instruction::MutVar {
+            name: name.lexeme.as_ref().unwrap(),
+            value: Box::new(value),
+            kind,
+            line: name.line,
+            ordering: None,
+        })
+    }
 
-                    Instruction::Boolean(true)
-                }
+    fn call(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let name: &'instr Token = self.previous();
 
-                TokenKind::False => {
-                    self.only_advance()?;
+        self.only_advance()?;
 
-                    Instruction::Boolean(false)
-                }
+        let mut args: Vec<Instruction<'instr>> = Vec::with_capacity(8);
 
-                TokenKind::RParen | TokenKind::RBrace => {
-                    self.only_advance()?;
+        while !self.match_token(TokenKind::RParen)? {
+            if self.match_token(TokenKind::Comma)? {
+                continue;
+            }
 
-                    return Err(ThrushError::Parse(
-                            ThrushErrorKind::SyntaxError,
-                            String::from("Syntax Error"),
-                            format!("Expected expression, found '{}'. Is this a function call or an function definition?", kind),
-                            self.peek().line,
-                        ));
-                }
+            let arg: Instruction<'instr> = Self::concrete_arithmetic(self.expr()?);
 
-                kind => {
-                    self.only_advance()?;
+            match &arg {
+                Instruction::Integer(..)
+                | Instruction::Float(..)
+                | Instruction::BinaryOp { .. }
+                | Instruction::Unary { .. }
+                | Instruction::Cast { .. }
+                | Instruction::Boolean(_)
+                | Instruction::String(_) => {}
 
-                    println!("{:?}", self.previous());
+                Instruction::RefVar { kind, .. }
+                | Instruction::Call { kind, .. }
+                | Instruction::Index { kind, .. }
+                    if *kind == DataTypes::Bool
+                        || VALID_INTEGER_TYPES.contains(kind)
+                        || VALID_FLOAT_TYPES.contains(kind) => {}
 
+                _ => {
                     return Err(ThrushError::Parse(
                         ThrushErrorKind::SyntaxError,
                         String::from("Syntax Error"),
-                        format!("Unexpected code '{}', check the code and review the syntax rules in the documentation.", kind),
-                        self.peek().line,
+                        format!(
+                            "The arguments of '{}' must be numbers, booleans or strings.",
+                            name.lexeme.as_ref().unwrap()
+                        ),
+                        name.line,
                     ));
                 }
-            },
-        };
+            }
 
-        Ok(primary)
+            args.push(arg);
+        }
+
+        Ok(Instruction::Call {
+            name: name.lexeme.as_ref().unwrap(),
+            args,
+            kind: self.find_variable(name.lexeme.as_ref().unwrap()),
+            line: name.line,
+            inline: false,
+        })
+    }
+
+    /// A call or an assignment on its own is a statement, it ends with ';'.
+    fn statement(&mut self) -> Result<Instruction<'instr>, ThrushError> {
+        let stmt: Instruction<'instr> = self.parse()?;
+
+        if matches!(stmt, Instruction::Call { .. } | Instruction::MutVar { .. }) {
+            self.consume(
+                TokenKind::SemiColon,
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                String::from("Expected ';'."),
+            )?;
+        }
+
+        Ok(stmt)
+    }
+
+    fn check_entry_point_builtin(&self) -> Result<(), ThrushError> {
+        if !self.in_entry_point {
+            return Err(ThrushError::Parse(
+                ThrushErrorKind::SyntaxError,
+                String::from("Syntax Error"),
+                format!(
+                    "'{}' is only available inside the entry point.",
+                    self.previous().kind
+                ),
+                self.previous().line,
+            ));
+        }
+
+        Ok(())
     }
 
     fn consume(
@@ -917,18 +3399,21 @@ impl<'instr, 'a> Parser<'instr, 'a> {
 
         println!("{:?}", self.peek());
 
-        Err(ThrushError::Parse(
-            error_kind,
-            error_title,
-            help,
-            self.peek().line,
+        Err(ThrushError::Located(
+            Box::new(ThrushError::Parse(
+                error_kind,
+                error_title,
+                help,
+                self.peek().line,
+            )),
+            self.peek().span.start,
         ))
     }
 
     fn find_variable(&self, name: &str) -> DataTypes {
-        for index in (0..self.scope).rev() {
+        for index in (0..=self.scope).rev() {
             if self.locals[index].contains_key(name) {
-                return self.locals[index].get(name).unwrap().defer();
+                return self.locals[index].get(name).unwrap().0.defer();
             }
         }
 
@@ -943,8 +3428,41 @@ impl<'instr, 'a> Parser<'instr, 'a> {
         self.globals.insert(name, kind);
     }
 
-    fn define_local(&mut self, name: &'instr str, kind: DataTypes) {
-        self.locals[self.scope].insert(name, kind);
+    /// A name can be defined once per scope, an inner scope can still shadow it.
+    fn define_local(
+        &mut self,
+        name: &'instr str,
+        kind: DataTypes,
+        line: usize,
+    ) -> Result<(), ThrushError> {
+        if let Some((_, defined)) = self.locals[self.scope].get(name) {
+            return Err(ThrushError::Related(
+                Box::new(ThrushError::Parse(
+                    ThrushErrorKind::Redefinition,
+                    String::from("Redefined Variable"),
+                    format!(
+                        "The variable `{}` is already defined in this scope, rename it or assign to it.",
+                        name
+                    ),
+                    line,
+                )),
+                format!("The variable `{}` is first defined here.", name),
+                *defined,
+            ));
+        }
+
+        self.locals[self.scope].insert(name, (kind, line));
+
+        Ok(())
+    }
+
+    /// The line where the variable visible from the current scope is declared.
+    fn find_declaration(&self, name: &str) -> Option<usize> {
+        self.locals[..=self.scope]
+            .iter()
+            .rev()
+            .find_map(|locals| locals.get(name))
+            .map(|(_, line)| *line)
     }
 
     fn begin_scope(&mut self) {
@@ -1027,6 +3545,10 @@ impl PartialEq for Instruction<'_> {
                 matches!(other, Instruction::Integer(_, _))
             }
 
+            Instruction::Float(_, _) => {
+                matches!(other, Instruction::Float(_, _))
+            }
+
             Instruction::String(_) => {
                 matches!(other, Instruction::String(_))
             }
@@ -1037,27 +3559,96 @@ impl PartialEq for Instruction<'_> {
 }
 
 impl<'instr> Instruction<'instr> {
-    pub fn get_data_type(&self) -> DataTypes {
+    /// The name of the variant, for the errors about a construct the backend can't lower.
+    pub fn variant_name(&self) -> &'static str {
         match self {
-            Instruction::Integer(data_type, _) => match data_type {
-                DataTypes::U8 => DataTypes::U8,
-                DataTypes::U16 => DataTypes::U16,
-                DataTypes::U32 => DataTypes::U32,
-                DataTypes::U64 => DataTypes::U64,
-
-                DataTypes::I8 => DataTypes::I8,
-                DataTypes::I16 => DataTypes::I16,
-                DataTypes::I32 => DataTypes::I32,
-                DataTypes::I64 => DataTypes::I64,
-
-                DataTypes::F32 => DataTypes::F32,
-                DataTypes::F64 => DataTypes::F64,
+            Instruction::Println(..) => "Println",
+            Instruction::Print(..) => "Print",
+            Instruction::EPrintln(..) => "EPrintln",
+            Instruction::EPrint(..) => "EPrint",
+            Instruction::String(..) => "String",
+            Instruction::Integer(..) => "Integer",
+            Instruction::Float(..) => "Float",
+            Instruction::Char(..) => "Char",
+            Instruction::Block { .. } => "Block",
+            Instruction::EntryPoint { .. } => "EntryPoint",
+            Instruction::Value(..) => "Value",
+            Instruction::Local(..) => "Local",
+            Instruction::Param { .. } => "Param",
+            Instruction::Function { .. } => "Function",
+            Instruction::Return(..) => "Return",
+            Instruction::Var { .. } => "Var",
+            Instruction::RefVar { .. } => "RefVar",
+            Instruction::MutVar { .. } => "MutVar",
+            Instruction::Boolean(..) => "Boolean",
+            Instruction::Call { .. } => "Call",
+            Instruction::ArgCount => "ArgCount",
+            Instruction::ArgAt(..) => "ArgAt",
+            Instruction::GetEnv(..) => "GetEnv",
+            Instruction::ToString(..) => "ToString",
+            Instruction::BitCast { .. } => "BitCast",
+            Instruction::Cast { .. } => "Cast",
+            Instruction::Defer(..) => "Defer",
+            Instruction::If { .. } => "If",
+            Instruction::While { .. } => "While",
+            Instruction::Loop { .. } => "Loop",
+            Instruction::Break(..) => "Break",
+            Instruction::Fence(..) => "Fence",
+            Instruction::ModuleAsm(..) => "ModuleAsm",
+            Instruction::InlineAsm { .. } => "InlineAsm",
+            Instruction::BinaryOp { .. } => "BinaryOp",
+            Instruction::Unary { .. } => "Unary",
+            Instruction::Compare { .. } => "Compare",
+            Instruction::LogicalAnd { .. } => "LogicalAnd",
+            Instruction::LogicalOr { .. } => "LogicalOr",
+            Instruction::ConstArray { .. } => "ConstArray",
+            Instruction::Array { .. } => "Array",
+            Instruction::Index { .. } => "Index",
+            Instruction::Null => "Null",
+        }
+    }
 
-                _ => unreachable!(),
-            },
+    pub fn get_data_type(&self) -> DataTypes {
+        match self {
+            Instruction::Integer(data_type, _) | Instruction::Float(data_type, _) => {
+                match data_type {
+                    DataTypes::U8 => DataTypes::U8,
+                    DataTypes::U16 => DataTypes::U16,
+                    DataTypes::U32 => DataTypes::U32,
+                    DataTypes::U64 => DataTypes::U64,
+
+                    DataTypes::I8 => DataTypes::I8,
+                    DataTypes::I16 => DataTypes::I16,
+                    DataTypes::I32 => DataTypes::I32,
+                    DataTypes::I64 => DataTypes::I64,
+
+                    DataTypes::F16 => DataTypes::F16,
+                    DataTypes::BF16 => DataTypes::BF16,
+                    DataTypes::F32 => DataTypes::F32,
+                    DataTypes::F64 => DataTypes::F64,
+
+                    _ => unreachable!(),
+                }
+            }
 
             Instruction::String(_) => DataTypes::String,
             Instruction::Boolean(_) => DataTypes::Bool,
+            Instruction::Char(_) => DataTypes::Char,
+            Instruction::Print(_)
+            | Instruction::Println(_)
+            | Instruction::EPrint(_)
+            | Instruction::EPrintln(_) => DataTypes::I32,
+            Instruction::RefVar { kind, .. } => kind.defer(),
+            Instruction::Call { kind, .. } => kind.defer(),
+            Instruction::Block { stmts } => stmts.last().unwrap().get_data_type(),
+            Instruction::BinaryOp { kind, .. } => kind.defer(),
+            Instruction::Unary { kind, .. } => kind.defer(),
+            Instruction::Compare { .. }
+            | Instruction::LogicalAnd { .. }
+            | Instruction::LogicalOr { .. } => DataTypes::Bool,
+            Instruction::Cast { kind, .. } => kind.defer(),
+            Instruction::Array { kind, .. } => kind.defer(),
+            Instruction::Index { kind, .. } => kind.defer(),
 
             _ => unreachable!(),
         }
@@ -1069,6 +3660,65 @@ impl<'instr> Instruction<'instr> {
             _ => None,
         }
     }
+
+    pub fn has_return(&self) -> bool {
+        match self {
+            Instruction::Return(_) => true,
+            Instruction::Block { stmts } => stmts.iter().any(|stmt| stmt.has_return()),
+            Instruction::If {
+                then_block,
+                else_block,
+                ..
+            } => then_block.has_return() || else_block.as_ref().is_some_and(|els| els.has_return()),
+            Instruction::While { body, .. } | Instruction::Loop { body, .. } => body.has_return(),
+            _ => false,
+        }
+    }
+
+    /// Whether a 'break' leaves the enclosing loop, one inside a nested loop only leaves that one.
+    pub fn has_break(&self) -> bool {
+        self.breaks_out(&[])
+    }
+
+    /// `nested` are the labels of the loops between the statement and the enclosing loop, a
+    /// labeled 'break' leaves it unless it names one of them.
+    fn breaks_out(&self, nested: &[Option<&str>]) -> bool {
+        match self {
+            Instruction::Break(None) => nested.is_empty(),
+            Instruction::Break(label) => !nested.contains(label),
+            Instruction::Block { stmts } => stmts.iter().any(|stmt| stmt.breaks_out(nested)),
+            Instruction::If {
+                then_block,
+                else_block,
+                ..
+            } => {
+                then_block.breaks_out(nested)
+                    || else_block
+                        .as_ref()
+                        .is_some_and(|els| els.breaks_out(nested))
+            }
+            Instruction::While { body, label, .. } | Instruction::Loop { body, label } => {
+                body.breaks_out(&[nested, &[*label]].concat())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether every path through the statement ends in a 'return'.
+    pub fn terminates(&self) -> bool {
+        match self {
+            Instruction::Return(_) => true,
+            Instruction::Block { stmts } => stmts.iter().any(|stmt| stmt.terminates()),
+            Instruction::If {
+                then_block,
+                else_block,
+                ..
+            } => then_block.terminates() && else_block.as_ref().is_some_and(|els| els.terminates()),
+            // Without a 'break' the loop is only left by a 'return'.
+            Instruction::Loop { body, .. } => !body.has_break(),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -1096,21 +3746,21 @@ impl<'ctx> ThrushScoper<'ctx> {
         self.blocks.push(ThrushBlock { stmts });
     }
 
-    pub fn analyze(&mut self) -> Result<(), String> {
+    pub fn analyze(&mut self, max_errors: usize) -> Result<(), String> {
         if self.blocks.is_empty() {
             return Ok(());
         }
 
-        for index in (0..=self.blocks.len() - 1).rev() {
+        'blocks: for index in (0..=self.blocks.len() - 1).rev() {
             for instr in self.blocks[index].stmts.iter().rev() {
                 match self.analyze_instruction(instr, index) {
                     Ok(()) => {}
                     Err(e) => {
-                        if self.errors.len() >= 10 {
-                            break;
-                        }
-
                         self.errors.push(e);
+
+                        if self.errors.len() >= max_errors {
+                            break 'blocks;
+                        }
                     }
                 }
             }
@@ -1186,7 +3836,7 @@ impl<'ctx> ThrushScoper<'ctx> {
                 Ok(())
             }
 
-            Instruction::Println(params) => {
+            Instruction::Println(params) | Instruction::EPrintln(params) => {
                 params.iter().try_for_each(|instr| {
                     match self.analyze_instruction(instr, index) {
                         Ok(()) => Ok(()),
@@ -1197,7 +3847,7 @@ impl<'ctx> ThrushScoper<'ctx> {
                 Ok(())
             }
 
-            Instruction::Print(params) => {
+            Instruction::Print(params) | Instruction::EPrint(params) => {
                 params.iter().try_for_each(|instr| {
                     match self.analyze_instruction(instr, index) {
                         Ok(()) => Ok(()),
@@ -1219,6 +3869,72 @@ impl<'ctx> ThrushScoper<'ctx> {
                 Ok(())
             }
 
+            Instruction::ArgAt(arg) => self.analyze_instruction(arg, index),
+
+            Instruction::ToString(value) => self.analyze_instruction(value, index),
+
+            Instruction::BitCast { value, .. } => self.analyze_instruction(value, index),
+
+            Instruction::Cast { value, .. } => self.analyze_instruction(value, index),
+
+            Instruction::Unary { operand, .. } => self.analyze_instruction(operand, index),
+
+            Instruction::MutVar { value, .. } => self.analyze_instruction(value, index),
+
+            Instruction::Defer(stmt) => self.analyze_instruction(stmt, index),
+
+            Instruction::If {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                self.analyze_instruction(condition, index)?;
+                self.analyze_instruction(then_block, index)?;
+
+                match else_block {
+                    Some(else_block) => self.analyze_instruction(else_block, index),
+                    None => Ok(()),
+                }
+            }
+
+            Instruction::While {
+                condition, body, ..
+            } => {
+                self.analyze_instruction(condition, index)?;
+                self.analyze_instruction(body, index)
+            }
+
+            Instruction::Loop { body, .. } => self.analyze_instruction(body, index),
+
+            Instruction::Call { args, .. } => {
+                args.iter()
+                    .try_for_each(|instr| match self.analyze_instruction(instr, index) {
+                        Ok(()) => Ok(()),
+                        Err(e) => Err(e),
+                    })?;
+
+                Ok(())
+            }
+
+            Instruction::InlineAsm { operands, .. } => {
+                operands.iter().try_for_each(|instr| {
+                    match self.analyze_instruction(instr, index) {
+                        Ok(()) => Ok(()),
+                        Err(e) => Err(e),
+                    }
+                })?;
+
+                Ok(())
+            }
+
+            Instruction::BinaryOp { left, right, .. }
+            | Instruction::Compare { left, right, .. }
+            | Instruction::LogicalAnd { left, right }
+            | Instruction::LogicalOr { left, right } => {
+                self.analyze_instruction(left, index)?;
+                self.analyze_instruction(right, index)
+            }
+
             _ => Ok(()),
         }
     }
@@ -1230,7 +3946,7 @@ impl<'ctx> ThrushScoper<'ctx> {
         block: Option<&Instruction<'ctx>>,
         mut depth: usize,
     ) -> bool {
-        if depth > self.blocks.len() {
+        if depth >= self.blocks.len() {
             return false;
         }
 
@@ -1302,7 +4018,7 @@ impl<'ctx> ThrushScoper<'ctx> {
         block: Option<&Instruction<'ctx>>,
         mut depth: usize,
     ) -> bool {
-        if depth > self.blocks.len() {
+        if depth >= self.blocks.len() {
             return false;
         }
 
@@ -1344,3 +4060,178 @@ impl<'ctx> ThrushScoper<'ctx> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            super::{
+                super::backend::compiler::tests::{source_file, SourceFile},
+                lexer::Lexer,
+            },
+            *,
+        },
+        std::mem,
+    };
+
+    /// The errors of the parser and the scoper for `source`.
+    fn parse_errors(name: &str, source: &str) -> Vec<ThrushError> {
+        parse_errors_with(name, source, Options::default())
+    }
+
+    fn parse_errors_with(name: &str, source: &str, options: Options) -> Vec<ThrushError> {
+        let _source: SourceFile = source_file(name, source);
+
+        let mut lexer: Lexer = Lexer::new(source.as_bytes());
+        let tokens: &[Token] = lexer.lex(options.max_errors()).unwrap();
+
+        let mut parser: Parser = Parser::new();
+
+        parser.tokens = Some(tokens);
+        parser.options = Some(&options);
+
+        let _ = parser.start();
+
+        mem::take(&mut parser.errors)
+    }
+
+    #[test]
+    fn an_unsigned_value_cant_be_negated() {
+        let errors: Vec<ThrushError> = parse_errors(
+            "negate-unsigned",
+            "fn main() {\n    let x = 5;\n    x = 6;\n    let y = -x;\n}",
+        );
+
+        match errors.first() {
+            Some(ThrushError::Parse(ThrushErrorKind::SyntaxError, _, help, 4)) => {
+                assert!(
+                    help.starts_with("Cannot negate a value of type 'u8'"),
+                    "{}",
+                    help
+                );
+            }
+            _ => panic!("{:?}", errors),
+        }
+    }
+
+    #[test]
+    fn a_signed_value_can_be_negated() {
+        assert!(parse_errors(
+            "negate-signed",
+            "fn main() {\n    let x i32 = -5;\n    x = -6;\n    let y = -x;\n}\n",
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn a_parameter_and_a_comparison_can_be_returned() {
+        assert!(parse_errors(
+            "return-param",
+            "fn id(n :: i32) i32 {\n    return n;\n}\n\nfn less(a :: i32, b :: i32) bool {\n    return a < b;\n}",
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn a_constant_index_past_the_end_is_rejected() {
+        let errors: Vec<ThrushError> = parse_errors(
+            "array-out-of-bounds",
+            "fn main() {\n    let a i32[3] = [1, 2, 3];\n    println(a[3]);\n}",
+        );
+
+        match errors.first() {
+            Some(ThrushError::Parse(ThrushErrorKind::UnreachableNumber, _, help, 3)) => {
+                assert_eq!(
+                    help,
+                    "The index 3 is out of bounds of `a`, which has 3 elements."
+                );
+            }
+            _ => panic!("{:?}", errors),
+        }
+    }
+
+    #[test]
+    fn a_literal_out_of_the_range_of_its_type_is_rejected() {
+        assert!(parse_errors(
+            "literal-in-range",
+            "fn main() {\n    let x i8 = -128;\n    let y i8 = 127;\n}",
+        )
+        .is_empty());
+
+        for literal in ["128", "-129"] {
+            let errors: Vec<ThrushError> = parse_errors(
+                "literal-out-of-range",
+                &format!("fn main() {{\n    let x i8 = {};\n}}", literal),
+            );
+
+            match errors.first() {
+                Some(ThrushError::Parse(ThrushErrorKind::UnreachableNumber, _, help, 2)) => {
+                    assert_eq!(
+                        help,
+                        &format!(
+                            "The literal '{}' doesn't fit in 'i8', its values range from -128 to 127.",
+                            literal
+                        )
+                    );
+                }
+                _ => panic!("{:?}", errors),
+            }
+        }
+    }
+
+    #[test]
+    fn fail_fast_stops_at_the_first_error() {
+        let source: &str = "fn a() {\n    let x i8 = 300;\n}\n\nfn b() {\n    let y i8 = 400;\n}\n\nfn main() {\n    let z i8 = 500;\n}";
+
+        let all: Vec<ThrushError> = parse_errors("collect-all", source);
+        let first: Vec<ThrushError> = parse_errors_with(
+            "fail-fast",
+            source,
+            Options {
+                fail_fast: true,
+                ..Options::default()
+            },
+        );
+
+        let lines: Vec<usize> = all
+            .iter()
+            .filter_map(|error| match error {
+                ThrushError::Parse(ThrushErrorKind::UnreachableNumber, _, _, line) => Some(*line),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(lines, [2, 6, 10], "{:?}", all);
+
+        match first.as_slice() {
+            [ThrushError::Parse(ThrushErrorKind::UnreachableNumber, _, _, 2)] => {}
+            _ => panic!("{:?}", first),
+        }
+    }
+
+    #[test]
+    fn a_redefinition_points_at_both_definitions() {
+        let errors: Vec<ThrushError> = parse_errors(
+            "redefinition",
+            "fn main() {\n    let x = 1;\n    println(x);\n    let x = 2;\n}",
+        );
+
+        match errors.first() {
+            Some(ThrushError::Related(error, note, 2)) => {
+                assert_eq!(note, "The variable `x` is first defined here.");
+
+                match &**error {
+                    ThrushError::Parse(ThrushErrorKind::Redefinition, _, help, 4) => {
+                        assert!(
+                            help.starts_with("The variable `x` is already defined"),
+                            "{}",
+                            help
+                        )
+                    }
+                    error => panic!("{:?}", error),
+                }
+            }
+            _ => panic!("{:?}", errors),
+        }
+    }
+}
@@ -21,3 +21,15 @@ impl LogType {
 pub fn log(ltype: LogType, msg: &str) {
     println!("{} {}", ltype.to_str().bold().bright_red(), msg.bold());
 }
+
+/// Logs an internal compiler error with the location in the compiler source where it was raised,
+/// use it with `file!()` and `line!()`.
+#[inline]
+pub fn log_internal(msg: &str, file: &str, line: u32) {
+    println!(
+        "{} {} {}",
+        "INTERNAL ERROR".bold().bright_red(),
+        msg.bold(),
+        format!("({}:{})", file, line).bright_black()
+    );
+}
@@ -5,31 +5,62 @@ mod frontend;
 mod logging;
 
 use {
-    backend::compiler::{Compiler, FileBuilder, Instruction, Linking, Opt, Options},
+    backend::{
+        cache::BuildCache,
+        compiler::{
+            find_clang, Abort, Compiler, FileBuilder, FloatFormat, Instruction, Linking, Opt,
+            Options,
+        },
+        target::TargetSpec,
+    },
     colored::{Colorize, CustomColor},
+    diagnostic::Diagnostic,
+    error::{ThrushError, ThrushErrorKind},
     frontend::{
-        lexer::{Lexer, Token},
+        lexer::{Lexer, Token, TokenKind, TokenSpan},
         parser::Parser,
     },
     inkwell::{
+        attributes::{Attribute, AttributeLoc},
+        basic_block::BasicBlock,
         builder::Builder,
         context::Context,
+        execution_engine::ExecutionEngine,
         module::Module,
         targets::{
-            CodeModel, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple,
+            CodeModel, InitializationConfig, RelocMode, Target, TargetData, TargetMachine,
+            TargetTriple,
         },
+        values::InstructionValue,
         OptimizationLevel,
     },
-    std::{env, fs::read_to_string, path::Path, sync::Mutex},
+    llvm_sys::core::LLVMGetVersion,
+    std::{
+        env,
+        fs::{read_to_string, write},
+        mem,
+        path::{Path, PathBuf},
+        process::{self, Command},
+        str::SplitWhitespace,
+        sync::Mutex,
+        time::{Duration, Instant},
+    },
 };
 
 pub static NAME: Mutex<String> = Mutex::new(String::new());
 pub static PATH: Mutex<String> = Mutex::new(String::new());
 
+/// The exit status when the source code has errors. A successful run exits with 0,
+/// and a panic of the compiler with 101 like any Rust program.
+const EXIT_COMPILE_ERRORS: i32 = 1;
+
+/// The exit status when the driver fails around the compilation: a bad path or
+/// option, a target LLVM doesn't know, or a missing clang or opt.
+const EXIT_DRIVER_ERROR: i32 = 2;
+
 fn main() {
     let mut parameters: Vec<String> = env::args().collect();
     let mut options: Options = Options::default();
-    let mut compile: bool = false;
 
     parameters.remove(0);
 
@@ -53,14 +84,16 @@ fn main() {
             }
 
             "-v" | "--version" => {
-                println!("{}", env!("CARGO_PKG_VERSION").bold());
+                version();
                 return;
             }
 
-            "-c" | "compile" => {
+            "-c" | "compile" | "-i" | "interpret" => {
+                options.interpret = matches!(parameter.as_str(), "-i" | "interpret");
+
                 if parameters.len() == 1 {
                     compile_help();
-                    return;
+                    process::exit(EXIT_DRIVER_ERROR);
                 }
 
                 let index: usize = parameters.len() - 1;
@@ -72,7 +105,7 @@ fn main() {
                         &format!("The path '{}' cannot be accessed.", &parameters[index]),
                     );
 
-                    return;
+                    process::exit(EXIT_DRIVER_ERROR);
                 }
 
                 if !path.is_file() {
@@ -81,7 +114,7 @@ fn main() {
                         &format!("The path '{}' ended with not a file.", &parameters[index]),
                     );
 
-                    return;
+                    process::exit(EXIT_DRIVER_ERROR);
                 }
 
                 if path.extension().is_none() {
@@ -93,7 +126,7 @@ fn main() {
                         ),
                     );
 
-                    return;
+                    process::exit(EXIT_DRIVER_ERROR);
                 }
 
                 if path.extension().unwrap() != "th" {
@@ -105,7 +138,7 @@ fn main() {
                         ),
                     );
 
-                    return;
+                    process::exit(EXIT_DRIVER_ERROR);
                 }
 
                 for i in 1..parameters.len() - 1 {
@@ -116,6 +149,9 @@ fn main() {
                         "--name" | "-n" => {
                             options.name = parameters[i + 1].clone();
                         }
+                        "--clang" | "-clang" => {
+                            options.clang_path = parameters[i + 1].clone();
+                        }
                         "--target" | "-t" => {
                             if TARGETS.contains(&parameters[i + 1].as_str()) {
                                 options.target_triple = TargetTriple::create(&parameters[i + 1]);
@@ -128,7 +164,24 @@ fn main() {
                                 &parameters[i + 1]
                             ));
 
-                            return;
+                            process::exit(EXIT_DRIVER_ERROR);
+                        }
+                        "--target-json" | "-target-json" => {
+                            match TargetSpec::from_file(Path::new(&parameters[i + 1])) {
+                                Ok(spec) => {
+                                    options.target_triple = TargetTriple::create(&spec.triple);
+                                    options.target_cpu = spec.cpu;
+                                    options.target_features = spec.features;
+                                    options.data_layout = Some(spec.data_layout);
+                                    options.code_model = spec.code_model;
+                                    options.reloc_mode = spec.reloc_mode;
+                                }
+
+                                Err(error) => {
+                                    logging::log(logging::LogType::ERROR, &error);
+                                    process::exit(EXIT_DRIVER_ERROR);
+                                }
+                            }
                         }
                         "--optimization" | "-opt" => match parameters[i + 1].as_str() {
                             "none" => {
@@ -171,7 +224,7 @@ fn main() {
                             "default" => {
                                 options.reloc_mode = RelocMode::Default;
                             }
-                            "dynamic" => {
+                            "dynamic" | "dynamic-no-pic" => {
                                 options.reloc_mode = RelocMode::DynamicNoPic;
                             }
                             "pic" => {
@@ -180,14 +233,142 @@ fn main() {
                             "static" => {
                                 options.reloc_mode = RelocMode::Static;
                             }
-                            _ => {
-                                options.reloc_mode = RelocMode::Default;
+                            mode => {
+                                logging::log(logging::LogType::ERROR, &format!(
+                                    "Unknown relocation model '{}'. Expected 'default', 'static', 'pic' or 'dynamic-no-pic'.",
+                                    mode
+                                ));
+
+                                process::exit(EXIT_DRIVER_ERROR);
                             }
                         },
 
                         "--emit-llvm" | "-emit-llvm" => {
                             options.emit_llvm = true;
                         }
+                        "--emit-asm" | "-emit-asm" => {
+                            options.emit_assembly = true;
+                        }
+                        "--dump-timings-json" | "-dump-timings-json" => {
+                            options.dump_timings_json = true;
+                        }
+                        "--force" | "-force" => {
+                            options.force = true;
+                        }
+                        "--print-passes" | "-print-passes" => {
+                            options.print_passes = true;
+                        }
+                        "--print-ir-after-pass" | "-print-ir-after-pass" => {
+                            options.print_ir_after_pass = true;
+                        }
+                        "--dump-tokens" | "-dump-tokens" => {
+                            options.dump_tokens = true;
+                        }
+                        "--dump-deps" | "-dump-deps" => {
+                            options.dump_deps = true;
+                        }
+                        "--dump-used-libc" | "-dump-used-libc" => {
+                            options.dump_used_libc = true;
+                        }
+                        "--dump-cfg" | "-dump-cfg" => {
+                            options.dump_cfg = true;
+                        }
+                        "--dump-metrics" | "-dump-metrics" => {
+                            options.dump_metrics = true;
+                        }
+                        "--stats" | "-stats" => {
+                            options.stats = true;
+                        }
+                        "--emit-dep-info" | "-emit-dep-info" => {
+                            options.emit_dep_info = true;
+                        }
+                        "--abort" | "-abort" => match parameters[i + 1].as_str() {
+                            "trap" => {
+                                options.abort = Some(Abort::Trap);
+                            }
+                            name => {
+                                options.abort = Some(Abort::Function(name.to_string()));
+                            }
+                        },
+                        "--float-format" | "-float-format" => match parameters[i + 1].as_str() {
+                            "shortest" => {
+                                options.float_format = FloatFormat::Shortest;
+                            }
+                            "fixed" => {
+                                options.float_format = FloatFormat::Fixed;
+                            }
+                            format => {
+                                logging::log(
+                                        logging::LogType::ERROR,
+                                        &format!(
+                                            "Unknown float format '{}'. Expected 'shortest' or 'fixed'.",
+                                            format
+                                        ),
+                                    );
+
+                                process::exit(EXIT_DRIVER_ERROR);
+                            }
+                        },
+                        "--frame-pointer" | "-frame-pointer" => {
+                            options.frame_pointer = true;
+                        }
+                        "--mangle" | "-mangle" => {
+                            options.mangle = true;
+                        }
+                        "--strip" | "-strip" => {
+                            options.strip = true;
+                        }
+                        "--sanitize" | "-sanitize" => {
+                            for sanitizer in parameters[i + 1].split(',') {
+                                match sanitizer {
+                                    "address" | "thread" | "memory" => {
+                                        if !options.sanitizers.iter().any(|s| s == sanitizer) {
+                                            options.sanitizers.push(sanitizer.to_string());
+                                        }
+                                    }
+                                    "undefined" => {
+                                        logging::log(
+                                            logging::LogType::ERROR,
+                                            "The 'undefined' sanitizer checks are inserted by the C frontend, there is nothing to check in Thrush code.",
+                                        );
+
+                                        process::exit(EXIT_DRIVER_ERROR);
+                                    }
+                                    _ => {
+                                        logging::log(
+                                            logging::LogType::ERROR,
+                                            &format!(
+                                                "The sanitizer '{}' is not supported, use 'address', 'thread' or 'memory'.",
+                                                sanitizer
+                                            ),
+                                        );
+
+                                        process::exit(EXIT_DRIVER_ERROR);
+                                    }
+                                }
+                            }
+                        }
+                        "--fail-fast" | "-fail-fast" => {
+                            options.fail_fast = true;
+                        }
+                        "--max-errors" | "-max-errors" => {
+                            match parameters[i + 1].parse::<usize>() {
+                                Ok(max_errors) if max_errors > 0 => {
+                                    options.max_errors = max_errors;
+                                }
+                                _ => {
+                                    logging::log(
+                                        logging::LogType::ERROR,
+                                        &format!(
+                                            "The maximum of errors '{}' must be a positive number.",
+                                            &parameters[i + 1]
+                                        ),
+                                    );
+
+                                    process::exit(EXIT_DRIVER_ERROR);
+                                }
+                            }
+                        }
                         "--static" | "-s" => {
                             options.linking = Linking::Static;
                         }
@@ -197,6 +378,10 @@ fn main() {
                         "--build" | "-b" => {
                             options.build = true;
                         }
+                        "--run" | "-run" => {
+                            options.build = true;
+                            options.run = true;
+                        }
 
                         _ => continue,
                     }
@@ -214,20 +399,26 @@ fn main() {
 
                 options.path = Path::new(&parameters[index]).to_path_buf();
 
-                compile = true;
-
                 break;
             }
 
-            "-i" | "interpret" => {}
-
             _ => {
                 help();
-                return;
+                process::exit(EXIT_DRIVER_ERROR);
             }
         }
     }
 
+    if options.print_passes {
+        options
+            .optimization
+            .passes()
+            .iter()
+            .for_each(|pass| logging::log(logging::LogType::INFO, pass));
+
+        return;
+    }
+
     match NAME.lock().unwrap().as_str() == "main.th" {
         true => {
             options.is_main = true;
@@ -239,19 +430,66 @@ fn main() {
 
     let origin_content: String = read_to_string(&options.path).unwrap_or_else(|error| {
         logging::log(logging::LogType::ERROR, error.to_string().as_str());
-        panic!()
+        process::exit(EXIT_DRIVER_ERROR);
     });
 
+    let cache_time: Instant = Instant::now();
+    let cache: BuildCache = BuildCache::new(&options, &origin_content);
+
+    if cache.is_fresh() {
+        // The only phase a cached build goes through.
+        if options.dump_timings_json {
+            dump_timings_json(&options.name, &[("cache", cache_time.elapsed())]);
+        }
+
+        if options.emit_dep_info {
+            emit_dep_info(&options, &cache.includes());
+        }
+
+        println!(
+            "\n  {} {} (cached)",
+            "Finished"
+                .custom_color(CustomColor::new(141, 141, 142))
+                .bold(),
+            PATH.lock().unwrap()
+        );
+
+        if options.run {
+            run_executable(&options);
+        }
+
+        return;
+    }
+
     let content: &[u8] = origin_content.as_bytes();
 
     let mut lexer: Lexer = Lexer::new(content);
-    let mut parser: Parser = Parser::new();
+
+    // Only the lexer runs, the included files are not resolved.
+    if options.dump_tokens {
+        match lexer.lex(options.max_errors()) {
+            Ok(tokens) => tokens
+                .iter()
+                .for_each(|token| logging::log(logging::LogType::INFO, &token.to_string())),
+            Err(_) => process::exit(EXIT_COMPILE_ERRORS),
+        }
+
+        return;
+    }
 
     Target::initialize_all(&InitializationConfig::default());
 
-    let context: Context = Context::create();
-    let builder: Builder<'_> = context.create_builder();
-    let module: Module<'_> = context.create_module(&options.name);
+    // Checked before the frontend runs, an unsupported target would only fail after it.
+    let machine: TargetMachine = match create_target_machine(&options) {
+        Ok(machine) => machine,
+        Err(error) => {
+            if let ThrushError::Compile(error) = error {
+                logging::log(logging::LogType::ERROR, &error);
+            }
+
+            process::exit(EXIT_DRIVER_ERROR);
+        }
+    };
 
     println!(
         "\n{} {}",
@@ -261,46 +499,121 @@ fn main() {
         PATH.lock().unwrap()
     );
 
-    let tokens: Result<&[Token], String> = lexer.lex();
+    let mut timings: Vec<(&str, Duration)> = Vec::with_capacity(4);
+
+    let mut includes: Vec<PathBuf> = Vec::new();
+
+    let lexer_time: Instant = Instant::now();
+    let tokens: Result<Vec<Token>, String> = lexer.lex(options.max_errors()).and_then(|tokens| {
+        resolve_includes(
+            &options.path,
+            tokens,
+            options.max_errors(),
+            &mut vec![options
+                .path
+                .canonicalize()
+                .unwrap_or_else(|_| options.path.clone())],
+            &mut includes,
+        )
+    });
+    timings.push(("lexer", lexer_time.elapsed()));
 
-    match tokens {
+    match &tokens {
         Ok(tokens) => {
-            parser.tokens = Some(tokens);
+            let mut parser: Parser = Parser::new();
+
+            // The instructions borrow from the tokens and the parser, so the module
+            // that is built from them is created after both.
+            let context: Context = Context::create();
+            let builder: Builder<'_> = context.create_builder();
+            let module: Module<'_> = context.create_module(&options.name);
+
+            parser.tokens = Some(tokens.as_slice());
             parser.options = Some(&options);
 
+            let parser_time: Instant = Instant::now();
             let instructions: Result<&[Instruction<'_>], String> = parser.start();
+            timings.push(("parser", parser_time.elapsed()));
 
             match instructions {
                 Ok(instructions) => {
                     module.set_triple(&options.target_triple);
 
-                    let opt: OptimizationLevel = match &options.optimization {
-                        Opt::None => OptimizationLevel::None,
-                        Opt::Low => OptimizationLevel::Default,
-                        Opt::Mid => OptimizationLevel::Less,
-                        Opt::Mcqueen => OptimizationLevel::Aggressive,
-                    };
+                    match &options.data_layout {
+                        Some(data_layout) => module
+                            .set_data_layout(&TargetData::create(data_layout).get_data_layout()),
+                        None => {
+                            module.set_data_layout(&machine.get_target_data().get_data_layout())
+                        }
+                    }
 
-                    let machine: TargetMachine = Target::from_triple(&options.target_triple)
-                        .unwrap()
-                        .create_target_machine(
-                            &options.target_triple,
-                            "",
-                            "",
-                            opt,
-                            options.reloc_mode,
-                            options.code_model,
-                        )
-                        .unwrap();
-
-                    module.set_data_layout(&machine.get_target_data().get_data_layout());
-
-                    Compiler::compile(&module, &builder, &context, instructions);
-
-                    if compile {
-                        FileBuilder::new(&options, &module).build();
-                    } else {
-                        todo!()
+                    let codegen_time: Instant = Instant::now();
+
+                    if let Err(error) = Compiler::compile(
+                        &module,
+                        &builder,
+                        &context,
+                        instructions,
+                        options.abort(),
+                        options.frame_pointer,
+                        options.float_format,
+                        options.mangle,
+                        options.emit_llvm,
+                    ) {
+                        Diagnostic::new(&PATH.lock().unwrap()).report(&error);
+
+                        logging::log(
+                            logging::LogType::ERROR,
+                            "Compilation proccess ended with errors.",
+                        );
+
+                        process::exit(EXIT_COMPILE_ERRORS);
+                    }
+
+                    timings.push(("codegen", codegen_time.elapsed()));
+
+                    set_target_attributes(&context, &module, &options);
+
+                    if options.dump_deps {
+                        dump_deps(&module);
+                        return;
+                    }
+
+                    if options.dump_used_libc {
+                        dump_used_libc(&module);
+                        return;
+                    }
+
+                    if options.dump_cfg {
+                        dump_cfg(&options.name, &module);
+                    }
+
+                    if options.dump_metrics {
+                        dump_metrics(&module);
+                    }
+
+                    let build_time: Instant = Instant::now();
+
+                    if !options.interpret {
+                        if !FileBuilder::new(&options, &module, &machine).build() {
+                            process::exit(EXIT_DRIVER_ERROR);
+                        }
+
+                        cache.save(&includes);
+
+                        if options.emit_dep_info {
+                            emit_dep_info(&options, &includes);
+                        }
+                    }
+
+                    timings.push(("build", build_time.elapsed()));
+
+                    if options.dump_timings_json {
+                        dump_timings_json(&options.name, &timings);
+                    }
+
+                    if options.stats {
+                        dump_stats(&module, &timings);
                     }
 
                     println!(
@@ -310,20 +623,518 @@ fn main() {
                             .bold(),
                         PATH.lock().unwrap()
                     );
+
+                    if options.run {
+                        run_executable(&options);
+                    }
+
+                    if options.interpret {
+                        interpret(&module, &options);
+                    }
                 }
 
                 Err(msg) => {
                     logging::log(logging::LogType::ERROR, &msg);
+                    process::exit(EXIT_COMPILE_ERRORS);
                 }
             }
         }
 
         Err(msg) => {
             logging::log(logging::LogType::ERROR, &msg);
+            process::exit(EXIT_COMPILE_ERRORS);
         }
     }
 }
 
+/// LLVM only knows the targets it was built with, a triple outside of them is
+/// reported by name with the reason LLVM gives.
+fn create_target_machine(options: &Options) -> Result<TargetMachine, ThrushError> {
+    let opt: OptimizationLevel = optimization_level(&options.optimization);
+
+    let target: Target = Target::from_triple(&options.target_triple).map_err(|error| {
+        ThrushError::Compile(format!(
+            "The target '{}' is not supported by this build of LLVM: {}",
+            options.target_triple.as_str().to_string_lossy(),
+            error.to_string().trim_end()
+        ))
+    })?;
+
+    target
+        .create_target_machine(
+            &options.target_triple,
+            &options.target_cpu,
+            &options.target_features,
+            opt,
+            options.reloc_mode,
+            options.code_model,
+        )
+        .ok_or_else(|| {
+            ThrushError::Compile(format!(
+                "Cannot create a target machine for '{}' with the CPU '{}' and the features '{}'.",
+                options.target_triple.as_str().to_string_lossy(),
+                options.target_cpu,
+                options.target_features
+            ))
+        })
+}
+
+fn optimization_level(optimization: &Opt) -> OptimizationLevel {
+    match optimization {
+        Opt::None => OptimizationLevel::None,
+        Opt::Low => OptimizationLevel::Default,
+        Opt::Mid => OptimizationLevel::Less,
+        Opt::Mcqueen => OptimizationLevel::Aggressive,
+    }
+}
+
+/// Tags every defined function with the CPU and features of the target, the
+/// object file is built from bitcode so this is how they reach the backend.
+fn set_target_attributes(context: &Context, module: &Module, options: &Options) {
+    let naked: u32 = Attribute::get_named_enum_kind_id("naked");
+
+    module
+        .get_functions()
+        .filter(|function| function.count_basic_blocks() > 0)
+        .for_each(|function| {
+            // The sanitizer passes only instrument the functions that ask for it, a naked
+            // function is left alone since its assembly has no room for the checks.
+            if function
+                .get_enum_attribute(AttributeLoc::Function, naked)
+                .is_none()
+            {
+                options.sanitizers.iter().for_each(|sanitizer| {
+                    function.add_attribute(
+                        AttributeLoc::Function,
+                        context.create_enum_attribute(
+                            Attribute::get_named_enum_kind_id(&format!("sanitize_{}", sanitizer)),
+                            0,
+                        ),
+                    );
+                });
+            }
+
+            if !options.target_cpu.is_empty() {
+                function.add_attribute(
+                    AttributeLoc::Function,
+                    context.create_string_attribute("target-cpu", &options.target_cpu),
+                );
+            }
+
+            if !options.target_features.is_empty() {
+                function.add_attribute(
+                    AttributeLoc::Function,
+                    context.create_string_attribute("target-features", &options.target_features),
+                );
+            }
+        });
+}
+
+/// Spawns the executable that was just built with the terminal's stdio and
+/// exits with its status, so the compiler can stand in for the program.
+fn run_executable(options: &Options) {
+    let executable: PathBuf = Path::new(".").join(options.output_path());
+
+    println!(
+        "  {} {}\n",
+        "Running"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        executable.display()
+    );
+
+    match Command::new(&executable).status() {
+        // A program killed by a signal has no exit code, report it as a failure.
+        Ok(status) => process::exit(status.code().unwrap_or(1)),
+        Err(error) => {
+            logging::log(
+                logging::LogType::ERROR,
+                &format!("Cannot run '{}': {}.", executable.display(), error),
+            );
+
+            process::exit(EXIT_DRIVER_ERROR);
+        }
+    }
+}
+
+/// Runs 'main' in memory with LLVM's JIT and exits with its return code, so a
+/// script runs without writing an object file or calling clang.
+fn interpret(module: &Module, options: &Options) {
+    if let Err(ThrushError::Compile(error)) = options.validate() {
+        logging::log(logging::LogType::ERROR, &error);
+        process::exit(EXIT_DRIVER_ERROR);
+    }
+
+    let Some(main) = module.get_function("main") else {
+        logging::log(
+            logging::LogType::ERROR,
+            "The interpreter needs a 'main' function to run.",
+        );
+
+        process::exit(EXIT_COMPILE_ERRORS);
+    };
+
+    ExecutionEngine::link_in_mc_jit();
+
+    let engine: ExecutionEngine = module
+        .create_jit_execution_engine(optimization_level(&options.optimization))
+        .unwrap_or_else(|error| {
+            logging::log(
+                logging::LogType::ERROR,
+                &format!(
+                    "Cannot create the JIT execution engine: {}",
+                    error.to_string().trim_end()
+                ),
+            );
+
+            process::exit(EXIT_DRIVER_ERROR);
+        });
+
+    println!(
+        "  {} {}\n",
+        "Running"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        PATH.lock().unwrap()
+    );
+
+    let path: String = PATH.lock().unwrap().clone();
+    let code: i32 = unsafe { engine.run_function_as_main(main, &[&path]) };
+
+    process::exit(code);
+}
+
+/// Prints the compiler version next to the linked LLVM and the external tools
+/// the build relies on, a missing tool is reported instead of failing.
+fn version() {
+    let (mut major, mut minor, mut patch): (u32, u32, u32) = (0, 0, 0);
+
+    unsafe { LLVMGetVersion(&mut major, &mut minor, &mut patch) };
+
+    println!("{} {}", "thrushc".bold(), env!("CARGO_PKG_VERSION").bold());
+    println!("{} {}.{}.{}", "LLVM".bold(), major, minor, patch);
+
+    [find_clang().as_str(), "opt"].iter().for_each(|tool| {
+        let version: String = Command::new(tool)
+            .arg("--version")
+            .output()
+            .ok()
+            .and_then(|output| tool_version(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_else(|| String::from("not found"));
+
+        println!("{} {}", tool.bold(), version);
+    });
+}
+
+/// Takes the word after 'version' in the output of '--version', like '18.1.3'
+/// in 'Ubuntu clang version 18.1.3 (1ubuntu1)'.
+fn tool_version(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut words: SplitWhitespace = line.split_whitespace();
+
+        words.find(|word| *word == "version")?;
+        words.next().map(String::from)
+    })
+}
+
+/// Writes the duration of every compiler phase in milliseconds to `{name}.timings.json`.
+///
+/// The schema is a flat object keyed by phase name (`lexer`, `parser`, `codegen`, `build`)
+/// always emitted in the same order, so CI tools can diff it between runs.
+fn dump_timings_json(name: &str, timings: &[(&str, Duration)]) {
+    let phases: Vec<String> = timings
+        .iter()
+        .map(|(phase, time)| format!("  \"{}\": {:.3}", phase, time.as_secs_f64() * 1000.0))
+        .collect();
+
+    if let Err(error) = write(
+        format!("{}.timings.json", name),
+        format!("{{\n{}\n}}\n", phases.join(",\n")),
+    ) {
+        logging::log(logging::LogType::ERROR, &error.to_string());
+    }
+}
+
+/// Counts what codegen produced, before the optimization passes run.
+fn module_metrics(module: &Module) -> [(&'static str, usize); 5] {
+    let mut functions: usize = 0;
+    let mut declarations: usize = 0;
+    let mut blocks: usize = 0;
+    let mut instructions: usize = 0;
+
+    module.get_functions().for_each(|function| {
+        if function.count_basic_blocks() == 0 {
+            declarations += 1;
+            return;
+        }
+
+        functions += 1;
+
+        function.get_basic_blocks().iter().for_each(|block| {
+            blocks += 1;
+
+            let mut instruction: Option<InstructionValue> = block.get_first_instruction();
+
+            while let Some(current) = instruction {
+                instructions += 1;
+                instruction = current.get_next_instruction();
+            }
+        });
+    });
+
+    [
+        ("functions", functions),
+        ("declarations", declarations),
+        ("globals", module.get_globals().count()),
+        ("basic blocks", blocks),
+        ("instructions", instructions),
+    ]
+}
+
+/// Logs the size of the module, to follow how the generated code grows.
+fn dump_metrics(module: &Module) {
+    module_metrics(module).iter().for_each(|(metric, count)| {
+        logging::log(logging::LogType::INFO, &format!("{}: {}", metric, count));
+    });
+}
+
+/// Logs the size of the module followed by the time spent in every compiler phase.
+fn dump_stats(module: &Module, timings: &[(&str, Duration)]) {
+    dump_metrics(module);
+
+    timings.iter().for_each(|(phase, time)| {
+        logging::log(
+            logging::LogType::INFO,
+            &format!("{}: {:.3} ms", phase, time.as_secs_f64() * 1000.0),
+        );
+    });
+}
+
+/// Logs every function that the module declares but doesn't define, these
+/// symbols must be provided by a library at link time.
+fn dump_deps(module: &Module) {
+    module
+        .get_functions()
+        .filter(|function| function.count_basic_blocks() == 0)
+        .for_each(|function| {
+            logging::log(
+                logging::LogType::INFO,
+                function.get_name().to_str().unwrap_or_default(),
+            );
+        });
+}
+
+/// Logs the C library symbols that codegen declared on its own, like 'printf' for
+/// `print` or 'stderr' for `eprint`, since the source never names them.
+fn dump_used_libc(module: &Module) {
+    module
+        .get_functions()
+        .filter(|function| function.count_basic_blocks() == 0)
+        .map(|function| function.get_name().to_string_lossy().into_owned())
+        .chain(
+            module
+                .get_globals()
+                .filter(|global| global.get_initializer().is_none())
+                .map(|global| global.get_name().to_string_lossy().into_owned()),
+        )
+        .for_each(|name| {
+            logging::log(logging::LogType::INFO, &name);
+        });
+}
+
+/// Writes the basic-block graph of every defined function to `{name}.cfg.dot`,
+/// one cluster per function, ready to be rendered with Graphviz.
+fn dump_cfg(name: &str, module: &Module) {
+    let mut dot: String = String::from("digraph cfg {\n  node [shape=box];\n");
+
+    module
+        .get_functions()
+        .filter(|function| function.count_basic_blocks() > 0)
+        .for_each(|function| {
+            let function_name: &str = function.get_name().to_str().unwrap_or_default();
+            let blocks: Vec<BasicBlock> = function.get_basic_blocks();
+
+            dot.push_str(&format!(
+                "  subgraph \"cluster_{0}\" {{\n    label=\"{0}\";\n",
+                function_name
+            ));
+
+            blocks.iter().enumerate().for_each(|(index, block)| {
+                dot.push_str(&format!(
+                    "    \"{0}.bb{1}\" [label=\"bb{1}\"];\n",
+                    function_name, index
+                ));
+
+                if let Some(terminator) = block.get_terminator() {
+                    (0..terminator.get_num_operands())
+                        .filter_map(|operand| terminator.get_operand(operand)?.right())
+                        .filter_map(|target| blocks.iter().position(|block| *block == target))
+                        .for_each(|target| {
+                            dot.push_str(&format!(
+                                "    \"{0}.bb{1}\" -> \"{0}.bb{2}\";\n",
+                                function_name, index, target
+                            ));
+                        });
+                }
+            });
+
+            dot.push_str("  }\n");
+        });
+
+    dot.push_str("}\n");
+
+    if let Err(error) = write(format!("{}.cfg.dot", name), dot) {
+        logging::log(logging::LogType::ERROR, &error.to_string());
+    }
+}
+
+/// Replaces every `include "file.th";` with the tokens of that file, resolved
+/// relative to the including file. Each file is included once, `stack` holds the
+/// files being included to reject cycles and `includes` collects all of them.
+fn resolve_includes(
+    path: &Path,
+    tokens: &[Token],
+    max_errors: usize,
+    stack: &mut Vec<PathBuf>,
+    includes: &mut Vec<PathBuf>,
+) -> Result<Vec<Token>, String> {
+    let mut resolved: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut index: usize = 0;
+
+    while index < tokens.len() {
+        if tokens[index].kind != TokenKind::Include {
+            resolved.push(tokens[index].clone());
+            index += 1;
+
+            continue;
+        }
+
+        let (line, span): (usize, TokenSpan) = (tokens[index].line, tokens[index].span);
+
+        let included: PathBuf = match (tokens.get(index + 1), tokens.get(index + 2)) {
+            (
+                Some(
+                    name @ Token {
+                        kind: TokenKind::String,
+                        ..
+                    },
+                ),
+                Some(Token {
+                    kind: TokenKind::SemiColon,
+                    ..
+                }),
+            ) => path
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(name.lexeme.as_ref().unwrap().trim_end_matches('\0')),
+
+            _ => {
+                return Err(report_include_error(
+                    path,
+                    ThrushError::Parse(
+                        ThrushErrorKind::SyntaxError,
+                        String::from("Syntax Error"),
+                        String::from("Expected the file to include. Like 'include \"math.th\";'."),
+                        line,
+                    ),
+                ));
+            }
+        };
+
+        index += 3;
+
+        let Ok(content) = read_to_string(&included) else {
+            return Err(report_include_error(
+                path,
+                ThrushError::Parse(
+                    ThrushErrorKind::SyntaxError,
+                    String::from("Syntax Error"),
+                    format!("Cannot read the included file '{}'.", included.display()),
+                    line,
+                ),
+            ));
+        };
+
+        let canonical: PathBuf = included.canonicalize().unwrap_or_else(|_| included.clone());
+
+        if stack.contains(&canonical) {
+            return Err(report_include_error(
+                path,
+                ThrushError::Parse(
+                    ThrushErrorKind::CircularInclude,
+                    String::from("Circular Include"),
+                    format!(
+                        "The file '{}' ends up including itself, remove the cycle.",
+                        included.display()
+                    ),
+                    line,
+                ),
+            ));
+        }
+
+        if includes.contains(&canonical) {
+            continue;
+        }
+
+        // The lexer reports its errors against the file in 'PATH'.
+        let previous: String =
+            mem::replace(&mut *PATH.lock().unwrap(), included.display().to_string());
+        let mut lexer: Lexer = Lexer::new(content.as_bytes());
+        *PATH.lock().unwrap() = previous;
+
+        let tokens: Vec<Token> = lexer.lex(max_errors)?.to_vec();
+
+        stack.push(canonical.clone());
+        let nested: Result<Vec<Token>, String> = resolve_includes(
+            &included,
+            &tokens[..tokens.len() - 1],
+            max_errors,
+            stack,
+            includes,
+        );
+        stack.pop();
+
+        includes.push(canonical);
+
+        // Later diagnostics only know the including file, so point them at the include.
+        resolved.extend(nested?.into_iter().map(|token| Token {
+            line,
+            span,
+            ..token
+        }));
+    }
+
+    Ok(resolved)
+}
+
+fn report_include_error(path: &Path, error: ThrushError) -> String {
+    Diagnostic::new(&path.display().to_string()).report(&error);
+    String::from("Compilation proccess ended with errors.")
+}
+
+/// Writes a Make-style `{name}.d` file with the output as target and the
+/// compiled sources as prerequisites, so build systems know when to rebuild.
+fn emit_dep_info(options: &Options, includes: &[PathBuf]) {
+    let mut sources: Vec<String> = vec![options.path.display().to_string()];
+
+    sources.extend(includes.iter().map(|include| include.display().to_string()));
+
+    let mut rule: String = format!(
+        "{}: {}\n",
+        options.output_path().display(),
+        sources.join(" ")
+    );
+
+    // Phony targets keep Make going when a source file is deleted.
+    sources
+        .iter()
+        .for_each(|source| rule.push_str(&format!("\n{}:\n", source)));
+
+    if let Err(error) = write(format!("{}.d", options.name), rule) {
+        logging::log(logging::LogType::ERROR, &error.to_string());
+    }
+}
+
 fn help() {
     println!(
         "\n{}\n",
@@ -358,7 +1169,7 @@ fn help() {
             .custom_color(CustomColor::new(141, 141, 142))
             .bold(),
         "-v".custom_color(CustomColor::new(141, 141, 142)).bold(),
-        "Show the version.".bold()
+        "Show the version of the compiler, LLVM, clang and opt.".bold()
     );
 
     println!(
@@ -370,7 +1181,7 @@ fn help() {
         "-i [--flags] [file]"
             .custom_color(CustomColor::new(141, 141, 142))
             .bold(),
-        "Interpret the code provided using the Interpreter.".bold()
+        "Run the code provided in memory with the JIT, without building it.".bold()
     );
 
     println!(
@@ -434,6 +1245,18 @@ fn compile_help() {
         "Name of the executable (Compiler dispatches it).".bold()
     );
 
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--clang [path]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-clang [path]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "The clang that compiles and links the output, 'clang-18' or 'clang' by default.".bold()
+    );
+
     println!(
         "{} ({} | {}) {}",
         "•".bold(),
@@ -446,6 +1269,18 @@ fn compile_help() {
         "Target architecture for the Compiler or Interpreter.".bold()
     );
 
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--target-json [file]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-target-json [file]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Configure a custom target from a JSON spec (triple, data layout, CPU, features).".bold()
+    );
+
     println!(
         "{} ({} | {}) {}",
         "•".bold(),
@@ -470,6 +1305,248 @@ fn compile_help() {
         "Compile the code to LLVM IR.".bold()
     );
 
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--emit-asm"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-emit-asm"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Compile the code to assembly for the target.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--dump-timings-json"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-dump-timings-json"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Write the time spent in every compiler phase to a JSON file.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--dump-metrics"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-dump-metrics"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Print the number of functions, globals, blocks and instructions.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--stats"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-stats"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Print the metrics of '--dump-metrics' and the time spent in every phase.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--force"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-force"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Recompile the file even if it has not changed since the last build.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--print-ir-after-pass"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-print-ir-after-pass"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Print the LLVM IR after the optimization passes have run.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--print-passes"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-print-passes"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "List the optimization passes for the chosen level and exit.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--dump-tokens"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-dump-tokens"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Print the tokens of the file with their line and byte span, and exit.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--dump-deps"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-dump-deps"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "List the external functions that the code needs at link time.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--dump-used-libc"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-dump-used-libc"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "List the C library functions and globals that the compiler declared implicitly, and exit."
+            .bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--dump-cfg"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-dump-cfg"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Write the control flow graph of every function to a Graphviz file.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--emit-dep-info"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-emit-dep-info"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Write a Make-style dependency file listing the compiled sources.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--abort [trap | function]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-abort [trap | function]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Call 'llvm.trap' or the named function when a runtime check fails.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--float-format [shortest | fixed]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-float-format [shortest | fixed]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Print floats in their shortest form with '%g', the default, or with six decimals with '%f'."
+            .bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--frame-pointer"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-frame-pointer"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Keep the frame pointer in every function for profilers and debuggers.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--mangle"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-mangle"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Mangle the names of private functions with the name of their file.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--strip"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-strip"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Remove the symbol table from the linked executable.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--sanitize [address | thread | memory]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-sanitize [address | thread | memory]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Instrument the code and link the runtime of a sanitizer.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--fail-fast"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-fail-fast"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Stop at the first error instead of collecting them.".bold()
+    );
+
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--max-errors [number]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "-max-errors [number]"
+            .custom_color(CustomColor::new(141, 141, 142))
+            .bold(),
+        "Collect up to this many errors per phase before stopping, 50 by default.".bold()
+    );
+
     println!(
         "{} ({} | {}) {}",
         "•".bold(),
@@ -500,6 +1577,14 @@ fn compile_help() {
         "Compile the code into executable.".bold()
     );
 
+    println!(
+        "{} ({} | {}) {}",
+        "•".bold(),
+        "--run".custom_color(CustomColor::new(141, 141, 142)).bold(),
+        "-run".custom_color(CustomColor::new(141, 141, 142)).bold(),
+        "Build the executable, run it and exit with its status.".bold()
+    );
+
     println!(
         "{} ({} | {}) {}",
         "•".bold(),
@@ -517,7 +1602,7 @@ fn compile_help() {
         "-reloc [mode]"
             .custom_color(CustomColor::new(141, 141, 142))
             .bold(),
-        "Indicate how references to memory addresses are handled.".bold()
+        "Relocation model: 'default', 'static' for static executables, 'pic' for shared libraries or 'dynamic-no-pic'.".bold()
     );
 
     println!(
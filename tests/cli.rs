@@ -0,0 +1,330 @@
+//! Runs the 'thrushc' binary on small programs, for what only a run shows: what
+//! the program prints and to which stream, the warnings and the exit code.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// A directory with a 'main.th' entry file, removed with everything the compiler
+/// wrote into it.
+struct Project {
+    dir: PathBuf,
+}
+
+impl Project {
+    fn new(name: &str, source: &str) -> Self {
+        let dir: PathBuf = env::temp_dir().join(format!("thrush-cli-{}", name));
+
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.th"), source).unwrap();
+
+        Self { dir }
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_thrushc"))
+            .args(args)
+            .current_dir(&self.dir)
+            .output()
+            .unwrap()
+    }
+}
+
+impl Drop for Project {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn interpret_exits_with_the_return_code_of_main() {
+    let project: Project = Project::new("interpret", "fn main() {\n    return 42;\n}");
+
+    let output: Output = project.run(&["interpret", "main.th"]);
+
+    assert_eq!(output.status.code(), Some(42), "{:?}", output);
+
+    // The literal is returned at the width of the exit code.
+    let output: Output = project.run(&["compile", "--emit-llvm", "main.th"]);
+    let ir: String = fs::read_to_string(project.dir.join("main.ll")).unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(ir.contains("ret i32 42"), "{}", ir);
+}
+
+#[test]
+fn a_compile_error_exits_with_1() {
+    let project: Project = Project::new("compile-error", "fn main() {\n    let x = 1\n}");
+
+    let output: Output = project.run(&["compile", "main.th"]);
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+}
+
+#[test]
+fn a_missing_file_exits_with_2() {
+    let project: Project = Project::new("missing-file", "fn main() {\n    return 0;\n}");
+
+    let output: Output = project.run(&["compile", "missing.th"]);
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+}
+
+#[test]
+fn an_unsupported_target_exits_with_2() {
+    let project: Project = Project::new("unsupported-target", "fn main() {\n    return 0;\n}");
+
+    let output: Output = project.run(&["compile", "--target", "no-such-target", "main.th"]);
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+}
+
+#[test]
+fn code_after_a_return_is_a_warning() {
+    let project: Project = Project::new(
+        "unreachable",
+        "fn main() {\n    println(\"live\");\n    return;\n    println(\"dead\");\n}",
+    );
+
+    let output: Output = project.run(&["compile", "--emit-llvm", "main.th"]);
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+    let ir: String = fs::read_to_string(project.dir.join("main.ll")).unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(
+        stdout.contains("Unreachable code at line 4, it comes after a 'return' or a 'break'"),
+        "{}",
+        stdout
+    );
+    assert!(!ir.contains("dead"), "{}", ir);
+}
+
+#[test]
+fn a_naked_function_can_be_called() {
+    let project: Project = Project::new(
+        "naked",
+        "@naked fn nothing() {\n    unsafe asm(\"ret\");\n}\n\nfn main() {\n    nothing();\n    return 7;\n}",
+    );
+
+    let output: Output = project.run(&["interpret", "main.th"]);
+
+    assert_eq!(output.status.code(), Some(7), "{:?}", output);
+}
+
+#[test]
+fn a_static_variable_keeps_its_value_across_calls() {
+    let project: Project = Project::new(
+        "static",
+        "fn next() i32 {\n    static count i32 = 0;\n    count = count + 1;\n    return count;\n}\n\nfn main() {\n    next();\n    next();\n    return next();\n}",
+    );
+
+    let output: Output = project.run(&["interpret", "main.th"]);
+
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+}
+
+#[test]
+fn eprint_writes_to_stderr() {
+    let project: Project = Project::new(
+        "eprint",
+        "fn main() {\n    println(\"out\");\n    eprintln(\"err\");\n    eprint(\"!\");\n}",
+    );
+
+    let output: Output = project.run(&["interpret", "main.th"]);
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr: String = String::from_utf8_lossy(&output.stderr).to_string();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.ends_with("out\n"), "{}", stdout);
+    assert!(!stdout.contains("err"), "{}", stdout);
+    assert_eq!(stderr, "err\n!");
+}
+
+#[test]
+fn a_target_missing_from_llvm_is_named_in_the_error() {
+    let project: Project = Project::new("missing-target", "fn main() {\n    return 0;\n}");
+
+    // A known triple whose backend is experimental, so LLVM isn't built with it.
+    let output: Output = project.run(&[
+        "compile",
+        "--target",
+        "csky-unknown-linux-gnuabiv2",
+        "main.th",
+    ]);
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert!(
+        stdout.contains(
+            "The target 'csky-unknown-linux-gnuabiv2' is not supported by this build of LLVM"
+        ),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn an_address_round_trips_through_an_integer() {
+    let project: Project = Project::new(
+        "pointer-cast",
+        "fn main() {\n    let n u64 = 4096;\n    n = 8192;\n    let p ptr = n as ptr;\n    let m u64 = p as u64;\n    let low u32 = p as u32;\n    println(m, low);\n}",
+    );
+
+    let output: Output = project.run(&["interpret", "main.th"]);
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.ends_with("8192 8192\n"), "{}", stdout);
+    assert!(
+        stdout.contains("Converting a pointer to 'u32' drops the upper bits of the address"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn floats_are_printed_in_their_shortest_form() {
+    let project: Project = Project::new(
+        "float-format",
+        "fn main() {\n    println(1.0, 0.25, 100000000000000000000.0, -3.5);\n}",
+    );
+
+    let output: Output = project.run(&["interpret", "main.th"]);
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout.ends_with("1 0.25 1e+20 -3.5\n"), "{}", stdout);
+
+    let output: Output = project.run(&["interpret", "--float-format", "fixed", "main.th"]);
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(
+        stdout.ends_with("1.000000 0.250000 100000000000000000000.000000 -3.500000\n"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn a_file_included_twice_is_compiled_once() {
+    let project: Project = Project::new(
+        "include-once",
+        "include \"a.th\";\ninclude \"b.th\";\n\nfn main() {\n    shared();\n}",
+    );
+
+    fs::write(project.dir.join("a.th"), "include \"shared.th\";\n").unwrap();
+    fs::write(project.dir.join("b.th"), "include \"shared.th\";\n").unwrap();
+    fs::write(project.dir.join("shared.th"), "fn shared() {\n}\n").unwrap();
+
+    let output: Output = project.run(&["compile", "--emit-llvm", "main.th"]);
+    let ir: String = fs::read_to_string(project.dir.join("main.ll")).unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(ir.matches("@shared(").count(), 2, "{}", ir);
+}
+
+#[test]
+fn a_circular_include_is_a_compile_error() {
+    let project: Project = Project::new("circular-include", "include \"a.th\";\n\nfn main() {\n}");
+
+    fs::write(project.dir.join("a.th"), "include \"b.th\";\n").unwrap();
+    fs::write(project.dir.join("b.th"), "include \"a.th\";\n").unwrap();
+
+    let output: Output = project.run(&["compile", "main.th"]);
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert!(
+        stdout.contains("The file 'a.th' ends up including itself, remove the cycle."),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn an_error_in_an_included_file_points_at_the_include() {
+    let project: Project = Project::new("include-error", "  include \"bad.th\";\n\nfn main() {\n}");
+
+    fs::write(
+        project.dir.join("bad.th"),
+        "fn helper() {\n    let x i32 = 1\n}\n",
+    )
+    .unwrap();
+
+    let output: Output = project.run(&["compile", "main.th"]);
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert!(stdout.contains("main.th:1:3"), "{}", stdout);
+}
+
+#[test]
+fn an_exported_function_can_be_called_from_c() {
+    let cc: &Path = Path::new("/usr/bin/cc");
+
+    if !cc.exists() {
+        return;
+    }
+
+    let project: Project = Project::new("export", "");
+
+    fs::write(
+        project.dir.join("math.th"),
+        "@export fn add(a :: i32, b :: i32) i32 {\n    return a + b;\n}",
+    )
+    .unwrap();
+    fs::write(
+        project.dir.join("caller.c"),
+        "#include <stdio.h>\n\nint add(int a, int b);\n\nint main(void) {\n    printf(\"%d\\n\", add(40, 2));\n    return 0;\n}\n",
+    )
+    .unwrap();
+
+    let output: Output = project.run(&["compile", "--lib", "math.th"]);
+
+    assert!(output.status.success(), "{:?}", output);
+
+    let output: Output = Command::new(cc)
+        .args(["caller.c", "main.o", "-o", "caller"])
+        .current_dir(&project.dir)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+
+    let output: Output = Command::new(project.dir.join("caller")).output().unwrap();
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "42\n");
+}
+
+#[test]
+fn only_a_windows_export_goes_in_the_dll_export_table() {
+    let project: Project = Project::new("dllexport", "");
+
+    fs::write(
+        project.dir.join("math.th"),
+        "@export fn add(a :: i32, b :: i32) i32 {\n    return a + b;\n}",
+    )
+    .unwrap();
+
+    for (target, dllexport) in [
+        ("x86_64-unknown-linux-gnu", false),
+        ("x86_64-pc-windows-msvc", true),
+    ] {
+        let output: Output = project.run(&[
+            "compile",
+            "--target",
+            target,
+            "--emit-llvm",
+            "--force",
+            "math.th",
+        ]);
+        let ir: String = fs::read_to_string(project.dir.join("main.ll")).unwrap();
+
+        assert!(output.status.success(), "{:?}", output);
+        assert_eq!(ir.contains("dllexport i32 @add("), dllexport, "{}", ir);
+    }
+}